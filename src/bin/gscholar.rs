@@ -0,0 +1,36 @@
+// Command line front end. The only command so far is the interactive
+// browser of the tui feature:
+//
+//   gscholar tui "graph isomorphism"
+
+use std::process::ExitCode;
+
+use gscholar::scholar::Client;
+
+const USAGE: &str = "usage: gscholar tui <query>";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let query = match args.as_slice() {
+        [command, query @ ..] if command == "tui" && !query.is_empty() => query.join(" "),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let client = match Client::builder().build() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("gscholar: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match gscholar::tui::run(&client, &query).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("gscholar: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,1519 @@
+// Result sets written out for people, eg: a literature scan to share as a
+// single file.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::normalize::TextNormalizer;
+use crate::scholar::ScholarResult;
+
+// A column of the report table, in the order given to ReportOptions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    // linked to the result's link when it has one
+    Title,
+    Authors,
+    Venue,
+    Year,
+    CitedBy,
+    // collapsed in its cell, expanded on click
+    Abstract,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    // heading and <title> of the page. Default = "Google Scholar results"
+    pub title: String,
+    // paragraph under the heading, eg: the purpose of the scan
+    pub notes: Option<String>,
+    // where the results come from, eg: ("Query", "deep learning") and
+    // ("Retrieved", "2024-03-05"), listed at the top
+    pub provenance: Vec<(String, String)>,
+    // Default = every column
+    pub columns: Vec<Column>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            title: "Google Scholar results".to_string(),
+            notes: None,
+            provenance: Vec::new(),
+            columns: vec![
+                Column::Title,
+                Column::Authors,
+                Column::Venue,
+                Column::Year,
+                Column::CitedBy,
+                Column::Abstract,
+            ],
+        }
+    }
+}
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;width:100%}\
+th,td{border-bottom:1px solid #ddd;padding:.4em;text-align:left;vertical-align:top}\
+th{cursor:pointer;background:#f4f4f4;user-select:none}\
+th[data-order=asc]::after{content:\" \\25B2\"}\
+th[data-order=desc]::after{content:\" \\25BC\"}\
+td.num{text-align:right}\
+dl.provenance{display:grid;grid-template-columns:max-content auto;gap:.2em 1em}\
+dl.provenance dt{font-weight:bold}\
+details summary{cursor:pointer;color:#555}";
+
+// sorts on a click on a header: by data-sort when the cell has one (numbers),
+// by text otherwise. Empty cells go last.
+const SCRIPT: &str = "document.querySelectorAll('th').forEach(function(th,col){\
+th.addEventListener('click',function(){\
+var tbody=th.closest('table').tBodies[0];\
+var asc=th.dataset.order!=='asc';\
+th.parentNode.querySelectorAll('th').forEach(function(h){delete h.dataset.order});\
+th.dataset.order=asc?'asc':'desc';\
+var key=function(row){var cell=row.cells[col];\
+var v=cell.dataset.sort!==undefined?cell.dataset.sort:cell.textContent.trim();\
+return v===''?null:(isNaN(v)?v.toLowerCase():Number(v))};\
+Array.from(tbody.rows).sort(function(a,b){\
+var x=key(a),y=key(b);\
+if(x===null||y===null){return x===y?0:(x===null?1:-1)}\
+return (x<y?-1:x>y?1:0)*(asc?1:-1)}).forEach(function(row){tbody.appendChild(row)})})});";
+
+// A standalone html page with a table of `results`, sortable by clicking
+// the column headers, with CSS and JS inline and no external assets. Every
+// scraped text is escaped, markup in titles or abstracts shows as text. Only
+// http and https links are linked.
+pub fn to_html_report(results: &[ScholarResult], options: ReportOptions) -> String {
+    let mut html = String::new();
+    let title = escape(&options.title);
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, STYLE, title
+    );
+    if let Some(notes) = &options.notes {
+        let _ = writeln!(html, "<p class=\"notes\">{}</p>", escape(notes));
+    }
+    if !options.provenance.is_empty() {
+        html.push_str("<dl class=\"provenance\">\n");
+        for (name, value) in &options.provenance {
+            let _ = writeln!(html, "<dt>{}</dt><dd>{}</dd>", escape(name), escape(value));
+        }
+        html.push_str("</dl>\n");
+    }
+
+    html.push_str("<table>\n<thead><tr>");
+    for column in &options.columns {
+        let _ = write!(html, "<th>{}</th>", header(*column));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for result in results {
+        html.push_str("<tr>");
+        for column in &options.columns {
+            html.push_str(&cell(result, *column));
+        }
+        html.push_str("</tr>\n");
+    }
+    let _ = write!(
+        html,
+        "</tbody>\n</table>\n<script>{}</script>\n</body>\n</html>\n",
+        SCRIPT
+    );
+    html
+}
+
+fn header(column: Column) -> &'static str {
+    match column {
+        Column::Title => "Title",
+        Column::Authors => "Authors",
+        Column::Venue => "Venue",
+        Column::Year => "Year",
+        Column::CitedBy => "Cited by",
+        Column::Abstract => "Abstract",
+    }
+}
+
+fn cell(result: &ScholarResult, column: Column) -> String {
+    match column {
+        Column::Title => {
+            let title = escape(&result.title);
+            let link = result.link.trim();
+            if link.starts_with("https://") || link.starts_with("http://") {
+                format!("<td><a href=\"{}\">{}</a></td>", escape(link), title)
+            } else {
+                format!("<td>{}</td>", title)
+            }
+        }
+        Column::Authors => format!("<td>{}</td>", escape(&result.authors.join(", "))),
+        Column::Venue => format!(
+            "<td>{}</td>",
+            escape(result.venue.as_deref().unwrap_or_default())
+        ),
+        Column::Year => number_cell(result.year.map(u32::from)),
+        Column::CitedBy => number_cell(result.cited_by),
+        Column::Abstract if result.abs.trim().is_empty() => "<td></td>".to_string(),
+        Column::Abstract => format!(
+            "<td><details><summary>Show</summary>{}</details></td>",
+            escape(result.abs.trim())
+        ),
+    }
+}
+
+fn number_cell(number: Option<u32>) -> String {
+    match number {
+        Some(number) => format!("<td class=\"num\" data-sort=\"{}\">{}</td>", number, number),
+        None => "<td class=\"num\"></td>".to_string(),
+    }
+}
+
+// text and attribute values alike, html or xml
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// How to_latex_bibliography writes characters outside ASCII
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatexStyle {
+    // as they are, for inputenc utf8 or a unicode engine
+    Utf8,
+    // as accent commands, eg: "é" as "\'{e}". Characters without one are
+    // kept
+    Accents,
+}
+
+// A \begin{thebibliography} block with an \bibitem per result, keyed by
+// bibtex_key: authors, ``title'', venue, year, leaving out what the result
+// lacks. Keys repeated among the results get a "b", "c", ... suffix, see
+// unique_key.
+pub fn to_latex_bibliography(results: &[ScholarResult], style: LatexStyle) -> String {
+    let widest = "9".repeat(results.len().max(1).to_string().len());
+    let mut latex = format!("\\begin{{thebibliography}}{{{}}}\n", widest);
+    let mut emitted = HashSet::new();
+    for result in results {
+        let key = unique_key(bibtex_key(result), &mut emitted);
+
+        let mut parts = Vec::new();
+        if let Some(authors) = join_authors(&result.authors) {
+            parts.push(latex_escape(&authors, style));
+        }
+        if !result.title.is_empty() {
+            parts.push(format!("``{}''", latex_escape(&result.title, style)));
+        }
+        if let Some(venue) = cited_venue(result) {
+            parts.push(format!("\\emph{{{}}}", latex_escape(&venue, style)));
+        }
+        if let Some(year) = result.year {
+            parts.push(year.to_string());
+        }
+        let _ = writeln!(latex, "\\bibitem{{{}}} {}.", key, parts.join(", "));
+    }
+    latex.push_str("\\end{thebibliography}\n");
+    latex
+}
+
+// Options of the citation exporters, to_bibtex_with and to_ris_with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CitationOptions {
+    // cite abbreviated venues by the full title venue::expand knows for
+    // them, eg: "Commun. ACM" as "Communications of the ACM". Default = false
+    #[cfg(feature = "venue-normalize")]
+    pub expand_venues: bool,
+}
+
+impl CitationOptions {
+    // the venue cited for `result`, None when it has none
+    fn venue(&self, result: &ScholarResult) -> Option<String> {
+        let venue = cited_venue(result)?;
+        #[cfg(feature = "venue-normalize")]
+        {
+            if self.expand_venues {
+                return Some(crate::venue::expand(&venue).unwrap_or(venue));
+            }
+        }
+        Some(venue)
+    }
+}
+
+// BibTeX entries of `results`, keyed like to_latex_bibliography: an
+// @article for results with a venue, @misc for the others, with the
+// fields the result has among author, title, journal, year and url.
+// Values are braced with LaTeX's special characters escaped, non-ASCII
+// kept as UTF-8.
+pub fn to_bibtex(results: &[ScholarResult]) -> String {
+    to_bibtex_with(results, CitationOptions::default())
+}
+
+// to_bibtex with `options`, eg: to cite full venue titles
+pub fn to_bibtex_with(results: &[ScholarResult], options: CitationOptions) -> String {
+    let mut bibtex = String::new();
+    let mut emitted = HashSet::new();
+    for result in results {
+        let key = unique_key(bibtex_key(result), &mut emitted);
+        let venue = options.venue(result);
+        let kind = if venue.is_some() { "article" } else { "misc" };
+        let mut fields = Vec::new();
+        if !result.authors.is_empty() {
+            fields.push(("author", result.authors.join(" and ")));
+        }
+        if !result.title.is_empty() {
+            fields.push(("title", result.title.clone()));
+        }
+        if let Some(venue) = venue {
+            fields.push(("journal", venue));
+        }
+        if let Some(year) = result.year {
+            fields.push(("year", year.to_string()));
+        }
+        let _ = writeln!(bibtex, "@{}{{{},", kind, key);
+        for (name, value) in fields {
+            let _ = writeln!(
+                bibtex,
+                "  {} = {{{}}},",
+                name,
+                latex_escape(&value, LatexStyle::Utf8)
+            );
+        }
+        let link = result.link.trim();
+        if link.starts_with("https://") || link.starts_with("http://") {
+            // urls are read verbatim, eg: by the url package
+            let _ = writeln!(bibtex, "  url = {{{}}},", link.replace(['{', '}'], ""));
+        }
+        bibtex.push_str("}\n");
+    }
+    bibtex
+}
+
+// RIS records of `results`, as reference managers import them: JOUR for
+// results with a venue, GEN for the others, with an AU line per author and
+// the title, venue (T2), year and url the result has. Line breaks inside
+// a value are folded into spaces.
+pub fn to_ris(results: &[ScholarResult]) -> String {
+    to_ris_with(results, CitationOptions::default())
+}
+
+// to_ris with `options`, eg: to cite full venue titles
+pub fn to_ris_with(results: &[ScholarResult], options: CitationOptions) -> String {
+    let mut ris = String::new();
+    for result in results {
+        let venue = options.venue(result);
+        let mut tag = |tag: &str, value: &str| {
+            let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !value.is_empty() {
+                let _ = writeln!(ris, "{}  - {}", tag, value);
+            }
+        };
+        tag("TY", if venue.is_some() { "JOUR" } else { "GEN" });
+        for author in &result.authors {
+            tag("AU", author);
+        }
+        tag("TI", &result.title);
+        if let Some(venue) = &venue {
+            tag("T2", venue);
+        }
+        if let Some(year) = result.year {
+            tag("PY", &year.to_string());
+        }
+        let link = result.link.trim();
+        if link.starts_with("https://") || link.starts_with("http://") {
+            tag("UR", link);
+        }
+        ris.push_str("ER  - \n");
+    }
+    ris
+}
+
+// The venue as scholar shows it, None when the result has none
+fn cited_venue(result: &ScholarResult) -> Option<String> {
+    let venue = result.venue.as_deref().map(str::trim)?;
+    if venue.is_empty() {
+        return None;
+    }
+    Some(venue.to_string())
+}
+
+// `key`, or when it was already emitted the first of key + "b", "c", ...
+// that wasn't, eg: "smith2020b" for a second "smith2020" even when another
+// result's own key is "smith2020b"
+fn unique_key(key: String, emitted: &mut HashSet<String>) -> String {
+    let key = (0..)
+        .map(|n| match n {
+            0 => key.clone(),
+            n => format!("{}{}", key, suffix(n)),
+        })
+        .find(|candidate| !emitted.contains(candidate))
+        .unwrap_or(key);
+    emitted.insert(key.clone());
+    key
+}
+
+// "b" for the first repeat, ... "z", then "ba", ...
+fn suffix(mut n: u32) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push((b'a' + (n % 26) as u8) as char);
+        n /= 26;
+        if n == 0 {
+            break;
+        }
+    }
+    letters.iter().rev().collect()
+}
+
+// "A", "A and B", "A, B and C"
+fn join_authors(authors: &[String]) -> Option<String> {
+    match authors {
+        [] => None,
+        [only] => Some(only.clone()),
+        [rest @ .., last] => Some(format!("{} and {}", rest.join(", "), last)),
+    }
+}
+
+// words left out of keys when they start the title
+const KEY_STOPWORDS: &[&str] = &["a", "an", "the", "on", "of", "in", "for", "and", "to"];
+
+// A BibTeX style key as scholar makes them: first author's surname, year and
+// first significant title word, lowercase ASCII, eg: "vaswani2017attention".
+// The parts go through TextNormalizer::for_matching first, so ligatures are
+// expanded and accented letters lose their accent, whatever form scholar
+// wrote them in. "result" when all three are missing.
+pub fn bibtex_key(result: &ScholarResult) -> String {
+    let surname = result
+        .authors
+        .first()
+        .and_then(|author| author.split_whitespace().last())
+        .map(key_part)
+        .unwrap_or_default();
+    let year = result.year.map(|year| year.to_string()).unwrap_or_default();
+    let word = result
+        .title
+        .split_whitespace()
+        .map(key_part)
+        .find(|word| !word.is_empty() && !KEY_STOPWORDS.contains(&word.as_str()))
+        .unwrap_or_default();
+    let key = format!("{}{}{}", surname, year, word);
+    if key.is_empty() {
+        "result".to_string()
+    } else {
+        key
+    }
+}
+
+fn key_part(text: &str) -> String {
+    TextNormalizer::default()
+        .for_matching(text)
+        .chars()
+        .filter_map(|c| match accent(c) {
+            // the letter the command accents, eg: "e" of "\'{e}"
+            Some(command) => command.chars().rev().find(char::is_ascii_alphabetic),
+            None => Some(c),
+        })
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+// escapes the characters special to LaTeX
+fn latex_escape(text: &str, style: LatexStyle) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '_' | '#' | '$' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            c if style == LatexStyle::Accents && !c.is_ascii() => match accent(c) {
+                Some(command) => escaped.push_str(command),
+                None => escaped.push(c),
+            },
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// the accent command of common latin letters
+fn accent(c: char) -> Option<&'static str> {
+    Some(match c {
+        'á' => "\\'{a}",
+        'à' => "\\`{a}",
+        'â' => "\\^{a}",
+        'ä' => "\\\"{a}",
+        'ã' => "\\~{a}",
+        'å' => "{\\aa}",
+        'æ' => "{\\ae}",
+        'ç' => "\\c{c}",
+        'é' => "\\'{e}",
+        'è' => "\\`{e}",
+        'ê' => "\\^{e}",
+        'ë' => "\\\"{e}",
+        'í' => "\\'{\\i}",
+        'ì' => "\\`{\\i}",
+        'î' => "\\^{\\i}",
+        'ï' => "\\\"{\\i}",
+        'ñ' => "\\~{n}",
+        'ó' => "\\'{o}",
+        'ò' => "\\`{o}",
+        'ô' => "\\^{o}",
+        'ö' => "\\\"{o}",
+        'õ' => "\\~{o}",
+        'ø' => "{\\o}",
+        'ő' => "\\H{o}",
+        'ú' => "\\'{u}",
+        'ù' => "\\`{u}",
+        'û' => "\\^{u}",
+        'ü' => "\\\"{u}",
+        'ű' => "\\H{u}",
+        'ý' => "\\'{y}",
+        'ß' => "{\\ss}",
+        'č' => "\\v{c}",
+        'ć' => "\\'{c}",
+        'ě' => "\\v{e}",
+        'ł' => "{\\l}",
+        'ń' => "\\'{n}",
+        'ř' => "\\v{r}",
+        'š' => "\\v{s}",
+        'ś' => "\\'{s}",
+        'ž' => "\\v{z}",
+        'ź' => "\\'{z}",
+        'ż' => "\\.{z}",
+        'Á' => "\\'{A}",
+        'À' => "\\`{A}",
+        'Â' => "\\^{A}",
+        'Ä' => "\\\"{A}",
+        'Å' => "{\\AA}",
+        'Æ' => "{\\AE}",
+        'Ç' => "\\c{C}",
+        'É' => "\\'{E}",
+        'È' => "\\`{E}",
+        'Í' => "\\'{I}",
+        'Ñ' => "\\~{N}",
+        'Ó' => "\\'{O}",
+        'Ö' => "\\\"{O}",
+        'Ø' => "{\\O}",
+        'Ú' => "\\'{U}",
+        'Ü' => "\\\"{U}",
+        'Č' => "\\v{C}",
+        'Ł' => "{\\L}",
+        'Š' => "\\v{S}",
+        'Ž' => "\\v{Z}",
+        _ => return None,
+    })
+}
+
+// A feed of results, eg: a saved query watched for new papers, shaped like
+// RSS and Atom feeds for integrations that expect one. Serialize it with
+// Feed::to_atom, or with serde's feature to another format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feed {
+    // the query
+    pub title: String,
+    // when the results were fetched
+    pub updated: SystemTime,
+    // in the order of the results
+    pub entries: Vec<FeedEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeedEntry {
+    // ScholarResult::stable_id, the same for the paper across runs
+    pub id: String,
+    pub title: String,
+    // only http and https links are kept
+    pub link: Option<String>,
+    // the abstract, None when scholar shows none
+    pub summary: Option<String>,
+    pub authors: Vec<String>,
+    // publication year, scholar shows no finer date
+    pub published: Option<u16>,
+}
+
+// The feed of `results` found for `query`, updated now. Set Feed::updated
+// for results fetched earlier.
+pub fn results_to_feed(results: &[ScholarResult], query: &str) -> Feed {
+    let entries = results
+        .iter()
+        .map(|result| {
+            let link = result.link.trim();
+            let summary = result.abs.trim();
+            FeedEntry {
+                id: result.stable_id(),
+                title: result.title.clone(),
+                link: (link.starts_with("https://") || link.starts_with("http://"))
+                    .then(|| link.to_string()),
+                summary: (!summary.is_empty()).then(|| summary.to_string()),
+                authors: result.authors.clone(),
+                published: result.year,
+            }
+        })
+        .collect();
+    Feed {
+        title: query.to_string(),
+        updated: SystemTime::now(),
+        entries,
+    }
+}
+
+impl Feed {
+    // An Atom 1.0 document. Ids are urns: "urn:gscholar:query:" and the
+    // percent encoded query for the feed, "urn:gscholar:" and FeedEntry::id
+    // for entries. Scholar dates papers to the year only, so entries carry
+    // no <published> and are updated when the results were fetched, the
+    // feed's update time. The feed is authored by Google Scholar, as Atom
+    // needs an author for entries without one.
+    pub fn to_atom(&self) -> String {
+        let updated = rfc3339(self.updated);
+        let mut atom = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        let _ = write!(
+            atom,
+            "<feed xmlns=\"http://www.w3.org/2005/Atom\">\n<id>urn:gscholar:query:{}</id>\n<title>{}</title>\n<updated>{}</updated>\n<author><name>Google Scholar</name></author>\n",
+            url::form_urlencoded::byte_serialize(self.title.as_bytes()).collect::<String>(),
+            escape(&self.title),
+            updated
+        );
+        for entry in &self.entries {
+            let _ = write!(
+                atom,
+                "<entry>\n<id>urn:gscholar:{}</id>\n<title>{}</title>\n<updated>{}</updated>\n",
+                escape(&entry.id),
+                escape(&entry.title),
+                updated
+            );
+            for author in &entry.authors {
+                let _ = writeln!(atom, "<author><name>{}</name></author>", escape(author));
+            }
+            if let Some(link) = &entry.link {
+                let _ = writeln!(atom, "<link href=\"{}\"/>", escape(link));
+            }
+            if let Some(summary) = &entry.summary {
+                let _ = writeln!(atom, "<summary>{}</summary>", escape(summary));
+            }
+            atom.push_str("</entry>\n");
+        }
+        atom.push_str("</feed>\n");
+        atom
+    }
+}
+
+// eg: "2024-03-05T14:30:00Z", to the second
+fn rfc3339(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0);
+    let (days, second) = (seconds / 86_400, seconds % 86_400);
+    // days to a gregorian date, from Howard Hinnant's civil_from_days
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        second / 3600,
+        second / 60 % 60,
+        second % 60
+    )
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Feed {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut feed = serializer.serialize_struct("Feed", 3)?;
+        feed.serialize_field("title", &self.title)?;
+        feed.serialize_field("updated", &rfc3339(self.updated))?;
+        feed.serialize_field("entries", &self.entries)?;
+        feed.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FeedEntry {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut entry = serializer.serialize_struct("FeedEntry", 6)?;
+        entry.serialize_field("id", &self.id)?;
+        entry.serialize_field("title", &self.title)?;
+        entry.serialize_field("link", &self.link)?;
+        entry.serialize_field("summary", &self.summary)?;
+        entry.serialize_field("authors", &self.authors)?;
+        entry.serialize_field("published", &self.published)?;
+        entry.end()
+    }
+}
+
+// widest a column of write_xlsx gets, in characters
+#[cfg(feature = "xlsx")]
+pub const MAX_XLSX_COLUMN_WIDTH: usize = 60;
+
+#[cfg(feature = "xlsx")]
+const XLSX_COLUMNS: [Column; 6] = [
+    Column::Title,
+    Column::Authors,
+    Column::Venue,
+    Column::Year,
+    Column::CitedBy,
+    Column::Abstract,
+];
+
+// a cell of write_xlsx
+#[cfg(feature = "xlsx")]
+enum XlsxCell {
+    Header(String),
+    Text(String),
+    Number(u32),
+    // text linking to the url
+    Link(String, String),
+    Empty,
+}
+
+#[cfg(feature = "xlsx")]
+impl XlsxCell {
+    fn text(&self) -> String {
+        match self {
+            XlsxCell::Header(text) | XlsxCell::Text(text) | XlsxCell::Link(text, _) => text.clone(),
+            XlsxCell::Number(number) => number.to_string(),
+            XlsxCell::Empty => String::new(),
+        }
+    }
+}
+
+// An Excel workbook of `results` at `path`, for people who open result sets
+// in a spreadsheet: a "Results" sheet with a frozen header row, the columns
+// of to_html_report with year and citations as numbers so they sort as
+// such, titles linked to the result's http or https link and columns as
+// wide as their longest text up to MAX_XLSX_COLUMN_WIDTH, and a
+// "Provenance" sheet listing `provenance`, eg: ("Query", "deep learning")
+// and ("Retrieved", "2024-03-05"), and the number of results. Strings are
+// written inline, the archive is stored uncompressed.
+#[cfg(feature = "xlsx")]
+pub fn write_xlsx(
+    results: &[ScholarResult],
+    provenance: &[(String, String)],
+    path: impl AsRef<std::path::Path>,
+) -> std::io::Result<()> {
+    std::fs::write(path, xlsx(results, provenance))
+}
+
+#[cfg(feature = "xlsx")]
+fn xlsx(results: &[ScholarResult], provenance: &[(String, String)]) -> Vec<u8> {
+    let mut rows = vec![XLSX_COLUMNS
+        .iter()
+        .map(|column| XlsxCell::Header(header(*column).to_string()))
+        .collect::<Vec<_>>()];
+    for result in results {
+        rows.push(
+            XLSX_COLUMNS
+                .iter()
+                .map(|column| xlsx_cell(result, *column))
+                .collect(),
+        );
+    }
+    let mut listed = vec![vec![
+        XlsxCell::Header("Field".to_string()),
+        XlsxCell::Header("Value".to_string()),
+    ]];
+    for (name, value) in provenance {
+        listed.push(vec![
+            XlsxCell::Text(name.clone()),
+            XlsxCell::Text(value.clone()),
+        ]);
+    }
+    listed.push(vec![
+        XlsxCell::Text("Results".to_string()),
+        XlsxCell::Number(results.len() as u32),
+    ]);
+
+    let (results_sheet, links) = worksheet(&rows, true);
+    let (provenance_sheet, _) = worksheet(&listed, false);
+    let mut zip = crate::zip::ZipWriter::default();
+    zip.add("[Content_Types].xml", CONTENT_TYPES);
+    zip.add("_rels/.rels", ROOT_RELS);
+    zip.add("xl/workbook.xml", WORKBOOK);
+    zip.add("xl/_rels/workbook.xml.rels", WORKBOOK_RELS);
+    zip.add("xl/styles.xml", STYLES);
+    zip.add("xl/worksheets/sheet1.xml", results_sheet);
+    zip.add("xl/worksheets/_rels/sheet1.xml.rels", links);
+    zip.add("xl/worksheets/sheet2.xml", provenance_sheet);
+    zip.finish()
+}
+
+#[cfg(feature = "xlsx")]
+fn xlsx_cell(result: &ScholarResult, column: Column) -> XlsxCell {
+    let text = |text: &str| match text.trim() {
+        "" => XlsxCell::Empty,
+        text => XlsxCell::Text(text.to_string()),
+    };
+    match column {
+        Column::Title => {
+            let link = result.link.trim();
+            if link.starts_with("https://") || link.starts_with("http://") {
+                XlsxCell::Link(result.title.clone(), link.to_string())
+            } else {
+                text(&result.title)
+            }
+        }
+        Column::Authors => text(&result.authors.join(", ")),
+        Column::Venue => text(result.venue.as_deref().unwrap_or_default()),
+        Column::Year => result
+            .year
+            .map_or(XlsxCell::Empty, |year| XlsxCell::Number(u32::from(year))),
+        Column::CitedBy => result.cited_by.map_or(XlsxCell::Empty, XlsxCell::Number),
+        Column::Abstract => text(&result.abs),
+    }
+}
+
+// the sheet's xml and the relationships of its hyperlinks
+#[cfg(feature = "xlsx")]
+fn worksheet(rows: &[Vec<XlsxCell>], freeze_header: bool) -> (String, String) {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut sheet = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">",
+    );
+    if freeze_header {
+        sheet.push_str(
+            "<sheetViews><sheetView workbookViewId=\"0\">\
+<pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/>\
+<selection pane=\"bottomLeft\"/></sheetView></sheetViews>",
+        );
+    }
+    sheet.push_str("<cols>");
+    for column in 0..columns {
+        let longest = rows
+            .iter()
+            .filter_map(|row| row.get(column))
+            .map(|cell| cell.text().chars().count())
+            .max()
+            .unwrap_or(0);
+        let width = (longest + 2).clamp(8, MAX_XLSX_COLUMN_WIDTH);
+        let _ = write!(
+            sheet,
+            "<col min=\"{}\" max=\"{}\" width=\"{}\" customWidth=\"1\"/>",
+            column + 1,
+            column + 1,
+            width
+        );
+    }
+    sheet.push_str("</cols><sheetData>");
+
+    let mut links = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let _ = write!(sheet, "<row r=\"{}\">", i + 1);
+        for (column, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", (b'A' + column as u8) as char, i + 1);
+            match cell {
+                XlsxCell::Header(text) => {
+                    let _ = write!(
+                        sheet,
+                        "<c r=\"{}\" s=\"1\" t=\"inlineStr\"><is><t>{}</t></is></c>",
+                        reference,
+                        xml_text(text)
+                    );
+                }
+                XlsxCell::Text(text) => {
+                    let _ = write!(
+                        sheet,
+                        "<c r=\"{}\" t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+                        reference,
+                        xml_text(text)
+                    );
+                }
+                XlsxCell::Link(text, url) => {
+                    let _ = write!(
+                        sheet,
+                        "<c r=\"{}\" s=\"2\" t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+                        reference,
+                        xml_text(text)
+                    );
+                    links.push((reference, url));
+                }
+                XlsxCell::Number(number) => {
+                    let _ = write!(sheet, "<c r=\"{}\"><v>{}</v></c>", reference, number);
+                }
+                XlsxCell::Empty => {}
+            }
+        }
+        sheet.push_str("</row>");
+    }
+    sheet.push_str("</sheetData>");
+
+    let mut rels = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+    );
+    if !links.is_empty() {
+        sheet.push_str("<hyperlinks>");
+        for (i, (reference, url)) in links.iter().enumerate() {
+            let _ = write!(
+                sheet,
+                "<hyperlink ref=\"{}\" r:id=\"rId{}\"/>",
+                reference,
+                i + 1
+            );
+            let _ = write!(
+                rels,
+                "<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink\" Target=\"{}\" TargetMode=\"External\"/>",
+                i + 1,
+                xml_text(url)
+            );
+        }
+        sheet.push_str("</hyperlinks>");
+    }
+    sheet.push_str("</worksheet>");
+    rels.push_str("</Relationships>");
+    (sheet, rels)
+}
+
+// escaped, without the control characters xml 1.0 can't hold
+#[cfg(feature = "xlsx")]
+fn xml_text(text: &str) -> String {
+    let allowed = text
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>();
+    escape(&allowed)
+}
+
+#[cfg(feature = "xlsx")]
+const CONTENT_TYPES: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+<Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+<Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+<Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>\
+<Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>\
+<Override PartName=\"/xl/worksheets/sheet2.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>\
+</Types>";
+
+#[cfg(feature = "xlsx")]
+const ROOT_RELS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>\
+</Relationships>";
+
+#[cfg(feature = "xlsx")]
+const WORKBOOK: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+<sheets><sheet name=\"Results\" sheetId=\"1\" r:id=\"rId1\"/>\
+<sheet name=\"Provenance\" sheetId=\"2\" r:id=\"rId2\"/></sheets></workbook>";
+
+#[cfg(feature = "xlsx")]
+const WORKBOOK_RELS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/>\
+<Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet2.xml\"/>\
+<Relationship Id=\"rId3\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>\
+</Relationships>";
+
+// cell styles: 0 plain, 1 bold header, 2 blue underlined link
+#[cfg(feature = "xlsx")]
+const STYLES: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<styleSheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+<fonts count=\"3\"><font><sz val=\"11\"/><name val=\"Calibri\"/></font>\
+<font><b/><sz val=\"11\"/><name val=\"Calibri\"/></font>\
+<font><u/><sz val=\"11\"/><color rgb=\"FF0563C1\"/><name val=\"Calibri\"/></font></fonts>\
+<fills count=\"2\"><fill><patternFill patternType=\"none\"/></fill><fill><patternFill patternType=\"gray125\"/></fill></fills>\
+<borders count=\"1\"><border><left/><right/><top/><bottom/><diagonal/></border></borders>\
+<cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/></cellStyleXfs>\
+<cellXfs count=\"3\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/>\
+<xf numFmtId=\"0\" fontId=\"1\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/>\
+<xf numFmtId=\"0\" fontId=\"2\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/></cellXfs>\
+</styleSheet>";
+
+// Zotero's item JSON, an array with an item per result, for Zotero's
+// "Import from Clipboard". Results with a venue are journal articles, the
+// others plain documents. Fields the result lacks are left out, as are all
+// Zotero fields scholar doesn't show. Authors are split with
+// names::parse_name, single word names go in as a single field name.
+#[cfg(feature = "zotero")]
+pub fn results_to_zotero_json(results: &[ScholarResult]) -> serde_json::Value {
+    results.iter().map(zotero_item).collect()
+}
+
+#[cfg(feature = "zotero")]
+fn zotero_item(result: &ScholarResult) -> serde_json::Value {
+    use crate::scholar::ResultDate;
+    use serde_json::{json, Map, Value};
+
+    let venue = cited_venue(result);
+    let mut item = Map::new();
+    let item_type = if venue.is_some() {
+        "journalArticle"
+    } else {
+        "document"
+    };
+    item.insert("itemType".to_string(), json!(item_type));
+    if !result.title.is_empty() {
+        item.insert("title".to_string(), json!(result.title));
+    }
+    let creators = result
+        .authors
+        .iter()
+        .map(|author| author.trim())
+        .filter(|author| !author.is_empty())
+        .map(|author| {
+            let name = crate::names::parse_name(author);
+            let mut given = std::iter::once(&name.given)
+                .chain(&name.middle)
+                .filter(|given| !given.is_empty())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(" ");
+            if given.is_empty() {
+                return json!({"creatorType": "author", "name": author});
+            }
+            // Zotero keeps suffixes with the given names
+            if let Some(suffix) = &name.suffix {
+                given = format!("{}, {}", given, suffix);
+            }
+            json!({"creatorType": "author", "firstName": given, "lastName": name.family})
+        })
+        .collect::<Vec<_>>();
+    if !creators.is_empty() {
+        item.insert("creators".to_string(), Value::Array(creators));
+    }
+    let date = match &result.date {
+        Some(ResultDate::Absolute { text, .. }) => Some(text.clone()),
+        _ => result.year.map(|year| year.to_string()),
+    };
+    if let Some(date) = date {
+        item.insert("date".to_string(), json!(date));
+    }
+    if !result.link.is_empty() {
+        item.insert("url".to_string(), json!(result.link));
+    }
+    if !result.abs.is_empty() {
+        item.insert("abstractNote".to_string(), json!(result.abs));
+    }
+    if let Some(venue) = venue {
+        item.insert("publicationTitle".to_string(), json!(venue));
+        // documents have no DOI field
+        if let Some(doi) = crate::urlnorm::find_doi(&result.link) {
+            item.insert("DOI".to_string(), json!(doi));
+        }
+    }
+    // the ids Zotero reads back from the extra field
+    let extra = [("PMID", &result.pmid), ("PMCID", &result.pmcid)]
+        .iter()
+        .filter_map(|(label, id)| Some(format!("{}: {}", label, id.as_deref()?)))
+        .collect::<Vec<_>>();
+    if !extra.is_empty() {
+        item.insert("extra".to_string(), json!(extra.join("\n")));
+    }
+    Value::Object(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results() -> Vec<ScholarResult> {
+        vec![
+            ScholarResult {
+                title: "<script>alert(\"x\")</script> & more".to_string(),
+                authors: vec!["A O'Author".to_string(), "B Author".to_string()],
+                venue: Some("Journal of <b>Examples</b>".to_string()),
+                year: Some(2019),
+                cited_by: Some(42),
+                abs: "An <img src=x onerror=alert(1)> abstract".to_string(),
+                link: "https://example.org/paper?a=1&b=\"2\"".to_string(),
+                ..Default::default()
+            },
+            ScholarResult {
+                title: "Linkless".to_string(),
+                link: "javascript:alert(1)".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn rows_are_escaped() {
+        let html = to_html_report(&results(), ReportOptions::default());
+
+        let body = &html[html.find("<tbody>").unwrap()..html.find("</tbody>").unwrap()];
+        assert_eq!(
+            body,
+            "<tbody>\n\
+<tr><td><a href=\"https://example.org/paper?a=1&amp;b=&quot;2&quot;\">&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &amp; more</a></td>\
+<td>A O&#39;Author, B Author</td>\
+<td>Journal of &lt;b&gt;Examples&lt;/b&gt;</td>\
+<td class=\"num\" data-sort=\"2019\">2019</td>\
+<td class=\"num\" data-sort=\"42\">42</td>\
+<td><details><summary>Show</summary>An &lt;img src=x onerror=alert(1)&gt; abstract</details></td></tr>\n\
+<tr><td>Linkless</td><td></td><td></td><td class=\"num\"></td><td class=\"num\"></td><td></td></tr>\n"
+        );
+        // the only script is ours
+        assert_eq!(html.matches("<script>").count(), 1);
+        assert!(!html.contains("<img"));
+    }
+
+    #[test]
+    fn page_structure() {
+        let options = ReportOptions {
+            title: "Scan <1>".to_string(),
+            notes: Some("For review".to_string()),
+            provenance: vec![
+                ("Query".to_string(), "deep learning".to_string()),
+                ("Retrieved".to_string(), "2024-03-05".to_string()),
+            ],
+            columns: vec![Column::Year, Column::Title],
+        };
+
+        let html = to_html_report(&results(), options);
+
+        assert!(html.starts_with("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Scan &lt;1&gt;</title>\n<style>"));
+        assert!(html.ends_with("</script>\n</body>\n</html>\n"));
+        let order = [
+            "<h1>Scan &lt;1&gt;</h1>",
+            "<p class=\"notes\">For review</p>",
+            "<dl class=\"provenance\">\n<dt>Query</dt><dd>deep learning</dd>\n<dt>Retrieved</dt><dd>2024-03-05</dd>\n</dl>",
+            "<thead><tr><th>Year</th><th>Title</th></tr></thead>",
+            "<tr><td class=\"num\" data-sort=\"2019\">2019</td><td><a href=",
+        ];
+        let positions = order
+            .iter()
+            .map(|part| {
+                html.find(part)
+                    .unwrap_or_else(|| panic!("missing {}", part))
+            })
+            .collect::<Vec<_>>();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        // self-contained
+        assert!(!html.contains("src=\"http"));
+        assert!(!html.contains("<link"));
+    }
+
+    #[test]
+    fn latex_bibliography() {
+        let results = vec![
+            ScholarResult {
+                title: "Rock & roll_music: 100% #1 ~ever".to_string(),
+                authors: vec![
+                    "J Müller".to_string(),
+                    "B Author".to_string(),
+                    "C Author".to_string(),
+                ],
+                venue: Some("Journal of R&D".to_string()),
+                year: Some(2019),
+                ..Default::default()
+            },
+            ScholarResult {
+                title: "The untitled".to_string(),
+                authors: vec!["A Author".to_string()],
+                ..Default::default()
+            },
+            ScholarResult {
+                title: "Rock and roll".to_string(),
+                authors: vec!["J Müller".to_string()],
+                year: Some(2019),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            to_latex_bibliography(&results, LatexStyle::Utf8),
+            "\\begin{thebibliography}{9}\n\
+\\bibitem{muller2019rock} J Müller, B Author and C Author, ``Rock \\& roll\\_music: 100\\% \\#1 \\textasciitilde{}ever'', \\emph{Journal of R\\&D}, 2019.\n\
+\\bibitem{authoruntitled} A Author, ``The untitled''.\n\
+\\bibitem{muller2019rockb} J Müller, ``Rock and roll'', 2019.\n\
+\\end{thebibliography}\n"
+        );
+        let accents = to_latex_bibliography(&results[..1], LatexStyle::Accents);
+        assert!(
+            accents.contains("\\bibitem{muller2019rock} J M\\\"{u}ller, B Author"),
+            "{}",
+            accents
+        );
+        assert_eq!(
+            to_latex_bibliography(&[], LatexStyle::Utf8),
+            "\\begin{thebibliography}{9}\n\\end{thebibliography}\n"
+        );
+    }
+
+    #[test]
+    fn feed_entries() {
+        let mut feed = results_to_feed(&results(), "deep <learning>");
+        feed.updated = UNIX_EPOCH + std::time::Duration::from_secs(1_709_649_000);
+
+        assert_eq!(feed.title, "deep <learning>");
+        let first = &feed.entries[0];
+        assert_eq!(first.id, results()[0].stable_id());
+        assert_eq!(first.title, "<script>alert(\"x\")</script> & more");
+        assert_eq!(
+            first.link.as_deref(),
+            Some("https://example.org/paper?a=1&b=\"2\"")
+        );
+        assert_eq!(
+            first.summary.as_deref(),
+            Some("An <img src=x onerror=alert(1)> abstract")
+        );
+        assert_eq!(first.authors, vec!["A O'Author", "B Author"]);
+        assert_eq!(first.published, Some(2019));
+        let linkless = &feed.entries[1];
+        assert_eq!(
+            (linkless.link.as_ref(), linkless.summary.as_ref()),
+            (None, None)
+        );
+
+        let atom = feed.to_atom();
+        assert!(atom.starts_with(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+<feed xmlns=\"http://www.w3.org/2005/Atom\">\n\
+<id>urn:gscholar:query:deep+%3Clearning%3E</id>\n\
+<title>deep &lt;learning&gt;</title>\n\
+<updated>2024-03-05T14:30:00Z</updated>\n\
+<author><name>Google Scholar</name></author>\n<entry>\n"
+        ));
+        assert!(atom.contains(&format!(
+            "<entry>\n<id>urn:gscholar:{}</id>\n<title>Linkless</title>\n<updated>2024-03-05T14:30:00Z</updated>\n</entry>\n</feed>\n",
+            feed.entries[1].id
+        )));
+        assert!(atom.contains(
+            "<updated>2024-03-05T14:30:00Z</updated>\n\
+<author><name>A O&#39;Author</name></author>\n<author><name>B Author</name></author>\n\
+<link href=\"https://example.org/paper?a=1&amp;b=&quot;2&quot;\"/>\n\
+<summary>An &lt;img src=x onerror=alert(1)&gt; abstract</summary>\n</entry>\n"
+        ));
+        assert!(!atom.contains("<published>"));
+    }
+
+    #[test]
+    fn rfc3339_dates() {
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        // either side of 2000-02-29
+        assert_eq!(
+            rfc3339(UNIX_EPOCH + std::time::Duration::from_secs(951_782_399)),
+            "2000-02-28T23:59:59Z"
+        );
+        assert_eq!(
+            rfc3339(UNIX_EPOCH + std::time::Duration::from_secs(951_868_800)),
+            "2000-03-01T00:00:00Z"
+        );
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn xlsx_workbook() {
+        let path =
+            std::env::temp_dir().join(format!("gscholar-export-{}.xlsx", std::process::id()));
+        let provenance = vec![("Query".to_string(), "deep learning".to_string())];
+
+        write_xlsx(&results(), &provenance, &path).unwrap();
+
+        let archive = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let files = crate::zip::read_stored(&archive);
+        let names = files
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "[Content_Types].xml",
+                "_rels/.rels",
+                "xl/workbook.xml",
+                "xl/_rels/workbook.xml.rels",
+                "xl/styles.xml",
+                "xl/worksheets/sheet1.xml",
+                "xl/worksheets/_rels/sheet1.xml.rels",
+                "xl/worksheets/sheet2.xml",
+            ]
+        );
+        let file = |name: &str| {
+            let (_, data) = files.iter().find(|(found, _)| found == name).unwrap();
+            String::from_utf8(data.clone()).unwrap()
+        };
+
+        let sheet = file("xl/worksheets/sheet1.xml");
+        assert!(sheet.contains(
+            "<pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/>"
+        ));
+        assert!(sheet.contains("<c r=\"A1\" s=\"1\" t=\"inlineStr\"><is><t>Title</t></is></c>"));
+        // typed year and citations
+        assert!(sheet.contains("<c r=\"D2\"><v>2019</v></c><c r=\"E2\"><v>42</v></c>"));
+        // the javascript link is no hyperlink, the row has no number cells
+        assert!(sheet.contains(
+            "<row r=\"3\"><c r=\"A3\" t=\"inlineStr\"><is><t xml:space=\"preserve\">Linkless</t></is></c></row>"
+        ));
+        assert!(sheet.contains(
+            "<c r=\"A2\" s=\"2\" t=\"inlineStr\"><is><t xml:space=\"preserve\">&lt;script&gt;"
+        ));
+        assert!(sheet.contains("<hyperlinks><hyperlink ref=\"A2\" r:id=\"rId1\"/></hyperlinks>"));
+        assert!(sheet.contains("<col min=\"4\" max=\"4\" width=\"8\" customWidth=\"1\"/>"));
+        assert!(file("xl/worksheets/_rels/sheet1.xml.rels").contains(
+            "Target=\"https://example.org/paper?a=1&amp;b=&quot;2&quot;\" TargetMode=\"External\""
+        ));
+
+        let listed = file("xl/worksheets/sheet2.xml");
+        assert!(listed.contains("<is><t xml:space=\"preserve\">deep learning</t></is>"));
+        assert!(listed.contains("<c r=\"B3\"><v>2</v></c>"));
+        assert!(!listed.contains("<pane"));
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn xlsx_column_widths_are_capped() {
+        let long = ScholarResult {
+            abs: "word ".repeat(100),
+            ..Default::default()
+        };
+
+        let (sheet, _) = worksheet(&[vec![xlsx_cell(&long, Column::Abstract)]], false);
+
+        assert!(sheet.contains(&format!(
+            "<col min=\"1\" max=\"1\" width=\"{}\" customWidth=\"1\"/>",
+            MAX_XLSX_COLUMN_WIDTH
+        )));
+    }
+
+    #[test]
+    fn bibtex_entries() {
+        let article = ScholarResult {
+            title: "Deep learning & 100% of_it".to_string(),
+            authors: vec!["Y LeCun".to_string(), "Y Bengio".to_string()],
+            venue: Some("Nature".to_string()),
+            year: Some(2015),
+            link: "https://www.nature.com/articles/nature14539".to_string(),
+            ..Default::default()
+        };
+        let misc = ScholarResult {
+            title: "Deep learning".to_string(),
+            authors: vec!["Y LeCun".to_string()],
+            year: Some(2015),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            to_bibtex(&[article, misc]),
+            "\
+@article{lecun2015deep,
+  author = {Y LeCun and Y Bengio},
+  title = {Deep learning \\& 100\\% of\\_it},
+  journal = {Nature},
+  year = {2015},
+  url = {https://www.nature.com/articles/nature14539},
+}
+@misc{lecun2015deepb,
+  author = {Y LeCun},
+  title = {Deep learning},
+  year = {2015},
+}
+"
+        );
+    }
+
+    #[test]
+    fn ris_records() {
+        let results = [
+            ScholarResult {
+                title: "Deep\nlearning".to_string(),
+                authors: vec!["Y LeCun".to_string(), "Y Bengio".to_string()],
+                venue: Some("nature".to_string()),
+                year: Some(2015),
+                link: "https://www.nature.com/articles/nature14539".to_string(),
+                ..Default::default()
+            },
+            ScholarResult {
+                title: "Untitled notes".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            to_ris(&results),
+            "TY  - JOUR
+AU  - Y LeCun
+AU  - Y Bengio
+TI  - Deep learning
+T2  - nature
+PY  - 2015
+UR  - https://www.nature.com/articles/nature14539
+ER  - 
+TY  - GEN
+TI  - Untitled notes
+ER  - 
+"
+        );
+    }
+
+    #[cfg(feature = "venue-normalize")]
+    #[test]
+    fn citations_expand_venues_on_request() {
+        let result = |venue: &str| ScholarResult {
+            title: "Deep learning".to_string(),
+            authors: vec!["Y LeCun".to_string()],
+            venue: Some(venue.to_string()),
+            year: Some(2015),
+            ..Default::default()
+        };
+        let results = [result("Commun. ACM"), result("Some Workshop")];
+        let expand = CitationOptions {
+            expand_venues: true,
+        };
+
+        let bibtex = to_bibtex_with(&results, expand);
+        let ris = to_ris_with(&results, expand);
+
+        assert!(bibtex.contains("  journal = {Communications of the ACM},\n"));
+        assert!(bibtex.contains("  journal = {Some Workshop},\n"));
+        assert!(ris.contains("T2  - Communications of the ACM\n"));
+        assert!(ris.contains("T2  - Some Workshop\n"));
+        // off unless asked for
+        assert!(to_bibtex(&results).contains("  journal = {Commun. ACM},\n"));
+        assert!(to_ris(&results).contains("T2  - Commun. ACM\n"));
+        let latex = to_latex_bibliography(&results, LatexStyle::Utf8);
+        assert!(latex.contains("\\emph{Commun. ACM}"));
+    }
+
+    #[test]
+    fn bibtex_keys() {
+        let key = |title: &str, authors: &[&str], year| {
+            bibtex_key(&ScholarResult {
+                title: title.to_string(),
+                authors: authors.iter().map(|a| a.to_string()).collect(),
+                year,
+                ..Default::default()
+            })
+        };
+
+        assert_eq!(
+            key(
+                "Attention is all you need",
+                &["A Vaswani", "N Shazeer"],
+                Some(2017)
+            ),
+            "vaswani2017attention"
+        );
+        assert_eq!(key("On the Origin", &["C Darwin"], None), "darwinorigin");
+        assert_eq!(key("", &[], None), "result");
+        assert_eq!(suffix(1), "b");
+        assert_eq!(suffix(26), "ba");
+    }
+
+    #[test]
+    fn stable_keys_across_text_forms() {
+        let key = |title: &str, author: &str| {
+            bibtex_key(&ScholarResult {
+                title: title.to_string(),
+                authors: vec![author.to_string()],
+                year: Some(2020),
+                ..Default::default()
+            })
+        };
+        let cases = [
+            // ligature, soft hyphen, plain
+            (
+                [
+                    "\u{fb01}nite automata",
+                    "fi\u{ad}nite automata",
+                    "finite automata",
+                ],
+                "J Smith",
+            ),
+            // composed and decomposed accents
+            (["Élan vital", "E\u{301}lan vital", "Elan vital"], "A Smith"),
+        ];
+        for (titles, author) in cases.iter() {
+            let keys = titles
+                .iter()
+                .map(|title| key(title, author))
+                .collect::<Vec<_>>();
+            assert!(keys.iter().all(|k| *k == keys[2]), "{:?}", keys);
+        }
+        assert_eq!(key("\u{fb01}nite automata", "J Smith"), "smith2020finite");
+        assert_eq!(
+            key("On graphs", "P Erdo\u{30b}s"),
+            key("On graphs", "P Erdős")
+        );
+        assert_eq!(key("On graphs", "P Erdős"), "erdos2020graphs");
+    }
+
+    #[test]
+    fn unique_keys_skip_emitted_ones() {
+        let mut emitted = HashSet::new();
+        let keys = ["smith2020", "smith2020b", "smith2020", "smith2020"]
+            .iter()
+            .map(|key| unique_key(key.to_string(), &mut emitted))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            keys,
+            vec!["smith2020", "smith2020b", "smith2020c", "smith2020d"]
+        );
+    }
+
+    #[cfg(feature = "zotero")]
+    #[test]
+    fn zotero_items() {
+        let article = ScholarResult {
+            title: "Attention is all you need".to_string(),
+            authors: vec![
+                "A Vaswani".to_string(),
+                "ML King Jr".to_string(),
+                "Aristotle".to_string(),
+            ],
+            venue: Some("Advances in neural information processing systems".to_string()),
+            year: Some(2017),
+            abs: "The dominant sequence transduction models ...".to_string(),
+            link: "https://dl.acm.org/doi/abs/10.5555/3295222.3295349".to_string(),
+            pmid: Some("123456".to_string()),
+            cited_by: Some(100000),
+            ..Default::default()
+        };
+        let bare = ScholarResult {
+            title: "Untitled notes".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            results_to_zotero_json(&[article, bare]),
+            serde_json::json!([
+                {
+                    "itemType": "journalArticle",
+                    "title": "Attention is all you need",
+                    "creators": [
+                        {"creatorType": "author", "firstName": "A", "lastName": "Vaswani"},
+                        {"creatorType": "author", "firstName": "M L, Jr", "lastName": "King"},
+                        {"creatorType": "author", "name": "Aristotle"},
+                    ],
+                    "date": "2017",
+                    "url": "https://dl.acm.org/doi/abs/10.5555/3295222.3295349",
+                    "abstractNote": "The dominant sequence transduction models ...",
+                    "publicationTitle": "Advances in neural information processing systems",
+                    "DOI": "10.5555/3295222.3295349",
+                    "extra": "PMID: 123456",
+                },
+                {"itemType": "document", "title": "Untitled notes"},
+            ])
+        );
+    }
+}
@@ -0,0 +1,619 @@
+// Citation strings formatted locally from the parsed fields of a result,
+// without fetching scholar's cite popup. Results are taken as journal
+// articles, their venue as the journal: there is no volume, issue nor pages
+// to format. The author list is what the byline shows, which scholar
+// truncates for long lists.
+//
+// Also plain text tables of author comparisons, see author_table.
+
+use crate::names::{parse_name, PersonName};
+use crate::scholar::{AuthorComparison, ScholarResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    // APA 7th edition reference list
+    Apa7,
+    // MLA 9th edition works cited
+    Mla9,
+    // Chicago 17th edition notes and bibliography, bibliography entry
+    Chicago,
+}
+
+// The citation without markup, and with the venue in markdown italics and
+// the rest escaped for markdown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Citation {
+    pub plain: String,
+    pub markdown: String,
+}
+
+// Formats `result` in `style`. Missing parts are left out the way the style
+// does it: APA and Chicago write "n.d." for a missing year, a missing author
+// list moves the title first, and a missing venue drops the venue with its
+// punctuation.
+//
+// APA titles are put in sentence case. Words with a capital past their first
+// letter, eg: "BERT" or "ImageNet", are taken as acronyms or names and kept;
+// other proper nouns can't be told apart and are lowercased. MLA and Chicago
+// titles are put in title case.
+pub fn citation(result: &ScholarResult, style: Style) -> Citation {
+    let names = result
+        .authors
+        .iter()
+        .map(|author| author.trim())
+        .filter(|author| !author.is_empty())
+        .map(parse_name)
+        .collect::<Vec<_>>();
+    let title = result.title.trim();
+    let venue = result
+        .venue
+        .as_deref()
+        .map(str::trim)
+        .filter(|venue| !venue.is_empty());
+    let parts = match style {
+        Style::Apa7 => apa(&names, title, venue, result.year),
+        Style::Mla9 => mla(&names, title, venue, result.year),
+        Style::Chicago => chicago(&names, title, venue, result.year),
+    };
+    Citation {
+        plain: parts.iter().map(|part| part.plain()).collect(),
+        markdown: parts.iter().map(|part| part.markdown()).collect(),
+    }
+}
+
+enum Part {
+    Text(String),
+    Italic(String),
+}
+
+impl Part {
+    fn plain(&self) -> &str {
+        match self {
+            Part::Text(text) | Part::Italic(text) => text,
+        }
+    }
+
+    fn markdown(&self) -> String {
+        match self {
+            Part::Text(text) => escape_markdown(text),
+            Part::Italic(text) => format!("*{}*", escape_markdown(text)),
+        }
+    }
+}
+
+fn text(text: impl Into<String>) -> Part {
+    Part::Text(text.into())
+}
+
+// Smith, J. R., & Jones, B. (2019). Title in sentence case. *Venue*.
+fn apa(names: &[PersonName], title: &str, venue: Option<&str>, year: Option<u16>) -> Vec<Part> {
+    let date = match year {
+        Some(year) => format!("({}).", year),
+        None => "(n.d.).".to_string(),
+    };
+    let title = (!title.is_empty()).then(|| close(&sentence_case(title)));
+    let mut parts = Vec::new();
+    match (apa_authors(names), title) {
+        (Some(authors), Some(title)) => parts.push(text(format!("{} {} {}", authors, date, title))),
+        (Some(authors), None) => parts.push(text(format!("{} {}", authors, date))),
+        (None, Some(title)) => parts.push(text(format!("{} {}", title, date))),
+        (None, None) => parts.push(text(date)),
+    }
+    if let Some(venue) = venue {
+        parts.push(text(" "));
+        push_closed_italic(&mut parts, venue);
+    }
+    parts
+}
+
+// up to 20 names, the last after "&", or the first 19, an ellipsis and the
+// last
+fn apa_authors(names: &[PersonName]) -> Option<String> {
+    let names = names.iter().map(apa_name).collect::<Vec<_>>();
+    Some(match names.as_slice() {
+        [] => return None,
+        [only] => only.clone(),
+        [first, second] => format!("{}, & {}", first, second),
+        [rest @ .., last] if names.len() <= 20 => format!("{}, & {}", rest.join(", "), last),
+        [.., last] => format!("{}, . . . {}", names[..19].join(", "), last),
+    })
+}
+
+// Smith, J. R.
+fn apa_name(name: &PersonName) -> String {
+    let initials = given_names(name).map(initials).collect::<Vec<_>>();
+    let mut formatted = name.family.clone();
+    if !initials.is_empty() {
+        formatted = format!("{}, {}", formatted, initials.join(" "));
+    }
+    if let Some(suffix) = &name.suffix {
+        formatted = format!("{}, {}", formatted, with_period(suffix));
+    }
+    formatted
+}
+
+// Smith, John R., and Barbara Jones. "Title in Title Case." *Venue*, 2019.
+fn mla(names: &[PersonName], title: &str, venue: Option<&str>, year: Option<u16>) -> Vec<Part> {
+    let authors = match names {
+        [] => None,
+        [only] => Some(inverted(only)),
+        [first, second] => Some(format!("{}, and {}", inverted(first), natural(second))),
+        [first, ..] => Some(format!("{}, et al", inverted(first))),
+    };
+    let mut parts = Vec::new();
+    if let Some(authors) = authors {
+        parts.push(text(close(&authors)));
+    }
+    if !title.is_empty() {
+        parts.push(text(separated(&parts, &quoted(&title_case(title)))));
+    }
+    match (venue, year) {
+        (Some(venue), Some(year)) => {
+            parts.push(text(separated(&parts, "")));
+            parts.push(Part::Italic(venue.to_string()));
+            parts.push(text(format!(", {}.", year)));
+        }
+        (Some(venue), None) => {
+            parts.push(text(separated(&parts, "")));
+            push_closed_italic(&mut parts, venue);
+        }
+        (None, Some(year)) => parts.push(text(separated(&parts, &format!("{}.", year)))),
+        (None, None) => {}
+    }
+    parts
+}
+
+// Smith, John R., and Barbara Jones. "Title in Title Case." *Venue* (2019).
+// Up to ten names, otherwise the first seven and "et al."
+fn chicago(names: &[PersonName], title: &str, venue: Option<&str>, year: Option<u16>) -> Vec<Part> {
+    let authors = match names {
+        [] => None,
+        [only] => Some(inverted(only)),
+        [first, second] => Some(format!("{}, and {}", inverted(first), natural(second))),
+        [first, rest @ ..] if names.len() <= 10 => {
+            let (last, middle) = rest.split_last().unwrap_or((first, &[]));
+            let middle = middle.iter().map(natural).collect::<Vec<_>>();
+            Some(format!(
+                "{}, {}, and {}",
+                inverted(first),
+                middle.join(", "),
+                natural(last)
+            ))
+        }
+        [first, rest @ ..] => {
+            let others = rest[..6].iter().map(natural).collect::<Vec<_>>();
+            Some(format!("{}, {}, et al", inverted(first), others.join(", ")))
+        }
+    };
+    let date = match year {
+        Some(year) => year.to_string(),
+        None => "n.d".to_string(),
+    };
+    let mut parts = Vec::new();
+    if let Some(authors) = authors {
+        parts.push(text(close(&authors)));
+    }
+    if !title.is_empty() {
+        parts.push(text(separated(&parts, &quoted(&title_case(title)))));
+    }
+    match venue {
+        Some(venue) => {
+            parts.push(text(separated(&parts, "")));
+            parts.push(Part::Italic(venue.to_string()));
+            match year {
+                Some(_) => parts.push(text(format!(" ({}).", date))),
+                None => parts.push(text(format!(", {}.", date))),
+            }
+        }
+        None => parts.push(text(separated(&parts, &format!("{}.", date)))),
+    }
+    parts
+}
+
+// Smith, John R.
+fn inverted(name: &PersonName) -> String {
+    let given = given_names(name).map(given_name).collect::<Vec<_>>();
+    let mut formatted = name.family.clone();
+    if !given.is_empty() {
+        formatted = format!("{}, {}", formatted, given.join(" "));
+    }
+    if let Some(suffix) = &name.suffix {
+        formatted = format!("{}, {}", formatted, with_period(suffix));
+    }
+    formatted
+}
+
+// John R. Smith
+fn natural(name: &PersonName) -> String {
+    let mut words = given_names(name).map(given_name).collect::<Vec<_>>();
+    words.push(name.family.clone());
+    if let Some(suffix) = &name.suffix {
+        words.push(with_period(suffix));
+    }
+    words.join(" ")
+}
+
+fn given_names(name: &PersonName) -> impl Iterator<Item = &str> {
+    std::iter::once(name.given.as_str())
+        .chain(name.middle.iter().map(String::as_str))
+        .filter(|given| !given.is_empty())
+}
+
+// "John" as is, an initial with its period: "J" -> "J.", "J-P" -> "J.-P."
+fn given_name(given: &str) -> String {
+    if given.split('-').all(|part| part.chars().count() <= 1) {
+        initials(given)
+    } else {
+        given.to_string()
+    }
+}
+
+// "John" -> "J.", "Jean-Paul" -> "J.-P."
+fn initials(given: &str) -> String {
+    given
+        .split('-')
+        .filter_map(|part| part.chars().next())
+        .map(|initial| format!("{}.", initial))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn with_period(suffix: &str) -> String {
+    match suffix.to_lowercase().as_str() {
+        "jr" | "sr" => format!("{}.", suffix),
+        _ => suffix.to_string(),
+    }
+}
+
+// `text` ending in a period, unless it already ends in punctuation that
+// closes a sentence
+fn close(text: &str) -> String {
+    if text.ends_with(['.', '?', '!']) {
+        text.to_string()
+    } else {
+        format!("{}.", text)
+    }
+}
+
+// "Title." with the period inside the quotes
+fn quoted(title: &str) -> String {
+    format!("\u{201c}{}\u{201d}", close(title))
+}
+
+// `text`, after a space when parts came before
+fn separated(parts: &[Part], text: &str) -> String {
+    if parts.is_empty() {
+        text.to_string()
+    } else {
+        format!(" {}", text)
+    }
+}
+
+// the venue in italics and its period, outside the italics
+fn push_closed_italic(parts: &mut Vec<Part>, venue: &str) {
+    let closed = venue.ends_with(['.', '?', '!']);
+    parts.push(Part::Italic(venue.to_string()));
+    if !closed {
+        parts.push(text("."));
+    }
+}
+
+// lowercase words left so in title case, unless first or last
+const MINOR_WORDS: &[&str] = &[
+    "a", "an", "the", "and", "but", "or", "nor", "for", "so", "yet", "as", "at", "by", "in", "of",
+    "off", "on", "per", "to", "up", "via", "with", "from", "into", "vs",
+];
+
+// "ImageNet", "BERT", "COVID-19": a capital past the first letter
+fn is_protected(word: &str) -> bool {
+    word.chars()
+        .filter(|c| c.is_alphabetic())
+        .skip(1)
+        .any(char::is_uppercase)
+}
+
+// First word and the first after a colon capitalized, the others lowercase
+// unless protected
+fn sentence_case(title: &str) -> String {
+    let mut capitalize = true;
+    let words = title
+        .split_whitespace()
+        .map(|word| {
+            let cased = if is_protected(word) {
+                word.to_string()
+            } else if capitalize {
+                capitalized(&word.to_lowercase())
+            } else {
+                word.to_lowercase()
+            };
+            capitalize = word.ends_with([':', '?', '!']);
+            cased
+        })
+        .collect::<Vec<_>>();
+    words.join(" ")
+}
+
+// Words capitalized but minor ones, which are lowercase unless they start
+// the title or a subtitle or end it. Protected words are kept.
+fn title_case(title: &str) -> String {
+    let words = title.split_whitespace().collect::<Vec<_>>();
+    let mut capitalize = true;
+    let cased = words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let bare = word
+                .trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase();
+            let cased = if is_protected(word) {
+                word.to_string()
+            } else if capitalize || i + 1 == words.len() || !MINOR_WORDS.contains(&bare.as_str()) {
+                capitalized(word)
+            } else {
+                word.to_lowercase()
+            };
+            capitalize = word.ends_with([':', '?', '!']);
+            cased
+        })
+        .collect::<Vec<_>>();
+    cased.join(" ")
+}
+
+// first letter uppercase, even after an opening quote or parenthesis
+fn capitalized(word: &str) -> String {
+    match word.char_indices().find(|(_, c)| c.is_alphabetic()) {
+        Some((i, c)) => format!(
+            "{}{}{}",
+            &word[..i],
+            c.to_uppercase(),
+            &word[i + c.len_utf8()..]
+        ),
+        None => word.to_string(),
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// Two tables: the metrics of each author, then their citations per year
+// on the common axis, left out when no profile has a chart. Unknown values
+// show as "-".
+//
+// Author    Citations  Since 2020  h-index  i10-index  Publications  Since 2022
+// Jane Doe       1200         800       15         20            42          10
+//
+// Citations per year
+// Author    2019  2020
+// Jane Doe   100   300
+pub fn author_table(comparison: &AuthorComparison) -> String {
+    let since = |year: Option<u16>| {
+        year.map_or_else(|| "Recent".to_string(), |year| format!("Since {}", year))
+    };
+    let known =
+        |value: Option<u32>| value.map_or_else(|| "-".to_string(), |value| value.to_string());
+    let mut metrics = vec![vec![
+        "Author".to_string(),
+        "Citations".to_string(),
+        since(comparison.citations_since),
+        "h-index".to_string(),
+        "i10-index".to_string(),
+        "Publications".to_string(),
+        since(Some(comparison.publications_since)),
+    ]];
+    for author in &comparison.authors {
+        metrics.push(vec![
+            author.name.clone(),
+            known(author.metrics.citations),
+            known(author.recent_citations),
+            known(author.metrics.h_index),
+            known(author.metrics.i10_index),
+            author.publications.to_string(),
+            author.recent_publications.to_string(),
+        ]);
+    }
+    let mut text = table(&metrics);
+    if comparison.years.is_empty() {
+        return text;
+    }
+
+    let header = std::iter::once("Author".to_string())
+        .chain(comparison.years.iter().map(u16::to_string))
+        .collect();
+    let mut per_year = vec![header];
+    for author in &comparison.authors {
+        per_year.push(
+            std::iter::once(author.name.clone())
+                .chain(author.citations_per_year.iter().map(u32::to_string))
+                .collect(),
+        );
+    }
+    text.push_str("\nCitations per year\n");
+    text.push_str(&table(&per_year));
+    text
+}
+
+// the first column left aligned, the others right aligned, two spaces apart
+fn table(rows: &[Vec<String>]) -> String {
+    let mut widths = Vec::new();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            let width = cell.chars().count();
+            match widths.get_mut(i) {
+                Some(widest) if *widest < width => *widest = width,
+                Some(_) => {}
+                None => widths.push(width),
+            }
+        }
+    }
+    let mut text = String::new();
+    for row in rows {
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, width))| match i {
+                0 => format!("{:<width$}", cell, width = width),
+                _ => format!("{:>width$}", cell, width = width),
+            })
+            .collect::<Vec<_>>();
+        text.push_str(cells.join("  ").trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(
+        authors: &[&str],
+        title: &str,
+        venue: Option<&str>,
+        year: Option<u16>,
+    ) -> ScholarResult {
+        ScholarResult {
+            title: title.to_string(),
+            authors: authors.iter().map(|a| a.to_string()).collect(),
+            venue: venue.map(str::to_string),
+            year,
+            ..Default::default()
+        }
+    }
+
+    fn cases() -> Vec<ScholarResult> {
+        vec![
+            result(
+                &["A Vaswani"],
+                "Attention Is All You Need",
+                Some("Advances in Neural Information Processing Systems"),
+                Some(2017),
+            ),
+            result(
+                &["JR Smith", "B Jones"],
+                "BERT for ImageNet: a study of the pretraining",
+                Some("Journal of Examples"),
+                Some(2019),
+            ),
+            result(
+                &["A Author", "B Author", "C Author"],
+                "Deep learning in the wild?",
+                None,
+                Some(2020),
+            ),
+            result(
+                &["J-P Sartre", "ML King Jr", "C Author", "D Author"],
+                "On being",
+                Some("Phil. Rev."),
+                None,
+            ),
+            result(&[], "Untitled work on COVID-19", Some("Some_Journal"), None),
+        ]
+    }
+
+    fn formatted(style: Style) -> Vec<String> {
+        cases()
+            .iter()
+            .map(|result| citation(result, style).plain)
+            .collect()
+    }
+
+    #[test]
+    fn apa7() {
+        assert_eq!(
+            formatted(Style::Apa7),
+            vec![
+                "Vaswani, A. (2017). Attention is all you need. Advances in Neural Information Processing Systems.",
+                "Smith, J. R., & Jones, B. (2019). BERT for ImageNet: A study of the pretraining. Journal of Examples.",
+                "Author, A., Author, B., & Author, C. (2020). Deep learning in the wild?",
+                "Sartre, J.-P., King, M. L., Jr., Author, C., & Author, D. (n.d.). On being. Phil. Rev.",
+                "Untitled work on COVID-19. (n.d.). Some_Journal.",
+            ]
+        );
+    }
+
+    #[test]
+    fn mla9() {
+        assert_eq!(
+            formatted(Style::Mla9),
+            vec![
+                "Vaswani, A. \u{201c}Attention Is All You Need.\u{201d} Advances in Neural Information Processing Systems, 2017.",
+                "Smith, J. R., and B. Jones. \u{201c}BERT for ImageNet: A Study of the Pretraining.\u{201d} Journal of Examples, 2019.",
+                "Author, A., et al. \u{201c}Deep Learning in the Wild?\u{201d} 2020.",
+                "Sartre, J.-P., et al. \u{201c}On Being.\u{201d} Phil. Rev.",
+                "\u{201c}Untitled Work on COVID-19.\u{201d} Some_Journal.",
+            ]
+        );
+    }
+
+    #[test]
+    fn chicago() {
+        assert_eq!(
+            formatted(Style::Chicago),
+            vec![
+                "Vaswani, A. \u{201c}Attention Is All You Need.\u{201d} Advances in Neural Information Processing Systems (2017).",
+                "Smith, J. R., and B. Jones. \u{201c}BERT for ImageNet: A Study of the Pretraining.\u{201d} Journal of Examples (2019).",
+                "Author, A., B. Author, and C. Author. \u{201c}Deep Learning in the Wild?\u{201d} 2020.",
+                "Sartre, J.-P., M. L. King Jr., C. Author, and D. Author. \u{201c}On Being.\u{201d} Phil. Rev., n.d.",
+                "\u{201c}Untitled Work on COVID-19.\u{201d} Some_Journal, n.d.",
+            ]
+        );
+    }
+
+    #[test]
+    fn long_author_lists() {
+        let names = (1..=22)
+            .map(|i| format!("A Author{}", i))
+            .collect::<Vec<_>>();
+        let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let apa = citation(&result(&names, "T", None, Some(2020)), Style::Apa7).plain;
+        assert!(apa.starts_with("Author1, A., Author2, A., "), "{}", apa);
+        assert!(
+            apa.contains("Author19, A., . . . Author22, A. (2020)."),
+            "{}",
+            apa
+        );
+        assert!(!apa.contains("Author20"));
+
+        let chicago = citation(&result(&names[..11], "T", None, None), Style::Chicago).plain;
+        assert!(
+            chicago.starts_with("Author1, A., A. Author2, A. Author3, A. Author4, A. Author5, A. Author6, A. Author7, et al. "),
+            "{}",
+            chicago
+        );
+    }
+
+    #[test]
+    fn markdown_variant() {
+        let citations = cases()
+            .iter()
+            .map(|result| citation(result, Style::Apa7).markdown)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            citations[1],
+            "Smith, J. R., & Jones, B. (2019). BERT for ImageNet: A study of the pretraining. *Journal of Examples*."
+        );
+        assert_eq!(
+            citations[2],
+            "Author, A., Author, B., & Author, C. (2020). Deep learning in the wild?"
+        );
+        assert_eq!(
+            citations[4],
+            "Untitled work on COVID-19. (n.d.). *Some\\_Journal*."
+        );
+        assert_eq!(
+            citation(&result(&[], "", None, None), Style::Mla9).plain,
+            ""
+        );
+    }
+}
@@ -0,0 +1,130 @@
+// Free functions backed by a lazily built, process-wide Client, for quick
+// scripts. Every caller shares that client, so its delay and quota apply to
+// all of them together: two tasks searching at once wait on each other.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::scholar::{Client, Error, ScholarArgs, ScholarResult};
+
+// delay between requests of the default client
+pub const DEFAULT_DELAY: Duration = Duration::from_secs(2);
+
+// user agent of the default client and the Client presets
+pub(crate) const DEFAULT_USER_AGENT: &str =
+    "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0";
+
+static DEFAULT_CLIENT: DefaultClient = DefaultClient::new();
+
+// searches for `query` with the default client
+pub async fn search(query: &str) -> Result<Vec<ScholarResult>, Error> {
+    search_args(&ScholarArgs::new(query)).await
+}
+
+pub async fn search_args(args: &ScholarArgs) -> Result<Vec<ScholarResult>, Error> {
+    default_client()?.scrape_scholar(args).await
+}
+
+// Installs `client` as the default client. Only possible before the first
+// call using it, afterwards this fails with Error::DefaultClientInUse.
+pub fn set_default_client(client: Client) -> Result<(), Error> {
+    DEFAULT_CLIENT.set(client)
+}
+
+fn default_client() -> Result<&'static Client, Error> {
+    DEFAULT_CLIENT.get_or_init(|| {
+        Client::builder()
+            .delay(DEFAULT_DELAY)
+            .user_agent(DEFAULT_USER_AGENT)
+            .build()
+    })
+}
+
+struct DefaultClient {
+    cell: OnceLock<Client>,
+}
+
+impl DefaultClient {
+    const fn new() -> Self {
+        DefaultClient {
+            cell: OnceLock::new(),
+        }
+    }
+
+    fn set(&self, client: Client) -> Result<(), Error> {
+        self.cell.set(client).map_err(|_| Error::DefaultClientInUse)
+    }
+
+    fn get_or_init<F>(&self, init: F) -> Result<&Client, Error>
+    where
+        F: FnOnce() -> Result<Client, Error>,
+    {
+        if let Some(client) = self.cell.get() {
+            return Ok(client);
+        }
+        let client = init()?;
+        // a concurrent first call may have won, its client is kept
+        let _ = self.cell.set(client);
+        Ok(self.cell.get().expect("default client was just set"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{results_page, MockServer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn counted_init(count: &AtomicUsize) -> impl FnOnce() -> Result<Client, Error> + '_ {
+        move || {
+            count.fetch_add(1, Ordering::SeqCst);
+            Client::builder().build()
+        }
+    }
+
+    #[test]
+    fn default_client_initialized_once() {
+        let default = DefaultClient::new();
+        let count = AtomicUsize::new(0);
+
+        let first = default.get_or_init(counted_init(&count)).unwrap() as *const Client;
+        let second = default.get_or_init(counted_init(&count)).unwrap() as *const Client;
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert_eq!(first, second);
+    }
+
+    // the only test touching the process-wide client: the first search
+    // locks in the client set before it
+    #[tokio::test]
+    async fn search_with_the_default_client() {
+        let server = MockServer::start(vec![
+            (200, results_page(None, 2)),
+            (200, results_page(None, 1)),
+        ])
+        .await;
+        let client = server.client();
+
+        set_default_client(client).unwrap();
+        let results = search("abcd").await.unwrap();
+        let limited = search_args(
+            &ScholarArgs::builder()
+                .query("efgh")
+                .limit(1)
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(limited.len(), 1);
+        let requests = server.requests();
+        assert!(requests[0].starts_with("GET /scholar?q=abcd "));
+        assert!(requests[1].starts_with("GET /scholar?q=efgh&num=1 "));
+        assert!(matches!(
+            set_default_client(Client::builder().build().unwrap()),
+            Err(Error::DefaultClientInUse)
+        ));
+    }
+}
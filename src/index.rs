@@ -0,0 +1,449 @@
+// Full text search over results collected earlier, without asking scholar
+// again. The results are kept in a JSON file in the index directory and the
+// inverted index is rebuilt from it in memory on open, which suits the tens
+// of thousands of results a literature scan collects.
+//
+// Results are ranked with BM25 over title, authors, venue and abstract,
+// each field scored on its own and weighted by FIELD_BOOSTS so a match in
+// the title counts more than one in the abstract. Terms are words after
+// TextNormalizer::for_matching; a result matches when it has any of the
+// query's terms.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::normalize::TextNormalizer;
+use crate::scholar::{CiteId, ClusterId, ScholarResult};
+
+const RESULTS_FILE: &str = "results.json";
+
+// weight of a match in each field, see Field
+pub const FIELD_BOOSTS: [f32; 4] = [3.0, 1.5, 1.0, 1.0];
+
+// BM25 term frequency saturation and length normalization
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Title,
+    Authors,
+    Venue,
+    Abstract,
+}
+
+const FIELDS: [Field; 4] = [Field::Title, Field::Authors, Field::Venue, Field::Abstract];
+
+impl Field {
+    fn text(self, result: &ScholarResult) -> String {
+        match self {
+            Field::Title => result.title.clone(),
+            Field::Authors => result.authors.join(" "),
+            Field::Venue => result.venue.clone().unwrap_or_default(),
+            Field::Abstract => result.abs.clone(),
+        }
+    }
+}
+
+pub struct LocalIndex {
+    path: PathBuf,
+    results: Vec<ScholarResult>,
+    // term -> (result, field, occurrences)
+    postings: HashMap<String, Vec<(usize, Field, u32)>>,
+    // terms per result and field
+    lengths: Vec<[u32; 4]>,
+}
+
+impl LocalIndex {
+    // Opens the index in `dir`, creating the directory when needed. A
+    // results file that can't be read back is an error rather than
+    // replaced, it would be overwritten on the next add.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<LocalIndex> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(RESULTS_FILE);
+        let results = match fs::read_to_string(&path) {
+            Ok(contents) => parse_results(&contents).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} is not a local index", path.display()),
+                )
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let mut index = LocalIndex {
+            path,
+            results: Vec::new(),
+            postings: HashMap::new(),
+            lengths: Vec::new(),
+        };
+        index.insert(results);
+        Ok(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    // Indexes `results` and saves the index, returning how many were new.
+    // A result already indexed, by cluster id or else by
+    // ScholarResult::stable_id, is replaced with the one added, eg: with its
+    // citation count updated. Only the fields the index keeps are stored:
+    // title, authors, byline, venue, year, abstract, link, cluster and cite
+    // ids and the citation count.
+    pub fn add(&mut self, results: impl IntoIterator<Item = ScholarResult>) -> io::Result<usize> {
+        let before = self.results.len();
+        let mut results = self
+            .results
+            .drain(..)
+            .chain(results.into_iter().map(|result| stored(&result)))
+            .collect::<Vec<_>>();
+        let mut latest = HashMap::new();
+        for (position, result) in results.iter().enumerate() {
+            latest.insert(result.stable_id(), position);
+        }
+        let mut position = 0;
+        results.retain(|result| {
+            position += 1;
+            latest.get(&result.stable_id()) == Some(&(position - 1))
+        });
+        self.insert(results);
+        fs::write(&self.path, self.to_json())?;
+        Ok(self.results.len() - before)
+    }
+
+    // The `limit` best matches of `query`, best first, with their score
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(f32, ScholarResult)> {
+        self.ranked(query, limit, |_| true)
+    }
+
+    // search among the results published in `years`. Results without a
+    // year are left out
+    pub fn search_years(
+        &self,
+        query: &str,
+        limit: usize,
+        years: RangeInclusive<u16>,
+    ) -> Vec<(f32, ScholarResult)> {
+        self.ranked(query, limit, |result| {
+            result.year.is_some_and(|year| years.contains(&year))
+        })
+    }
+
+    fn ranked<F>(&self, query: &str, limit: usize, keep: F) -> Vec<(f32, ScholarResult)>
+    where
+        F: Fn(&ScholarResult) -> bool,
+    {
+        let count = self.results.len() as f32;
+        let mut averages = [0.0; 4];
+        for lengths in &self.lengths {
+            for (average, length) in averages.iter_mut().zip(lengths) {
+                *average += *length as f32 / count;
+            }
+        }
+
+        let mut terms = terms(query);
+        terms.sort_unstable();
+        terms.dedup();
+        let mut scores = HashMap::new();
+        for term in &terms {
+            let postings = match self.postings.get(term) {
+                Some(postings) => postings,
+                None => continue,
+            };
+            let mut with_term = postings
+                .iter()
+                .map(|(result, _, _)| result)
+                .collect::<Vec<_>>();
+            with_term.dedup();
+            let matching = with_term.len() as f32;
+            let idf = (1.0 + (count - matching + 0.5) / (matching + 0.5)).ln();
+            for &(result, field, occurrences) in postings {
+                let occurrences = occurrences as f32;
+                let length = self.lengths[result][field as usize] as f32;
+                let norm = 1.0 - B + B * length / averages[field as usize].max(1.0);
+                let score = FIELD_BOOSTS[field as usize] * idf * occurrences * (K1 + 1.0)
+                    / (occurrences + K1 * norm);
+                *scores.entry(result).or_insert(0.0) += score;
+            }
+        }
+
+        let mut ranked = scores
+            .into_iter()
+            .filter(|(result, _)| keep(&self.results[*result]))
+            .collect::<Vec<_>>();
+        // best first, ties in the order indexed
+        ranked.sort_by(|(a, a_score), (b, b_score)| b_score.total_cmp(a_score).then(a.cmp(b)));
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(result, score)| (score, self.results[result].clone()))
+            .collect()
+    }
+
+    // replaces the indexed results with `results`
+    fn insert(&mut self, results: Vec<ScholarResult>) {
+        self.postings.clear();
+        self.lengths.clear();
+        for (position, result) in results.iter().enumerate() {
+            let mut lengths = [0; 4];
+            for field in FIELDS.iter().copied() {
+                let terms = terms(&field.text(result));
+                lengths[field as usize] = terms.len() as u32;
+                let mut counts = HashMap::new();
+                for term in terms {
+                    *counts.entry(term).or_insert(0) += 1;
+                }
+                for (term, occurrences) in counts {
+                    self.postings
+                        .entry(term)
+                        .or_default()
+                        .push((position, field, occurrences));
+                }
+            }
+            self.lengths.push(lengths);
+        }
+        // postings of a term by result, see ranked
+        for postings in self.postings.values_mut() {
+            postings.sort_unstable_by_key(|(result, field, _)| (*result, *field as usize));
+        }
+        self.results = results;
+    }
+
+    fn to_json(&self) -> String {
+        let results = self
+            .results
+            .iter()
+            .map(|result| {
+                json!({
+                    "title": result.title,
+                    "authors": result.authors,
+                    "author": result.author,
+                    "venue": result.venue,
+                    "year": result.year,
+                    "abstract": result.abs,
+                    "link": result.link,
+                    "cluster_id": result.cluster_id.as_ref().map(ClusterId::as_str),
+                    "cite_id": result.cite_id.as_ref().map(CiteId::as_str),
+                    "cited_by": result.cited_by,
+                })
+            })
+            .collect();
+        let mut json = Value::Array(results).to_string();
+        json.push('\n');
+        json
+    }
+}
+
+// the fields the index keeps, see LocalIndex::add
+fn stored(result: &ScholarResult) -> ScholarResult {
+    ScholarResult {
+        title: result.title.clone(),
+        authors: result.authors.clone(),
+        author: result.author.clone(),
+        venue: result.venue.clone(),
+        year: result.year,
+        abs: result.abs.clone(),
+        link: result.link.clone(),
+        cluster_id: result.cluster_id.clone(),
+        cite_id: result.cite_id.clone(),
+        cited_by: result.cited_by,
+        ..Default::default()
+    }
+}
+
+fn parse_results(contents: &str) -> Option<Vec<ScholarResult>> {
+    let results = serde_json::from_str::<Value>(contents).ok()?;
+    results.as_array()?.iter().map(parse_result).collect()
+}
+
+fn parse_result(result: &Value) -> Option<ScholarResult> {
+    let text = |name| result.get(name).and_then(Value::as_str).map(str::to_string);
+    Some(ScholarResult {
+        title: text("title")?,
+        authors: result
+            .get("authors")?
+            .as_array()?
+            .iter()
+            .map(|author| author.as_str().map(str::to_string))
+            .collect::<Option<_>>()?,
+        author: text("author").unwrap_or_default(),
+        venue: text("venue"),
+        year: result
+            .get("year")
+            .and_then(Value::as_u64)
+            .and_then(|year| u16::try_from(year).ok()),
+        abs: text("abstract").unwrap_or_default(),
+        link: text("link").unwrap_or_default(),
+        cluster_id: text("cluster_id").map(ClusterId::from),
+        cite_id: text("cite_id").map(CiteId::from),
+        cited_by: result
+            .get("cited_by")
+            .and_then(Value::as_u64)
+            .and_then(|count| u32::try_from(count).ok()),
+        ..Default::default()
+    })
+}
+
+fn terms(text: &str) -> Vec<String> {
+    TextNormalizer::default()
+        .for_matching(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("gscholar-index-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn result(cluster: &str, title: &str, abs: &str, year: u16) -> ScholarResult {
+        ScholarResult {
+            title: title.to_string(),
+            abs: abs.to_string(),
+            authors: vec!["A Author".to_string()],
+            venue: Some("Journal of Examples".to_string()),
+            year: Some(year),
+            cluster_id: Some(cluster.parse().unwrap()),
+            ..Default::default()
+        }
+    }
+
+    fn fixtures() -> Vec<ScholarResult> {
+        vec![
+            result(
+                "1",
+                "Deep residual learning",
+                "We use contrastive losses for pretraining.",
+                2016,
+            ),
+            result(
+                "2",
+                "Contrastive learning of visual representations",
+                "A simple framework for self-supervised learning.",
+                2020,
+            ),
+            result(
+                "3",
+                "Attention is all you need",
+                "Sequence transduction with attention only.",
+                2017,
+            ),
+            result(
+                "4",
+                "Supervised contrastive learning",
+                "Contrastive losses with labels.",
+                2020,
+            ),
+        ]
+    }
+
+    fn clusters(found: &[(f32, ScholarResult)]) -> Vec<&str> {
+        found
+            .iter()
+            .map(|(_, result)| result.cluster_id.as_ref().unwrap().as_str())
+            .collect()
+    }
+
+    #[test]
+    fn ranks_title_matches_first() {
+        let dir = index_dir("ranks");
+        let mut index = LocalIndex::open(&dir).unwrap();
+        assert_eq!(index.add(fixtures()).unwrap(), 3 + 1);
+
+        let found = index.search("Contrastive", 10);
+
+        // 4 has the term in its title and abstract, 2 in its title, 1 only
+        // in its abstract
+        assert_eq!(clusters(&found), vec!["4", "2", "1"]);
+        assert!(found[0].0 > found[1].0 && found[1].0 > found[2].0);
+        assert_eq!(
+            found[1].1.title,
+            "Contrastive learning of visual representations"
+        );
+        assert_eq!(clusters(&index.search("contrastive", 1)), vec!["4"]);
+        assert!(index.search("transformers", 10).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn filters_by_year() {
+        let dir = index_dir("years");
+        let mut index = LocalIndex::open(&dir).unwrap();
+        index.add(fixtures()).unwrap();
+
+        let found = index.search_years("learning contrastive", 10, 2016..=2017);
+
+        assert_eq!(clusters(&found), vec!["1"]);
+        assert_eq!(
+            clusters(&index.search_years("attention", 10, 2018..=2024)),
+            Vec::<&str>::new()
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn incremental_adds_replace_by_cluster() {
+        let dir = index_dir("incremental");
+        let mut index = LocalIndex::open(&dir).unwrap();
+        index.add(fixtures()).unwrap();
+        drop(index);
+
+        let mut index = LocalIndex::open(&dir).unwrap();
+        assert_eq!(index.len(), 4);
+        let mut updated = result(
+            "3",
+            "Attention is all you need",
+            "Sequence transduction with attention only.",
+            2017,
+        );
+        updated.cited_by = Some(100_000);
+        let added = index
+            .add(vec![
+                updated,
+                result("5", "Contrastive predictive coding", "", 2018),
+            ])
+            .unwrap();
+
+        assert_eq!(added, 1);
+        assert_eq!(index.len(), 5);
+        let found = LocalIndex::open(&dir).unwrap().search("attention", 10);
+        assert_eq!(clusters(&found), vec!["3"]);
+        assert_eq!(found[0].1.cited_by, Some(100_000));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn corrupt_results_file_is_an_error() {
+        let dir = index_dir("corrupt");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(RESULTS_FILE), "not json").unwrap();
+
+        let opened = LocalIndex::open(&dir);
+
+        assert_eq!(
+            opened.err().map(|e| e.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,199 @@
+// Lightweight query language detection.
+//
+// Non-latin scripts are recognized from their unicode ranges. Latin text is
+// scored against short stopword lists and a few language specific letters;
+// a language wins only with at least two hits and a strict lead over the
+// runner up, so short or ambiguous text is left undetected.
+
+// (language, stopwords)
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "of", "and", "in", "on", "for", "with", "to", "from", "by", "is", "are", "an",
+            "a", "its", "how", "what", "between",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "von", "mit", "auf", "für", "im", "den", "ein", "eine",
+            "zur", "zum", "bei", "über", "des", "ist", "wie",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "de", "des", "et", "du", "une", "pour", "dans", "sur", "au", "aux",
+            "par", "est", "avec", "entre",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "de", "del", "y", "una", "para", "con", "por", "en", "sobre",
+            "es", "entre", "como",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "lo", "gli", "della", "delle", "dei", "e", "per", "con", "una", "nel", "nella",
+            "sulla", "tra",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "os", "as", "da", "do", "das", "dos", "e", "uma", "para", "com", "em", "na", "no",
+            "sobre", "entre",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "van", "en", "voor", "met", "op", "bij", "naar", "over", "tussen",
+        ],
+    ),
+];
+
+// letters that only show up in one of the latin languages above
+const LETTERS: &[(char, &str)] = &[
+    ('ß', "de"),
+    ('ä', "de"),
+    ('ö', "de"),
+    ('ü', "de"),
+    ('ç', "fr"),
+    ('è', "fr"),
+    ('ê', "fr"),
+    ('ñ', "es"),
+    ('ã', "pt"),
+    ('õ', "pt"),
+];
+
+// ISO 639-1 code of the language `text` is written in, if recognizable
+pub fn detect(text: &str) -> Option<&'static str> {
+    if let Some(lang) = detect_script(text) {
+        return Some(lang);
+    }
+
+    let text = text.to_lowercase();
+    let mut scores = STOPWORDS
+        .iter()
+        .map(|(lang, words)| {
+            let stopwords = text
+                .split(|c: char| !c.is_alphabetic())
+                .filter(|word| words.contains(word))
+                .count();
+            let letters = LETTERS
+                .iter()
+                .filter(|(letter, letter_lang)| letter_lang == lang && text.contains(*letter))
+                .count();
+            (*lang, stopwords + letters)
+        })
+        .collect::<Vec<_>>();
+    scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+
+    match scores.as_slice() {
+        [(lang, best), (_, second), ..] if *best >= 2 && best > second => Some(lang),
+        _ => None,
+    }
+}
+
+fn detect_script(text: &str) -> Option<&'static str> {
+    let mut han = 0;
+    let mut kana = 0;
+    let mut counts = [("ko", 0), ("ru", 0), ("el", 0), ("ar", 0), ("he", 0)];
+    for c in text.chars() {
+        match c {
+            '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' => han += 1,
+            '\u{3040}'..='\u{30ff}' => kana += 1,
+            '\u{ac00}'..='\u{d7af}' | '\u{1100}'..='\u{11ff}' => counts[0].1 += 1,
+            '\u{0400}'..='\u{04ff}' => counts[1].1 += 1,
+            '\u{0370}'..='\u{03ff}' => counts[2].1 += 1,
+            '\u{0600}'..='\u{06ff}' => counts[3].1 += 1,
+            '\u{0590}'..='\u{05ff}' => counts[4].1 += 1,
+            _ => {}
+        }
+    }
+    if kana > 0 {
+        return Some("ja");
+    }
+    if han > 0 {
+        return Some("zh");
+    }
+    counts
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .max_by_key(|(_, count)| *count)
+        .map(|(lang, _)| *lang)
+}
+
+// (detected language, hl, lr with english)
+const SCHOLAR_LANGUAGES: &[(&str, &str, &str)] = &[
+    ("de", "de", "lang_de|lang_en"),
+    ("fr", "fr", "lang_fr|lang_en"),
+    ("es", "es", "lang_es|lang_en"),
+    ("it", "it", "lang_it|lang_en"),
+    ("pt", "pt-BR", "lang_pt|lang_en"),
+    ("nl", "nl", "lang_nl|lang_en"),
+    ("zh", "zh-CN", "lang_zh-CN|lang_zh-TW|lang_en"),
+    ("ja", "ja", "lang_ja|lang_en"),
+    ("ko", "ko", "lang_ko|lang_en"),
+    ("ru", "ru", "lang_ru|lang_en"),
+    ("el", "el", "lang_el|lang_en"),
+    ("ar", "ar", "lang_ar|lang_en"),
+    ("he", "iw", "lang_iw|lang_en"),
+];
+
+// scholar's hl and lr values for a detected language, None for english or
+// languages scholar has no interface for
+pub(crate) fn scholar_params(lang: &str) -> Option<(&'static str, &'static str)> {
+    SCHOLAR_LANGUAGES
+        .iter()
+        .find(|(code, _, _)| *code == lang)
+        .map(|(_, hl, lr)| (*hl, *lr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_languages() {
+        let cases = [
+            (
+                "Auswirkungen der Digitalisierung auf die Arbeitswelt",
+                Some("de"),
+            ),
+            ("深度学习在医学图像分析中的应用", Some("zh")),
+            ("ニューラルネットワークの学習", Some("ja")),
+            ("Машинное обучение", Some("ru")),
+            (
+                "L'impact du changement climatique sur les forêts",
+                Some("fr"),
+            ),
+            (
+                "el impacto de la inteligencia artificial en la educación",
+                Some("es"),
+            ),
+            ("the effect of sleep on memory consolidation", Some("en")),
+            ("graph isomorphism", None),
+            ("die", None),
+            ("", None),
+        ];
+        for (text, lang) in cases.iter() {
+            assert_eq!(detect(text), *lang, "detecting {}", text);
+        }
+    }
+
+    #[test]
+    fn scholar_params_for_languages() {
+        assert_eq!(scholar_params("de"), Some(("de", "lang_de|lang_en")));
+        assert_eq!(
+            scholar_params("zh"),
+            Some(("zh-CN", "lang_zh-CN|lang_zh-TW|lang_en"))
+        );
+        assert_eq!(scholar_params("en"), None);
+    }
+}
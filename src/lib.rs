@@ -1,4 +1,29 @@
+pub mod export;
+pub mod format;
+mod global;
+#[cfg(feature = "index")]
+pub mod index;
+#[cfg(feature = "lang-detect")]
+pub mod lang;
+#[cfg(feature = "bib")]
+pub mod library;
+pub mod metrics;
+pub mod names;
+pub mod normalize;
 pub mod scholar;
+pub mod scoring;
+pub mod stats;
+#[cfg(feature = "tracker")]
+pub mod tracker;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod urlnorm;
+#[cfg(feature = "venue-normalize")]
+pub mod venue;
+#[cfg(feature = "xlsx")]
+mod zip;
+
+pub use self::global::{search, search_args, set_default_client, DEFAULT_DELAY};
 
 #[cfg(test)]
 mod tests {
@@ -6,7 +31,7 @@ mod tests {
     #[test]
     fn new_scholar_query() {
         let sc = scholar::ScholarArgs {
-            query: "machine-learning",
+            query: "machine-learning".to_string(),
             cite_id: None,
             from_year: None,
             to_year: None,
@@ -19,14 +44,17 @@ mod tests {
             adult_filtering: None,
             include_similar_results: None,
             include_citations: None,
+            review_articles: None,
+            legal_scope: None,
         };
         assert_eq!(sc.query, "machine-learning");
     }
 
     #[tokio::test]
+    #[ignore = "requires access to scholar.google.com"]
     async fn scrape() {
         let sc = scholar::ScholarArgs {
-            query: "machine-learning",
+            query: "machine-learning".to_string(),
             cite_id: None,
             from_year: None,
             to_year: None,
@@ -39,6 +67,8 @@ mod tests {
             adult_filtering: None,
             include_similar_results: None,
             include_citations: None,
+            review_articles: None,
+            legal_scope: None,
         };
 
         let client = scholar::init_client();
@@ -0,0 +1,445 @@
+// Checks search results against a BibTeX library, eg: references.bib, to
+// tell the papers already in it from the new ones.
+//
+// A result and an entry match by DOI when both have one. Otherwise their
+// titles are compared: the Dice coefficient of the character bigrams of the
+// titles after TextNormalizer::for_matching, from 0 (nothing in common) to
+// 1 (same title). At TITLE_MATCH or above, and with years at most one apart
+// when both are known (preprint and publication), the result is known. From
+// NEAR_MISS up, or a title match with years further apart, the pair is left
+// for review instead of being decided either way.
+
+use std::collections::HashSet;
+
+use crate::normalize::TextNormalizer;
+use crate::scholar::ScholarResult;
+use crate::urlnorm::find_doi;
+
+// title similarity from which a result is taken to be the entry
+pub const TITLE_MATCH: f64 = 0.9;
+// title similarity from which a result is reported as a near miss
+pub const NEAR_MISS: f64 = 0.7;
+
+// An entry of a BibTeX file. Field names are lowercase, values are as
+// written without their outer braces or quotes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BibEntry {
+    // eg: "article"
+    pub entry_type: String,
+    pub key: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl BibEntry {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchKind {
+    Doi,
+    // title similarity, see the module comment
+    Title(f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct KnownResult {
+    pub result: ScholarResult,
+    // key of the matching entry
+    pub bib_key: String,
+    pub matched_by: MatchKind,
+}
+
+// a result that resembles an entry too little, or with too distant a year,
+// to be decided
+#[derive(Debug, Clone)]
+pub struct NearMiss {
+    pub result: ScholarResult,
+    // key of the most similar entry
+    pub bib_key: String,
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DedupReport {
+    pub already_known: Vec<KnownResult>,
+    pub new: Vec<ScholarResult>,
+    pub near_misses: Vec<NearMiss>,
+}
+
+// Splits `results` by whether `bib_source`, the text of a BibTeX file, has
+// them. Each result lands in exactly one list of the report, in the order
+// of `results`.
+pub fn dedup_against_bibtex(results: &[ScholarResult], bib_source: &str) -> DedupReport {
+    let normalizer = TextNormalizer::default();
+    let entries = parse_bibtex(bib_source)
+        .into_iter()
+        .map(|entry| {
+            let doi = entry.field("doi").and_then(find_doi);
+            let title = entry
+                .field("title")
+                .map(|title| bigrams(&normalizer.for_matching(&strip_latex(title))))
+                .unwrap_or_default();
+            let year = entry
+                .field("year")
+                .and_then(|year| year.trim().parse::<u16>().ok());
+            (entry.key, doi, title, year)
+        })
+        .collect::<Vec<_>>();
+
+    let mut report = DedupReport::default();
+    for result in results {
+        let doi = result_doi(result);
+        let by_doi = entries
+            .iter()
+            .find(|(_, entry_doi, _, _)| doi.is_some() && entry_doi.is_some() && doi == *entry_doi);
+        if let Some((key, ..)) = by_doi {
+            report.already_known.push(KnownResult {
+                result: result.clone(),
+                bib_key: key.clone(),
+                matched_by: MatchKind::Doi,
+            });
+            continue;
+        }
+
+        let title = bigrams(&normalizer.for_matching(&result.title));
+        let best = entries
+            .iter()
+            .map(|(key, _, entry_title, year)| (key, dice(&title, entry_title), year))
+            .fold(
+                None,
+                |best: Option<(&String, f64, &Option<u16>)>, candidate| match best {
+                    Some(best) if best.1 >= candidate.1 => Some(best),
+                    _ => Some(candidate),
+                },
+            );
+        match best {
+            Some((key, similarity, year)) if similarity >= NEAR_MISS => {
+                let years_agree = match (result.year, *year) {
+                    (Some(a), Some(b)) => a.abs_diff(b) <= 1,
+                    _ => true,
+                };
+                if similarity >= TITLE_MATCH && years_agree {
+                    report.already_known.push(KnownResult {
+                        result: result.clone(),
+                        bib_key: key.clone(),
+                        matched_by: MatchKind::Title(similarity),
+                    });
+                } else {
+                    report.near_misses.push(NearMiss {
+                        result: result.clone(),
+                        bib_key: key.clone(),
+                        similarity,
+                    });
+                }
+            }
+            _ => report.new.push(result.clone()),
+        }
+    }
+    report
+}
+
+// The entries of a BibTeX file. @comment, @string and @preamble are
+// skipped, string macros are not expanded and an entry cut short by the end
+// of the file keeps the fields read so far.
+pub fn parse_bibtex(source: &str) -> Vec<BibEntry> {
+    let mut entries = Vec::new();
+    let mut rest = source;
+    while let Some(at) = rest.find('@') {
+        rest = &rest[at + 1..];
+        let open = match rest.find(['{', '(']) {
+            Some(open) => open,
+            None => break,
+        };
+        let entry_type = rest[..open].trim().to_lowercase();
+        let close = if rest[open..].starts_with('(') {
+            ')'
+        } else {
+            '}'
+        };
+        rest = &rest[open + 1..];
+        if ["comment", "string", "preamble"].contains(&entry_type.as_str()) {
+            // skip the whole body, an '@' in it doesn't start an entry
+            rest = delimited(rest, close).1;
+            continue;
+        }
+        if entry_type.is_empty() || entry_type.contains(char::is_whitespace) {
+            continue;
+        }
+        let key_end = rest.find([',', '}', ')']).unwrap_or(rest.len());
+        let key = rest[..key_end].trim().to_string();
+        rest = rest[key_end..]
+            .strip_prefix(',')
+            .unwrap_or(&rest[key_end..]);
+
+        let mut fields = Vec::new();
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+            if rest.is_empty() || rest.starts_with(['}', ')']) {
+                rest = rest.get(1..).unwrap_or_default();
+                break;
+            }
+            let eq = match rest.find('=') {
+                Some(eq) => eq,
+                None => break,
+            };
+            let name = rest[..eq].trim().to_lowercase();
+            let (value, after) = field_value(&rest[eq + 1..]);
+            rest = after;
+            fields.push((name, value));
+        }
+        entries.push(BibEntry {
+            entry_type,
+            key,
+            fields,
+        });
+    }
+    entries
+}
+
+// a field value: {braced}, "quoted" or bare pieces joined with #, and what
+// follows it
+fn field_value(input: &str) -> (String, &str) {
+    let mut value = String::new();
+    let mut rest = input.trim_start();
+    loop {
+        let (piece, after) = match rest.chars().next() {
+            Some('{') => delimited(&rest[1..], '}'),
+            Some('"') => delimited(&rest[1..], '"'),
+            _ => {
+                let end = rest.find([',', '}', ')', '#']).unwrap_or(rest.len());
+                (rest[..end].trim(), &rest[end..])
+            }
+        };
+        value.push_str(piece);
+        rest = after.trim_start();
+        match rest.strip_prefix('#') {
+            Some(after) => rest = after.trim_start(),
+            None => return (value, rest),
+        }
+    }
+}
+
+// text up to the `close` at brace depth 0, and what follows it
+fn delimited(input: &str, close: char) -> (&str, &str) {
+    let mut depth = 0usize;
+    for (i, c) in input.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            c if c == close && depth == 0 => return (&input[..i], &input[i + 1..]),
+            _ => {}
+        }
+    }
+    (input, "")
+}
+
+// title without the braces protecting capitals and with escaped characters
+// unescaped, eg: "{BERT}: Pre-training \& more" -> "BERT: Pre-training & more"
+fn strip_latex(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' => {}
+            '\\' => match chars.peek() {
+                Some(next) if !next.is_alphabetic() => {
+                    stripped.push(*next);
+                    chars.next();
+                }
+                _ => {
+                    // a command such as \emph, keep its argument
+                    while chars.peek().is_some_and(|c| c.is_alphabetic()) {
+                        chars.next();
+                    }
+                }
+            },
+            c => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+// DOI of a result, from its link or source links, eg:
+// https://dl.acm.org/doi/abs/10.1145/3065386
+fn result_doi(result: &ScholarResult) -> Option<String> {
+    std::iter::once(result.link.as_str())
+        .chain(result.source_links.iter().map(|source| source.url.as_str()))
+        .find_map(find_doi)
+}
+
+fn bigrams(text: &str) -> HashSet<(char, char)> {
+    let chars = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+fn dice(a: &HashSet<(char, char)>, b: &HashSet<(char, char)>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::SourceLink;
+
+    const LIBRARY: &str = r#"
+% my references
+@comment{jabref-meta: databaseType:bibtex;}
+@comment{dropped: @misc{draft, title = {Draft}}, ask {jane@example.org}}
+@preamble("\\newcommand{\\at}{@}")
+@string{nips = "Advances in Neural Information Processing Systems"}
+
+@inproceedings{krizhevsky2012,
+  title = {{ImageNet} Classification with Deep Convolutional Neural Networks},
+  author = {Krizhevsky, Alex and Sutskever, Ilya and Hinton, Geoffrey E},
+  booktitle = nips,
+  year = 2012,
+  doi = {https://doi.org/10.1145/3065386},
+}
+
+@article{vaswani2017attention,
+  title = "Attention Is All You {N}eed",
+  author = "Vaswani, Ashish and others",
+  year = "2017"
+}
+
+@book{knuth1984,
+  title = {The {TeX}book},
+  year = {1984},
+}
+"#;
+
+    fn result(title: &str, year: Option<u16>, link: &str) -> ScholarResult {
+        ScholarResult {
+            title: title.to_string(),
+            year,
+            link: link.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_entries() {
+        let entries = parse_bibtex(LIBRARY);
+
+        let keys = entries.iter().map(|e| e.key.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            vec!["krizhevsky2012", "vaswani2017attention", "knuth1984"]
+        );
+        assert_eq!(entries[0].entry_type, "inproceedings");
+        assert_eq!(
+            entries[0].field("title"),
+            Some("{ImageNet} Classification with Deep Convolutional Neural Networks")
+        );
+        assert_eq!(entries[0].field("booktitle"), Some("nips"));
+        assert_eq!(entries[0].field("year"), Some("2012"));
+        assert_eq!(
+            entries[1].field("author"),
+            Some("Vaswani, Ashish and others")
+        );
+        assert_eq!(entries[2].field("TITLE"), Some("The {TeX}book"));
+        assert_eq!(
+            strip_latex("\\emph{Deep} {L}earning \\& more"),
+            "Deep Learning & more"
+        );
+    }
+
+    #[test]
+    fn dedup_report() {
+        let mut by_doi = result(
+            "Imagenet classification with deep convolutional networks",
+            Some(2017),
+            "https://example.org/landing",
+        );
+        by_doi.source_links.push(SourceLink {
+            label: "[PDF] acm.org".to_string(),
+            url: "https://dl.acm.org/doi/pdf/10.1145/3065386".to_string(),
+            ..Default::default()
+        });
+        let results = vec![
+            by_doi,
+            result(
+                "Attention is all you need",
+                Some(2018),
+                "https://arxiv.org/abs/1706.03762",
+            ),
+            result(
+                "Attention is all you need in speech separation",
+                Some(2021),
+                "",
+            ),
+            result("The TeXbook", Some(1986), ""),
+            result(
+                "Deep residual learning for image recognition",
+                Some(2016),
+                "",
+            ),
+        ];
+
+        let report = dedup_against_bibtex(&results, LIBRARY);
+
+        let known = report
+            .already_known
+            .iter()
+            .map(|k| {
+                (
+                    k.result.title.as_str(),
+                    k.bib_key.as_str(),
+                    k.matched_by.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(known.len(), 2);
+        assert_eq!(known[0].1, "krizhevsky2012");
+        assert_eq!(known[0].2, MatchKind::Doi);
+        assert_eq!(known[1].1, "vaswani2017attention");
+        assert_eq!(known[1].2, MatchKind::Title(1.0));
+
+        let near = report
+            .near_misses
+            .iter()
+            .map(|n| (n.result.title.as_str(), n.bib_key.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            near,
+            vec![
+                (
+                    "Attention is all you need in speech separation",
+                    "vaswani2017attention"
+                ),
+                // same title, 2 years apart
+                ("The TeXbook", "knuth1984"),
+            ]
+        );
+        assert!(report.near_misses.iter().all(|n| n.similarity >= NEAR_MISS));
+
+        let new = report
+            .new
+            .iter()
+            .map(|r| r.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(new, vec!["Deep residual learning for image recognition"]);
+    }
+
+    #[test]
+    fn empty_library() {
+        let results = vec![result("Attention is all you need", Some(2017), "")];
+
+        let report = dedup_against_bibtex(&results, "");
+
+        assert!(report.already_known.is_empty());
+        assert!(report.near_misses.is_empty());
+        assert_eq!(report.new.len(), 1);
+    }
+}
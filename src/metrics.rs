@@ -0,0 +1,103 @@
+// Citation metrics computed locally from parsed results.
+
+use crate::scholar::ScholarResult;
+
+// Largest h such that h of the citation counts are at least h.
+pub fn h_index(citations: impl IntoIterator<Item = u32>) -> u32 {
+    let mut citations = citations.into_iter().collect::<Vec<_>>();
+    citations.sort_unstable_by(|a, b| b.cmp(a));
+    citations
+        .iter()
+        .zip(1..)
+        .take_while(|(count, rank)| **count >= *rank)
+        .count() as u32
+}
+
+// h_index over the "Cited by" counts of results, uncited results count as 0
+pub fn h_index_of(results: &[ScholarResult]) -> u32 {
+    h_index(results.iter().map(|result| result.cited_by.unwrap_or(0)))
+}
+
+// Largest g such that the g most cited papers have at least g² citations
+// together. g is at most the number of papers, no zero padding.
+pub fn g_index(citations: impl IntoIterator<Item = u32>) -> u32 {
+    let mut citations = citations.into_iter().collect::<Vec<_>>();
+    citations.sort_unstable_by(|a, b| b.cmp(a));
+    let mut sum = 0u64;
+    citations
+        .iter()
+        .zip(1u64..)
+        .take_while(|(count, rank)| {
+            sum += u64::from(**count);
+            sum >= rank * rank
+        })
+        .count() as u32
+}
+
+// g_index over the "Cited by" counts of results, uncited results count as 0
+pub fn g_index_of(results: &[ScholarResult]) -> u32 {
+    g_index(results.iter().map(|result| result.cited_by.unwrap_or(0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn h_index_sequences() {
+        let cases: [(&[u32], u32); 8] = [
+            (&[], 0),
+            (&[0, 0, 0], 0),
+            (&[1], 1),
+            (&[10], 1),
+            (&[3, 0, 6, 1, 5], 3),
+            (&[4, 4, 4, 4], 4),
+            (&[5, 5, 5, 5, 5, 5], 5),
+            (&[25, 8, 5, 3, 3], 3),
+        ];
+        for (citations, expected) in cases.iter() {
+            assert_eq!(
+                h_index(citations.iter().copied()),
+                *expected,
+                "h-index of {:?}",
+                citations
+            );
+        }
+    }
+
+    #[test]
+    fn g_index_sequences() {
+        let cases: [(&[u32], u32); 7] = [
+            (&[], 0),
+            (&[0, 0], 0),
+            (&[1], 1),
+            (&[100], 1),
+            (&[3, 0, 6, 1, 5], 3),
+            (&[10, 8, 5, 4, 3], 5),
+            (&[25, 8, 5, 3, 3], 5),
+        ];
+        for (citations, expected) in cases.iter() {
+            assert_eq!(
+                g_index(citations.iter().copied()),
+                *expected,
+                "g-index of {:?}",
+                citations
+            );
+        }
+    }
+
+    #[test]
+    fn h_index_of_results() {
+        let results = [Some(12), None, Some(2), Some(7)]
+            .iter()
+            .map(|cited_by| ScholarResult {
+                cited_by: *cited_by,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(h_index_of(&results), 2);
+        assert_eq!(h_index_of(&[]), 0);
+        assert_eq!(g_index_of(&results), 4);
+    }
+}
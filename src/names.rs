@@ -0,0 +1,373 @@
+// Person names as written in bylines and reference lists, and the usual
+// initials-compatible comparison between them: "J. R. Smith",
+// "John R Smith" and "Smith, John" may all be the same person.
+
+use std::fmt;
+
+use crate::normalize::TextNormalizer;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PersonName {
+    // first given name or its initial, without the period, eg: "John" or
+    // "J". Empty for single word names
+    pub given: String,
+    // further given names or initials, eg: ["R"] for "John R. Smith"
+    pub middle: Vec<String>,
+    // with its particles, eg: "van Beethoven" or "Smith-Jones"
+    pub family: String,
+    // eg: "Jr" or "III"
+    pub suffix: Option<String>,
+}
+
+// lowercase words that belong to the family name that follows them
+const PARTICLES: &[&str] = &[
+    "al", "bin", "da", "das", "de", "del", "della", "den", "der", "des", "di", "do", "dos", "du",
+    "el", "ibn", "la", "le", "st", "ten", "ter", "van", "von", "zu",
+];
+
+const SUFFIXES: &[&str] = &["jr", "sr", "ii", "iii", "iv"];
+
+// Reads "Given Middle Family" and "Family, Given Middle" orders, with an
+// optional suffix ("Jr.", ", III"). Initials may come with or without
+// periods, or run together like scholar writes them ("JR Smith"). A first
+// word of more than three capitals followed by one that isn't is taken as
+// the family name written first, eg: "ZHANG Wei". Otherwise family-first
+// names such as "Zhang Wei" can't be told apart and are read as
+// given-first.
+pub fn parse_name(name: &str) -> PersonName {
+    let name = name
+        .trim()
+        .trim_end_matches('…')
+        .trim_end_matches("...")
+        .trim();
+    let parts = name
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    match parts.as_slice() {
+        [] => PersonName::default(),
+        [natural] => parse_natural(natural),
+        // "John Smith, Jr."
+        [natural, suffix] if is_suffix(suffix) => PersonName {
+            suffix: Some(clean_suffix(suffix)),
+            ..parse_natural(natural)
+        },
+        // "Smith, John R." or "Smith, John, Jr."
+        [family, given, rest @ ..] => {
+            let mut words = given.split_whitespace().collect::<Vec<_>>();
+            let mut suffix = rest
+                .iter()
+                .find(|part| is_suffix(part))
+                .map(|s| clean_suffix(s));
+            if words.len() > 1 && words.last().is_some_and(|w| is_suffix(w)) {
+                suffix = words.pop().map(clean_suffix);
+            }
+            PersonName {
+                family: words_of(family).join(" "),
+                suffix,
+                ..given_names(&words)
+            }
+        }
+    }
+}
+
+// Whether `a` and `b` can name the same person: same family name, no
+// conflicting given names (an initial matches any name it starts) and no
+// conflicting suffix. Missing given or middle names don't conflict.
+// Case, accents, hyphens and periods are ignored.
+pub fn compatible(a: &str, b: &str) -> bool {
+    parse_name(a).compatible_with(&parse_name(b))
+}
+
+// `name` in the canonical form of PersonName's Display, so "JA Smith",
+// "J A Smith" and "J. A. Smith" all become "JA Smith"
+pub fn canonical(name: &str) -> String {
+    let parsed = parse_name(name);
+    if parsed.family.is_empty() {
+        return name.trim().to_string();
+    }
+    parsed.to_string()
+}
+
+impl PersonName {
+    pub fn compatible_with(&self, other: &PersonName) -> bool {
+        if key(&self.family) != key(&other.family) {
+            return false;
+        }
+        if let (Some(a), Some(b)) = (&self.suffix, &other.suffix) {
+            if key(a) != key(b) {
+                return false;
+            }
+        }
+        self.given_names()
+            .zip(other.given_names())
+            .all(|(a, b)| given_compatible(a, b))
+    }
+
+    // given and middle names are all initials, eg: "J. R. Smith"
+    pub fn is_abbreviated(&self) -> bool {
+        self.given_names().all(is_initial)
+    }
+
+    fn given_names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.given.as_str())
+            .chain(self.middle.iter().map(String::as_str))
+            .filter(|name| !name.is_empty())
+    }
+}
+
+// Given names first and the suffix last, without periods or commas so the
+// name can sit in a comma separated byline. Runs of initials are written
+// together the way scholar does: "JA Smith", "John A Smith",
+// "J-P Sartre", "ML King Jr".
+impl fmt::Display for PersonName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut previous_initial = false;
+        for (i, name) in self.given_names().enumerate() {
+            let initial = name.chars().count() == 1;
+            if i > 0 && !(initial && previous_initial) {
+                f.write_str(" ")?;
+            }
+            f.write_str(name)?;
+            previous_initial = initial;
+        }
+        if !self.given.is_empty() || !self.middle.is_empty() {
+            f.write_str(" ")?;
+        }
+        f.write_str(&self.family)?;
+        if let Some(suffix) = &self.suffix {
+            write!(f, " {}", suffix)?;
+        }
+        Ok(())
+    }
+}
+
+// the family name in the form compared by compatible
+pub(crate) fn family_key(name: &PersonName) -> String {
+    key(&name.family)
+}
+
+fn parse_natural(text: &str) -> PersonName {
+    let mut words = words_of(text);
+    let mut suffix = None;
+    if words.len() > 2 && words.last().is_some_and(|w| is_suffix(w)) {
+        suffix = words.pop().map(clean_suffix);
+    }
+    if words.len() == 1 {
+        return PersonName {
+            family: words[0].to_string(),
+            suffix,
+            ..PersonName::default()
+        };
+    }
+
+    let family_first = words.len() > 1
+        && is_capitals(words[0])
+        && words[0].chars().filter(|c| c.is_alphabetic()).count() > 3
+        && !words[1..].iter().all(|w| is_capitals(w));
+    if family_first {
+        return PersonName {
+            family: words[0].to_string(),
+            suffix,
+            ..given_names(&words[1..])
+        };
+    }
+
+    let mut start = words.len().saturating_sub(1);
+    while start > 1 && PARTICLES.contains(&words[start - 1]) {
+        start -= 1;
+    }
+    PersonName {
+        family: words[start..].join(" "),
+        suffix,
+        ..given_names(&words[..start])
+    }
+}
+
+// "J.R." -> ["J", "R"], "JR" -> ["J", "R"], "J.-P." -> ["J-P"]
+fn given_names(words: &[&str]) -> PersonName {
+    let mut names = Vec::new();
+    for word in words {
+        if word.contains('-') {
+            names.push(word.replace('.', ""));
+        } else if word.contains('.') {
+            names.extend(
+                word.split('.')
+                    .filter(|part| !part.is_empty())
+                    .map(str::to_string),
+            );
+        } else if is_capitals(word) && (2..=3).contains(&word.chars().count()) {
+            names.extend(word.chars().map(String::from));
+        } else {
+            names.push(word.to_string());
+        }
+    }
+    let mut names = names.into_iter();
+    PersonName {
+        given: names.next().unwrap_or_default(),
+        middle: names.collect(),
+        ..PersonName::default()
+    }
+}
+
+fn given_compatible(a: &str, b: &str) -> bool {
+    let (ka, kb) = (key(a), key(b));
+    if ka.chars().count() == 1 || kb.chars().count() == 1 {
+        return ka.chars().next() == kb.chars().next();
+    }
+    if is_initial(a) || is_initial(b) {
+        return initials(a) == initials(b);
+    }
+    ka == kb
+}
+
+// "J" or "J-P"
+fn is_initial(name: &str) -> bool {
+    name.split('-').all(|part| part.chars().count() <= 1)
+}
+
+fn initials(name: &str) -> String {
+    name.split('-')
+        .filter_map(|part| part.chars().next())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn is_capitals(word: &str) -> bool {
+    word.chars().all(|c| c.is_uppercase() || c == '-')
+}
+
+fn is_suffix(word: &str) -> bool {
+    SUFFIXES.contains(&clean_suffix(word).to_lowercase().as_str())
+}
+
+fn clean_suffix(word: &str) -> String {
+    word.trim().trim_end_matches('.').to_string()
+}
+
+fn words_of(text: &str) -> Vec<&str> {
+    text.split_whitespace().collect()
+}
+
+// lowercase, without accents, hyphens, spaces, periods and apostrophes
+fn key(text: &str) -> String {
+    TextNormalizer::default()
+        .for_matching(text)
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(given: &str, middle: &[&str], family: &str, suffix: Option<&str>) -> PersonName {
+        PersonName {
+            given: given.to_string(),
+            middle: middle.iter().map(|m| m.to_string()).collect(),
+            family: family.to_string(),
+            suffix: suffix.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn parse_name_table() {
+        let cases = [
+            ("John R. Smith", name("John", &["R"], "Smith", None)),
+            ("Smith, John R.", name("John", &["R"], "Smith", None)),
+            ("J. R. Smith", name("J", &["R"], "Smith", None)),
+            ("J.R. Smith", name("J", &["R"], "Smith", None)),
+            ("JR Smith", name("J", &["R"], "Smith", None)),
+            ("Y LeCun", name("Y", &[], "LeCun", None)),
+            ("G Hinton…", name("G", &[], "Hinton", None)),
+            ("Jean-Paul Sartre", name("Jean-Paul", &[], "Sartre", None)),
+            ("J.-P. Sartre", name("J-P", &[], "Sartre", None)),
+            ("Anne Smith-Jones", name("Anne", &[], "Smith-Jones", None)),
+            (
+                "Ludwig van Beethoven",
+                name("Ludwig", &[], "van Beethoven", None),
+            ),
+            ("Juan de la Cruz", name("Juan", &[], "de la Cruz", None)),
+            (
+                "van Beethoven, Ludwig",
+                name("Ludwig", &[], "van Beethoven", None),
+            ),
+            (
+                "Martin Luther King Jr.",
+                name("Martin", &["Luther"], "King", Some("Jr")),
+            ),
+            (
+                "King, Martin Luther, Jr.",
+                name("Martin", &["Luther"], "King", Some("Jr")),
+            ),
+            ("John Smith, III", name("John", &[], "Smith", Some("III"))),
+            ("Plato", name("", &[], "Plato", None)),
+            ("ZHANG Wei", name("Wei", &[], "ZHANG", None)),
+            ("Zhang, Wei", name("Wei", &[], "Zhang", None)),
+            // without a marker the order can't be known
+            ("Zhang Wei", name("Zhang", &[], "Wei", None)),
+            ("", PersonName::default()),
+        ];
+        for (input, expected) in cases.iter() {
+            assert_eq!(parse_name(input), *expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn canonical_forms() {
+        for spelling in [
+            "JA Smith",
+            "J A Smith",
+            "J. A. Smith",
+            "J.A. Smith",
+            "Smith, J. A.",
+        ]
+        .iter()
+        {
+            assert_eq!(canonical(spelling), "JA Smith", "{:?}", spelling);
+        }
+        let cases = [
+            ("John A. Smith", "John A Smith"),
+            ("J. Alan Smith", "J Alan Smith"),
+            ("J.-P. Sartre", "J-P Sartre"),
+            ("King, Martin L., Jr.", "Martin L King Jr"),
+            ("Ludwig van Beethoven", "Ludwig van Beethoven"),
+            ("  Plato ", "Plato"),
+            ("", ""),
+        ];
+        for (input, expected) in cases.iter() {
+            assert_eq!(canonical(input), *expected, "{:?}", input);
+            // stable once canonical
+            assert_eq!(canonical(expected), *expected, "{:?}", expected);
+        }
+    }
+
+    #[test]
+    fn compatible_table() {
+        let cases = [
+            ("J. R. Smith", "John R Smith", true),
+            ("Smith, John", "John R Smith", true),
+            ("JR Smith", "John Smith", true),
+            ("JR Smith", "John Q Smith", false),
+            ("Jane Smith", "John Smith", false),
+            ("J Smith", "J Smythe", false),
+            ("José García", "Jose Garcia", true),
+            ("J.-P. Sartre", "Jean-Paul Sartre", true),
+            ("J Sartre", "Jean-Paul Sartre", true),
+            ("J.-M. Sartre", "Jean-Paul Sartre", false),
+            ("M L King Jr", "Martin Luther King Sr", false),
+            ("M L King Jr", "Martin Luther King", true),
+            ("Plato", "Plato", true),
+            ("ZHANG Wei", "Wei Zhang", true),
+            ("Anne Smith-Jones", "A. Smith-Jones", true),
+            ("Anne Smith-Jones", "Anne Jones", false),
+            ("Ludwig van Beethoven", "L Beethoven", false),
+        ];
+        for (a, b, expected) in cases.iter() {
+            assert_eq!(compatible(a, b), *expected, "{:?} ~ {:?}", a, b);
+            assert_eq!(compatible(b, a), *expected, "{:?} ~ {:?}", b, a);
+        }
+    }
+}
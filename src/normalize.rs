@@ -0,0 +1,198 @@
+// Cleanup of scraped text: ligatures, soft hyphens, typographic quotes and
+// decomposed accents that make equal titles compare differently.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextNormalizer {
+    // compose letter + combining accent into one character (NFC) for the
+    // latin accents, eg: "e\u{301}" -> "é"
+    pub compose: bool,
+    // drop soft hyphens (U+00AD) left by hyphenated pdf text
+    pub soft_hyphens: bool,
+    // "ﬁ" -> "fi", "ﬂ" -> "fl", ...
+    pub ligatures: bool,
+    // curly quotes -> ' and "
+    pub straight_quotes: bool,
+    // "é" -> "e", only applied by for_matching
+    pub fold_diacritics: bool,
+}
+
+impl Default for TextNormalizer {
+    fn default() -> Self {
+        TextNormalizer {
+            compose: true,
+            soft_hyphens: true,
+            ligatures: true,
+            straight_quotes: true,
+            fold_diacritics: true,
+        }
+    }
+}
+
+// combining accent, the letters it composes with and the composed letters,
+// in the same order
+const COMPOSITIONS: &[(char, &str, &str)] = &[
+    ('\u{300}', "AaEeIiNnOoUu", "ÀàÈèÌìǸǹÒòÙù"),
+    (
+        '\u{301}',
+        "AaCcEeGgIiLlNnOoRrSsUuYyZz",
+        "ÁáĆćÉéǴǵÍíĹĺŃńÓóŔŕŚśÚúÝýŹź",
+    ),
+    (
+        '\u{302}',
+        "AaCcEeGgHhIiJjOoSsUuWwYy",
+        "ÂâĈĉÊêĜĝĤĥÎîĴĵÔôŜŝÛûŴŵŶŷ",
+    ),
+    ('\u{303}', "AaIiNnOoUu", "ÃãĨĩÑñÕõŨũ"),
+    ('\u{304}', "AaEeIiOoUuYy", "ĀāĒēĪīŌōŪūȲȳ"),
+    ('\u{306}', "AaEeGgIiOoUu", "ĂăĔĕĞğĬĭŎŏŬŭ"),
+    ('\u{307}', "AaCcEeGgIOoZz", "ȦȧĊċĖėĠġİȮȯŻż"),
+    ('\u{308}', "AaEeIiOoUuYy", "ÄäËëÏïÖöÜüŸÿ"),
+    ('\u{30A}', "AaUu", "ÅåŮů"),
+    ('\u{30B}', "OoUu", "ŐőŰű"),
+    (
+        '\u{30C}',
+        "AaCcDdEeGgHhIijKkLlNnOoRrSsTtUuZz",
+        "ǍǎČčĎďĚěǦǧȞȟǏǐǰǨǩĽľŇňǑǒŘřŠšŤťǓǔŽž",
+    ),
+    ('\u{327}', "CcEeGgKkLlNnRrSsTt", "ÇçȨȩĢģĶķĻļŅņŖŗŞşŢţ"),
+    ('\u{328}', "AaEeIiOoUu", "ĄąĘęĮįǪǫŲų"),
+];
+
+impl TextNormalizer {
+    // text with the enabled steps applied, except fold_diacritics
+    pub fn normalize(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            if self.soft_hyphens && c == '\u{AD}' {
+                continue;
+            }
+            if let Some(expanded) = ligature(c).filter(|_| self.ligatures) {
+                out.push_str(expanded);
+                continue;
+            }
+            let c = if self.straight_quotes {
+                straighten(c)
+            } else {
+                c
+            };
+            if self.compose && is_combining_accent(c) {
+                if let Some(composed) = out.chars().last().and_then(|base| compose(base, c)) {
+                    out.pop();
+                    out.push(composed);
+                    continue;
+                }
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    // normalize, then lowercased and, with fold_diacritics, without accents.
+    // For comparing titles and names, not for display.
+    pub fn for_matching(&self, text: &str) -> String {
+        let normalized = self.normalize(text).to_lowercase();
+        if !self.fold_diacritics {
+            return normalized;
+        }
+        normalized
+            .chars()
+            .filter(|c| !is_combining_accent(*c))
+            .map(fold)
+            .collect()
+    }
+}
+
+fn ligature(c: char) -> Option<&'static str> {
+    match c {
+        'ﬀ' => Some("ff"),
+        'ﬁ' => Some("fi"),
+        'ﬂ' => Some("fl"),
+        'ﬃ' => Some("ffi"),
+        'ﬄ' => Some("ffl"),
+        'ﬅ' | 'ﬆ' => Some("st"),
+        _ => None,
+    }
+}
+
+fn straighten(c: char) -> char {
+    match c {
+        '‘' | '’' | '‚' | '‛' => '\'',
+        '“' | '”' | '„' | '‟' => '"',
+        c => c,
+    }
+}
+
+fn compose(base: char, accent: char) -> Option<char> {
+    let (_, bases, composed) = COMPOSITIONS.iter().find(|(mark, _, _)| *mark == accent)?;
+    let i = bases.chars().position(|b| b == base)?;
+    composed.chars().nth(i)
+}
+
+fn fold(c: char) -> char {
+    COMPOSITIONS
+        .iter()
+        .find_map(|(_, bases, composed)| {
+            let i = composed.chars().position(|x| x == c)?;
+            bases.chars().nth(i)
+        })
+        .unwrap_or(c)
+}
+
+fn is_combining_accent(c: char) -> bool {
+    ('\u{300}'..='\u{36F}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_table() {
+        let normalizer = TextNormalizer::default();
+        let cases = [
+            ("Eﬃcient ﬁnite automata", "Efficient finite automata"),
+            ("Hyper\u{AD}graph partitioning", "Hypergraph partitioning"),
+            ("Caf\u{65}\u{301} society", "Café society"),
+            ("“Attention” isn’t all", "\"Attention\" isn't all"),
+            ("Già\u{300} visto", "Già\u{300} visto"),
+            ("\u{301}leading accent", "\u{301}leading accent"),
+        ];
+        for (input, expected) in cases.iter() {
+            assert_eq!(normalizer.normalize(input), *expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn steps_toggle() {
+        let normalizer = TextNormalizer {
+            compose: false,
+            soft_hyphens: false,
+            ligatures: false,
+            straight_quotes: false,
+            fold_diacritics: false,
+        };
+        let text = "“ﬁ\u{AD}ne” cafe\u{301}";
+
+        assert_eq!(normalizer.normalize(text), text);
+        assert_eq!(normalizer.for_matching("Café"), "café");
+    }
+
+    #[test]
+    fn matching_forms_agree() {
+        let normalizer = TextNormalizer::default();
+        let forms = [
+            "The ﬁrst Café",
+            "The first Cafe\u{301}",
+            "THE FIRST CAFÉ",
+            "The ﬁrst Cafe",
+        ];
+        for form in forms.iter() {
+            assert_eq!(normalizer.for_matching(form), "the first cafe");
+        }
+        // display text keeps the accent
+        assert_eq!(
+            normalizer.normalize("The first Cafe\u{301}"),
+            "The first Café"
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+use super::scholar::{is_blocked_page, Client, Error, ScholarArgs};
+
+// What a one result query tells about this client, IP and proxy
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessStatus {
+    // scholar served results, `latency` is the time to the end of the body
+    Ok { latency: Duration },
+    // scholar sent its cookie consent page instead of results
+    ConsentRequired,
+    // 403 or a captcha page
+    Blocked,
+    // 429, with the wait scholar asked for in Retry-After, if any
+    RateLimited { retry_after: Option<Duration> },
+    // no usable answer: connection or DNS failure, timeout or an
+    // unexpected status
+    NetworkError { reason: String },
+}
+
+impl Client {
+    // Runs q=test&num=1 once and reports how it went, eg: before a long
+    // crawl or to compare proxies. Outcomes other than Ok are reported, not
+    // returned as errors; Err only when the url can't be built. Like
+    // check_proxy the request is not throttled nor counted against the
+    // daily quota, and waits at most the client's link_timeout.
+    pub async fn check_access(&self) -> Result<AccessStatus, Error> {
+        let mut args = ScholarArgs::new("test");
+        args.limit = Some(1);
+        let url = self.get_url(&args)?;
+
+        let sent = Instant::now();
+        let resp = match self
+            .http()
+            .get(&url)
+            .timeout(self.link_timeout())
+            .send()
+            .await
+        {
+            Ok(resp) => resp,
+            Err(e) => {
+                return Ok(AccessStatus::NetworkError {
+                    reason: e.to_string(),
+                })
+            }
+        };
+        let status = resp.status();
+        let retry_after = retry_after(resp.headers());
+        let consent_redirect = resp
+            .url()
+            .host_str()
+            .is_some_and(|host| host.starts_with("consent."));
+        let body = match resp.text().await {
+            Ok(body) => body,
+            Err(e) => {
+                return Ok(AccessStatus::NetworkError {
+                    reason: e.to_string(),
+                })
+            }
+        };
+        let latency = sent.elapsed();
+
+        Ok(match status {
+            StatusCode::TOO_MANY_REQUESTS => AccessStatus::RateLimited { retry_after },
+            StatusCode::FORBIDDEN => AccessStatus::Blocked,
+            status if !status.is_success() => AccessStatus::NetworkError {
+                reason: format!("{url} answered {status}"),
+            },
+            _ if consent_redirect || is_consent_page(&body) => AccessStatus::ConsentRequired,
+            _ if is_blocked_page(&body) => AccessStatus::Blocked,
+            _ => AccessStatus::Ok { latency },
+        })
+    }
+}
+
+// the form of consent.google.com, served in place of the page or after a
+// redirect to it
+fn is_consent_page(document: &str) -> bool {
+    document.contains("action=\"https://consent.google.com")
+}
+
+// Retry-After in seconds. The HTTP date form is not used by scholar
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{results_page, MockServer};
+    use reqwest::header::HeaderValue;
+    use std::net::TcpListener;
+
+    async fn status_of(status: u16, body: &str) -> AccessStatus {
+        let server = MockServer::start(vec![(status, body.to_string())]).await;
+        let client = server.client();
+
+        let access = client.check_access().await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("q=test&num=1"), "{}", requests[0]);
+        access
+    }
+
+    #[tokio::test]
+    async fn check_access_outcomes() {
+        assert!(matches!(
+            status_of(200, &results_page(None, 1)).await,
+            AccessStatus::Ok { .. }
+        ));
+        let consent = r#"<html><body><form action="https://consent.google.com/save" method="POST"><button>Accept all</button></form></body></html>"#;
+        assert_eq!(status_of(200, consent).await, AccessStatus::ConsentRequired);
+        let captcha = "<html><body><div id=\"gs_captcha_ccl\"></div></body></html>";
+        assert_eq!(status_of(200, captcha).await, AccessStatus::Blocked);
+        assert_eq!(status_of(403, "").await, AccessStatus::Blocked);
+        assert_eq!(
+            status_of(429, "").await,
+            AccessStatus::RateLimited { retry_after: None }
+        );
+        match status_of(503, "").await {
+            AccessStatus::NetworkError { reason } => assert!(reason.contains("503"), "{}", reason),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn check_access_unreachable() {
+        // nothing listens on the port once the listener is dropped
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let client = Client::builder()
+            .base_url(&format!("http://127.0.0.1:{}/scholar?", port))
+            .build()
+            .unwrap();
+
+        let access = client.check_access().await.unwrap();
+
+        assert!(matches!(access, AccessStatus::NetworkError { .. }));
+    }
+
+    #[test]
+    fn retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), None);
+    }
+}
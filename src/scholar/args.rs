@@ -0,0 +1,224 @@
+// ScholarArgs built a setter at a time, checked as a whole before any
+// request is made.
+
+use super::ids::{CiteId, ClusterId};
+use super::legal::LegalScope;
+use super::scholar::{Error, ScholarArgs};
+
+// Setters are named after the ScholarArgs fields they set, options left
+// unset stay None
+#[derive(Clone)]
+pub struct ScholarArgsBuilder {
+    args: ScholarArgs,
+}
+
+impl ScholarArgs {
+    pub fn builder() -> ScholarArgsBuilder {
+        ScholarArgsBuilder {
+            args: ScholarArgs::new(""),
+        }
+    }
+}
+
+impl ScholarArgsBuilder {
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.args.query = query.into();
+        self
+    }
+
+    pub fn cite_id(mut self, cite_id: CiteId) -> Self {
+        self.args.cite_id = Some(cite_id);
+        self
+    }
+
+    pub fn from_year(mut self, year: u16) -> Self {
+        self.args.from_year = Some(year);
+        self
+    }
+
+    pub fn to_year(mut self, year: u16) -> Self {
+        self.args.to_year = Some(year);
+        self
+    }
+
+    // 0 for relevance, 1 for date with abstracts only, 2 for date with
+    // everything, see ScholarArgs::sort_by
+    pub fn sort_by(mut self, sort_by: u8) -> Self {
+        self.args.sort_by = Some(sort_by);
+        self
+    }
+
+    pub fn cluster_id(mut self, cluster_id: ClusterId) -> Self {
+        self.args.cluster_id = Some(cluster_id);
+        self
+    }
+
+    pub fn lang(mut self, lang: impl Into<String>) -> Self {
+        self.args.lang = Some(lang.into());
+        self
+    }
+
+    pub fn lang_limit(mut self, lang_limit: impl Into<String>) -> Self {
+        self.args.lang_limit = Some(lang_limit.into());
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.args.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.args.offset = Some(offset);
+        self
+    }
+
+    pub fn adult_filtering(mut self, enabled: bool) -> Self {
+        self.args.adult_filtering = Some(enabled);
+        self
+    }
+
+    // see ScholarArgs::show_omitted_results
+    pub fn show_omitted_results(mut self, show: bool) -> Self {
+        self.args.include_similar_results = Some(show);
+        self
+    }
+
+    pub fn include_citations(mut self, include: bool) -> Self {
+        self.args.include_citations = Some(include);
+        self
+    }
+
+    pub fn review_articles(mut self, only: bool) -> Self {
+        self.args.review_articles = Some(only);
+        self
+    }
+
+    pub fn legal_scope(mut self, scope: LegalScope) -> Self {
+        self.args.legal_scope = Some(scope);
+        self
+    }
+
+    // The args, or Error::RequiredFieldError without a query, cite id or
+    // cluster id, and Error::InvalidArgs for a cluster id with a query or
+    // cite id (scholar answers the versions page alone), a sort_by over 2
+    // or a from_year after to_year. The query's length is checked by the
+    // client sending it, see ClientBuilder::max_query_length.
+    pub fn build(self) -> Result<ScholarArgs, Error> {
+        let args = self.args;
+        let invalid = |reason: &str| Err(Error::InvalidArgs(reason.to_string()));
+        if args.query.is_empty() && args.cite_id.is_none() && args.cluster_id.is_none() {
+            return Err(Error::RequiredFieldError);
+        }
+        if args.cluster_id.is_some() && !args.query.is_empty() {
+            return invalid("cluster_id can't be combined with a query");
+        }
+        if args.cluster_id.is_some() && args.cite_id.is_some() {
+            return invalid("cluster_id can't be combined with cite_id");
+        }
+        if args.sort_by.is_some_and(|sort_by| sort_by > 2) {
+            return invalid("sort_by must be 0, 1 or 2");
+        }
+        if let (Some(from), Some(to)) = (args.from_year, args.to_year) {
+            if from > to {
+                return Err(Error::InvalidArgs(format!(
+                    "from_year {} is after to_year {}",
+                    from, to
+                )));
+            }
+        }
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimal_args() {
+        let query = String::from("deep learning");
+
+        let args = ScholarArgs::builder().query(query).build().unwrap();
+
+        assert_eq!(
+            args.get_url().unwrap(),
+            "https://scholar.google.com/scholar?q=deep%20learning"
+        );
+        assert_eq!(args.limit, None);
+        let cited_by = ScholarArgs::builder()
+            .cite_id("1000".parse().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(
+            cited_by.get_url().unwrap(),
+            ScholarArgs::cited_by("1000".parse().unwrap())
+                .get_url()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn every_option() {
+        let args = ScholarArgs::builder()
+            .query("abcd")
+            .cite_id("213123123123".parse().unwrap())
+            .from_year(2018)
+            .to_year(2021)
+            .sort_by(0)
+            .lang("en")
+            .lang_limit(format!("lang_{}|lang_en", "fr"))
+            .limit(10)
+            .offset(5)
+            .adult_filtering(true)
+            .show_omitted_results(true)
+            .include_citations(true)
+            .review_articles(true)
+            .legal_scope(LegalScope::Courts(vec!["3".to_string()]))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            args.get_url().unwrap(),
+            "https://scholar.google.com/scholar?q=abcd&cites=213123123123&as_ylo=2018&as_yhi=2021&scisbd=0&hl=en&lr=lang_fr|lang_en&num=10&start=5&safe=active&filter=0&as_vis=0&as_rr=1&as_sdt=4,3"
+        );
+    }
+
+    #[test]
+    fn invalid_combinations_fail_at_build() {
+        let cluster = || ScholarArgs::builder().cluster_id("2000".parse().unwrap());
+        assert!(cluster().build().is_ok());
+        let failures = [
+            (
+                cluster().query("abcd").build(),
+                "cluster_id can't be combined with a query",
+            ),
+            (
+                cluster().cite_id("1000".parse().unwrap()).build(),
+                "cluster_id can't be combined with cite_id",
+            ),
+            (
+                ScholarArgs::builder().query("abcd").sort_by(3).build(),
+                "sort_by must be 0, 1 or 2",
+            ),
+            (
+                ScholarArgs::builder()
+                    .query("abcd")
+                    .from_year(2021)
+                    .to_year(2018)
+                    .build(),
+                "from_year 2021 is after to_year 2018",
+            ),
+        ];
+        for (built, reason) in failures.iter() {
+            match built {
+                Err(Error::InvalidArgs(found)) => assert_eq!(found, reason),
+                _ => panic!("expected InvalidArgs({:?})", reason),
+            }
+        }
+        assert!(matches!(
+            ScholarArgs::builder().limit(10).build(),
+            Err(Error::RequiredFieldError)
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use scraper::{Html, Selector};
+
+use super::scholar::{Client, Error, ScholarResult};
+
+// formatted references from scholar's cite popup
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Citation {
+    pub mla: Option<String>,
+    pub apa: Option<String>,
+    pub chicago: Option<String>,
+    pub harvard: Option<String>,
+    pub vancouver: Option<String>,
+    // link to the BibTeX entry of the result
+    pub bibtex_url: Option<String>,
+}
+
+impl Client {
+    // Fetches the cite popup of `result`. Fails with RequiredFieldError when
+    // the result has no cite button (see ScholarResult::has_export).
+    pub async fn fetch_citation(&self, result: &ScholarResult) -> Result<Citation, Error> {
+        let result_id = match &result.result_id {
+            Some(id) if result.has_export => id,
+            _ => return Err(Error::RequiredFieldError),
+        };
+        let query = format!("info:{}:scholar.google.com/", result_id);
+        let url = self.scholar_url(&[
+            ("q", &query),
+            ("output", "cite"),
+            ("scirp", "0"),
+            ("hl", "en"),
+        ])?;
+        let doc = self.get_document(&url).await?;
+        parse_citation(&doc)
+    }
+}
+
+pub(crate) fn parse_citation(document: &str) -> Result<Citation, Error> {
+    let fragment = Html::parse_document(document);
+    let row_selector = Selector::parse("#gs_citt tr").map_err(|_| Error::ParseError)?;
+    let style_selector = Selector::parse(".gs_cith").map_err(|_| Error::ParseError)?;
+    let text_selector = Selector::parse(".gs_citr").map_err(|_| Error::ParseError)?;
+    let export_selector = Selector::parse("#gs_citi a").map_err(|_| Error::ParseError)?;
+
+    let mut citation = Citation::default();
+    for row in fragment.select(&row_selector) {
+        let style = row
+            .select(&style_selector)
+            .next()
+            .map(|n| n.text().collect::<String>());
+        let text = row
+            .select(&text_selector)
+            .next()
+            .map(|n| n.text().collect::<String>());
+        let (style, text) = match (style, text) {
+            (Some(style), Some(text)) => (style, text.trim().to_string()),
+            _ => continue,
+        };
+        let slot = match style.trim() {
+            "MLA" => &mut citation.mla,
+            "APA" => &mut citation.apa,
+            "Chicago" => &mut citation.chicago,
+            "Harvard" => &mut citation.harvard,
+            "Vancouver" => &mut citation.vancouver,
+            _ => continue,
+        };
+        *slot = Some(text);
+    }
+    citation.bibtex_url = fragment
+        .select(&export_selector)
+        .find(|n| n.text().collect::<String>().trim() == "BibTeX")
+        .and_then(|n| n.value().attr("href"))
+        .map(str::to_string);
+
+    if citation == Citation::default() {
+        return Err(Error::ParseError);
+    }
+    Ok(citation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{result_block, results_page_with, MockServer};
+
+    const CITE_POPUP: &str = r#"<div id="gs_citt"><table><tbody>
+<tr><th scope="row" class="gs_cith">MLA</th><td><div tabindex="0" class="gs_citr">LeCun, Yann, Yoshua Bengio, and Geoffrey Hinton. "Deep learning." <i>nature</i> 521.7553 (2015): 436-444.</div></td></tr>
+<tr><th scope="row" class="gs_cith">APA</th><td><div tabindex="0" class="gs_citr">LeCun, Y., Bengio, Y., &amp; Hinton, G. (2015). Deep learning. <i>nature</i>, <i>521</i>(7553), 436-444.</div></td></tr>
+<tr><th scope="row" class="gs_cith">Chicago</th><td><div tabindex="0" class="gs_citr">LeCun, Yann, Yoshua Bengio, and Geoffrey Hinton. "Deep learning." <i>nature</i> 521, no. 7553 (2015): 436-444.</div></td></tr>
+</tbody></table></div>
+<div id="gs_citi"><a class="gs_citi" href="https://scholar.googleusercontent.com/scholar.bib?q=info:cid0:scholar.google.com/&amp;output=citation">BibTeX</a> <a class="gs_citi" href="https://scholar.googleusercontent.com/scholar.enw?q=info:cid0:scholar.google.com/&amp;output=citation">EndNote</a></div>"#;
+
+    #[test]
+    fn parse_cite_popup() {
+        let citation = parse_citation(CITE_POPUP).unwrap();
+
+        assert_eq!(
+            citation.apa.as_deref(),
+            Some("LeCun, Y., Bengio, Y., & Hinton, G. (2015). Deep learning. nature, 521(7553), 436-444.")
+        );
+        assert!(citation
+            .mla
+            .unwrap()
+            .starts_with("LeCun, Yann, Yoshua Bengio"));
+        assert!(citation.chicago.unwrap().contains("no. 7553"));
+        assert_eq!(citation.harvard, None);
+        assert_eq!(
+            citation.bibtex_url.as_deref(),
+            Some("https://scholar.googleusercontent.com/scholar.bib?q=info:cid0:scholar.google.com/&output=citation")
+        );
+        assert!(matches!(
+            parse_citation("<html></html>"),
+            Err(Error::ParseError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn fetch_citation_of_result() {
+        let server = MockServer::start(vec![
+            (200, results_page_with(None, vec![result_block(0)])),
+            (200, CITE_POPUP.to_string()),
+        ])
+        .await;
+        let client = server.client();
+        let results = client
+            .scrape_scholar(&crate::scholar::ScholarArgs::new("deep learning"))
+            .await
+            .unwrap();
+
+        let citation = client.fetch_citation(&results[0]).await.unwrap();
+
+        assert!(citation.apa.is_some());
+        assert!(server.requests()[1].starts_with(
+            "GET /scholar?q=info%3Acid0%3Ascholar.google.com%2F&output=cite&scirp=0&hl=en "
+        ));
+    }
+
+    #[tokio::test]
+    async fn fetch_citation_without_cite_button() {
+        let client = Client::builder().build().unwrap();
+        let result = ScholarResult {
+            result_id: Some("cid0".to_string()),
+            has_export: false,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            client.fetch_citation(&result).await,
+            Err(Error::RequiredFieldError)
+        ));
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use crate::names::{self, parse_name, PersonName};
+
+use super::ids::CiteId;
+use super::progress::{no_progress, report, Progress, ProgressHandler};
+use super::scholar::{parse_total_results, Client, Error, ScholarResult};
+
+#[derive(Debug)]
+pub struct CiterAnalysis {
+    // authors of the citing papers, most frequent first
+    pub top_authors: Vec<(String, u32)>,
+    // venues of the citing papers, most frequent first
+    pub top_venues: Vec<(String, u32)>,
+    // number of citing papers actually looked at
+    pub examined: u32,
+    // number of citing papers scholar reports, if shown
+    pub reported_total: Option<u64>,
+}
+
+const PAGE_SIZE: u32 = 10;
+
+impl Client {
+    // Pages through the cited-by results of `result` (at most `max_pages`
+    // pages) and counts the authors and venues of the citing papers.
+    pub async fn analyze_citers(
+        &self,
+        result: &ScholarResult,
+        max_pages: u32,
+    ) -> Result<CiterAnalysis, Error> {
+        self.analyze_citers_with_progress(result, max_pages, no_progress)
+            .await
+    }
+
+    // Same as analyze_citers, reporting each page of citers to `progress`.
+    // `completed` counts pages out of at most `max_pages`.
+    pub async fn analyze_citers_with_progress<H: ProgressHandler>(
+        &self,
+        result: &ScholarResult,
+        max_pages: u32,
+        progress: H,
+    ) -> Result<CiterAnalysis, Error> {
+        let cite_id = result.cite_id.as_ref().ok_or(Error::RequiredFieldError)?;
+
+        let mut authors: HashMap<String, u32> = HashMap::new();
+        let mut venues: HashMap<String, u32> = HashMap::new();
+        let mut examined = 0;
+        let mut reported_total = None;
+
+        for page in 0..max_pages {
+            let (citers, total) = self.citers_page(cite_id, page).await?;
+            if page == 0 {
+                reported_total = total;
+            }
+            if citers.is_empty() {
+                break;
+            }
+            for citer in &citers {
+                for author in &citer.authors {
+                    *authors.entry(author.clone()).or_default() += 1;
+                }
+                if let Some(venue) = &citer.venue {
+                    *venues.entry(venue.clone()).or_default() += 1;
+                }
+            }
+            examined += citers.len() as u32;
+            report(
+                &progress,
+                Progress {
+                    operation: "analyze_citers",
+                    completed: u64::from(page + 1),
+                    total: Some(u64::from(max_pages)),
+                    message: Some(format!("{} citing papers examined", examined)),
+                },
+            );
+            if reported_total.is_some_and(|total| u64::from(examined) >= total) {
+                break;
+            }
+        }
+
+        Ok(CiterAnalysis {
+            top_authors: sorted(merge_authors(authors)),
+            top_venues: sorted(venues),
+            examined,
+            reported_total,
+        })
+    }
+
+    // Pages through the cited-by results of `result` (at most `max_pages`
+    // pages) and sorts out the citing papers that share an author with
+    // `author_names`, compared with names::compatible. A citing paper whose
+    // author list scholar cut short ("A Lee, B Chen…") and that shares no
+    // listed author is uncertain: the shared author may be among the
+    // missing ones.
+    pub async fn cited_by_excluding_self(
+        &self,
+        result: &ScholarResult,
+        author_names: &[&str],
+        max_pages: u32,
+    ) -> Result<SelfCitationReport, Error> {
+        let cite_id = result.cite_id.as_ref().ok_or(Error::RequiredFieldError)?;
+        let listed = author_names
+            .iter()
+            .map(|name| parse_name(name))
+            .collect::<Vec<_>>();
+        let mut report = SelfCitationReport::default();
+
+        for page in 0..max_pages {
+            let (citers, total) = self.citers_page(cite_id, page).await?;
+            if page == 0 {
+                report.reported_total = total;
+            }
+            if citers.is_empty() {
+                break;
+            }
+            report.examined += citers.len() as u32;
+            for citer in citers {
+                let shared = citer.authors.iter().any(|author| {
+                    let author = parse_name(author);
+                    listed.iter().any(|name| name.compatible_with(&author))
+                });
+                if shared {
+                    report.self_citations += 1;
+                } else if citer.authors_truncated {
+                    report.uncertain.push(citer);
+                } else {
+                    report.filtered.push(citer);
+                }
+            }
+            if report
+                .reported_total
+                .is_some_and(|total| u64::from(report.examined) >= total)
+            {
+                break;
+            }
+        }
+        Ok(report)
+    }
+
+    // one page of PAGE_SIZE citers and the total scholar reports on it
+    async fn citers_page(
+        &self,
+        cite_id: &CiteId,
+        page: u32,
+    ) -> Result<(Vec<ScholarResult>, Option<u64>), Error> {
+        let start = (page * PAGE_SIZE).to_string();
+        let url = self.scholar_url(&[
+            ("cites", cite_id.as_str()),
+            ("start", &start),
+            ("num", &PAGE_SIZE.to_string()),
+        ])?;
+        let doc = self.get_document(&url).await?;
+        let total = parse_total_results(&doc);
+        Ok((self.parse_page(&url, doc).await?, total))
+    }
+}
+
+// the citing papers of a result, split by whether they cite their own author
+#[derive(Debug, Default)]
+pub struct SelfCitationReport {
+    // number of citing papers actually looked at
+    pub examined: u32,
+    // number of citing papers scholar reports, if shown
+    pub reported_total: Option<u64>,
+    // citing papers with an author of the list
+    pub self_citations: u32,
+    // citing papers with none of the listed authors
+    pub filtered: Vec<ScholarResult>,
+    // citing papers with none of the listed authors among the shown ones,
+    // but with authors left out of the byline
+    pub uncertain: Vec<ScholarResult>,
+}
+
+// An abbreviated name ("J Smith", "JR Smith") is counted with the one
+// spelled out name it is compatible with, see names::compatible. With both
+// "John Smith" and "Jane Smith" present, "J Smith" is ambiguous and stays
+// on its own.
+fn merge_authors(counts: HashMap<String, u32>) -> HashMap<String, u32> {
+    let mut families: HashMap<String, Vec<(String, PersonName, u32)>> = HashMap::new();
+    let mut merged = HashMap::new();
+    for (name, count) in counts {
+        let parsed = parse_name(&name);
+        if parsed.given.is_empty() {
+            merged.insert(name, count);
+        } else {
+            families
+                .entry(names::family_key(&parsed))
+                .or_default()
+                .push((name, parsed, count));
+        }
+    }
+
+    for (_, names) in families {
+        let full_names = names
+            .iter()
+            .filter(|(_, parsed, _)| !parsed.is_abbreviated())
+            .collect::<Vec<_>>();
+        for (name, parsed, count) in &names {
+            let mut candidates = full_names
+                .iter()
+                .filter(|(_, full, _)| parsed.is_abbreviated() && parsed.compatible_with(full));
+            let target = match (candidates.next(), candidates.next()) {
+                (Some((full, _, _)), None) => full,
+                _ => name,
+            };
+            *merged.entry(target.clone()).or_default() += count;
+        }
+    }
+    merged
+}
+
+fn sorted(counts: HashMap<String, u32>) -> Vec<(String, u32)> {
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{result_block_with_byline, results_page_with, MockServer};
+
+    fn cited_result() -> ScholarResult {
+        ScholarResult {
+            cite_id: Some("1234".parse().unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn analyze_citers_aggregates_pages() {
+        let first_page = results_page_with(
+            Some("About 4 results"),
+            vec![
+                result_block_with_byline(0, "J Smith, A Lee - Nature, 2020 - nature.com"),
+                result_block_with_byline(1, "John Smith - Science, 2021 - science.org"),
+                result_block_with_byline(2, "A Lee, K Park - Nature, 2021 - nature.com"),
+            ],
+        );
+        let second_page = results_page_with(
+            Some("Page 2 of about 4 results"),
+            vec![result_block_with_byline(
+                3,
+                "M Chen, J Smith - Nature, 2022 - nature.com",
+            )],
+        );
+        let server = MockServer::start(vec![(200, first_page), (200, second_page)]).await;
+        let client = server.client();
+
+        let analysis = client.analyze_citers(&cited_result(), 5).await.unwrap();
+
+        assert_eq!(analysis.examined, 4);
+        assert_eq!(analysis.reported_total, Some(4));
+        assert_eq!(
+            analysis.top_authors,
+            vec![
+                ("John Smith".to_string(), 3),
+                ("A Lee".to_string(), 2),
+                ("K Park".to_string(), 1),
+                ("M Chen".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            analysis.top_venues,
+            vec![("Nature".to_string(), 3), ("Science".to_string(), 1)]
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains("cites=1234&start=10&num=10"));
+    }
+
+    #[tokio::test]
+    async fn analyze_citers_stops_at_max_pages() {
+        let page = results_page_with(
+            Some("About 50 results"),
+            vec![result_block_with_byline(
+                0,
+                "A Lee - Nature, 2020 - nature.com",
+            )],
+        );
+        let server = MockServer::start(vec![(200, page.clone()), (200, page)]).await;
+        let client = server.client();
+
+        let analysis = client.analyze_citers(&cited_result(), 1).await.unwrap();
+
+        assert_eq!(analysis.examined, 1);
+        assert_eq!(analysis.reported_total, Some(50));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn cited_by_excluding_self_counts() {
+        let first_page = results_page_with(
+            Some("About 6 results"),
+            vec![
+                // self citations, under the full name and with initials
+                result_block_with_byline(0, "J Smith, A Lee - Nature, 2020 - nature.com"),
+                result_block_with_byline(1, "K Park, JR Smith - Science, 2021 - science.org"),
+                result_block_with_byline(2, "A Lee, K Park - Nature, 2021 - nature.com"),
+                // another Smith
+                result_block_with_byline(3, "Jane Smith - Cell, 2021 - cell.com"),
+            ],
+        );
+        let second_page = results_page_with(
+            Some("Page 2 of about 6 results"),
+            vec![
+                result_block_with_byline(4, "M Chen, A Lee, B Kim… - Nature, 2022 - nature.com"),
+                result_block_with_byline(
+                    5,
+                    "John R. Smith, M Chen… - arXiv preprint, 2022 - arxiv.org",
+                ),
+            ],
+        );
+        let server = MockServer::start(vec![(200, first_page), (200, second_page)]).await;
+        let client = server.client();
+
+        let report = client
+            .cited_by_excluding_self(&cited_result(), &["John R. Smith", "Q Author"], 5)
+            .await
+            .unwrap();
+
+        assert_eq!(report.examined, 6);
+        assert_eq!(report.reported_total, Some(6));
+        assert_eq!(report.self_citations, 3);
+        let titles =
+            |results: &[ScholarResult]| results.iter().map(|r| r.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&report.filtered), vec!["Paper 2", "Paper 3"]);
+        assert_eq!(titles(&report.uncertain), vec!["Paper 4"]);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn cited_by_excluding_self_needs_cite_id() {
+        let client = Client::builder().build().unwrap();
+
+        let report = client
+            .cited_by_excluding_self(&ScholarResult::default(), &["J Smith"], 1)
+            .await;
+
+        assert!(matches!(report, Err(Error::RequiredFieldError)));
+    }
+
+    #[test]
+    fn ambiguous_initials_stay_apart() {
+        let counts = [("J Smith", 2), ("John Smith", 1), ("Jane Smith", 1)]
+            .iter()
+            .map(|(name, count)| (name.to_string(), *count))
+            .collect::<HashMap<_, _>>();
+
+        let merged = sorted(merge_authors(counts));
+
+        assert_eq!(
+            merged,
+            vec![
+                ("J Smith".to_string(), 2),
+                ("Jane Smith".to_string(), 1),
+                ("John Smith".to_string(), 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,374 @@
+// Collaboration networks from the co-authors listed beside author profiles.
+
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::fmt::Write;
+
+use super::ids::AuthorId;
+use super::profile::{parse_author_metrics, parse_author_profile, parse_coauthors};
+use super::progress::{no_progress, report, Progress, ProgressHandler};
+use super::scholar::{Client, Error};
+use crate::export::escape;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoauthorNode {
+    pub user_id: AuthorId,
+    pub name: String,
+    pub affiliation: Option<String>,
+    // the "All" citations of the profile
+    pub citations: Option<u32>,
+    // hops from the nearest seed, 0 for the seeds
+    pub depth: u8,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoauthorGraph {
+    // in the order fetched, seeds first
+    pub nodes: Vec<CoauthorNode>,
+    // co-authorships between nodes, each once with the lower id first,
+    // sorted. Either profile listing the other makes an edge
+    pub edges: Vec<(AuthorId, AuthorId)>,
+    // scholar blocked the crawl (Error::Blocked) before it was done, the
+    // graph has the profiles fetched until then
+    pub blocked: bool,
+}
+
+impl Client {
+    // coauthor_graph_with_progress without progress updates
+    pub async fn coauthor_graph(
+        &self,
+        seeds: &[AuthorId],
+        max_depth: u8,
+        max_nodes: usize,
+    ) -> Result<CoauthorGraph, Error> {
+        self.coauthor_graph_with_progress(seeds, max_depth, max_nodes, no_progress)
+            .await
+    }
+
+    // Fetches the profiles of `seeds`, then of their co-authors, breadth
+    // first, up to `max_depth` hops from a seed and `max_nodes` profiles in
+    // all, one request per profile at the client's delay. Only the
+    // co-authors scholar lists beside a profile are followed, about twenty
+    // at most. Error::Blocked ends the crawl with the partial graph, see
+    // CoauthorGraph::blocked, any other error fails it. `completed` counts
+    // the profiles fetched.
+    pub async fn coauthor_graph_with_progress<H: ProgressHandler>(
+        &self,
+        seeds: &[AuthorId],
+        max_depth: u8,
+        max_nodes: usize,
+        progress: H,
+    ) -> Result<CoauthorGraph, Error> {
+        let mut graph = CoauthorGraph::default();
+        let mut queued = HashSet::new();
+        let mut queue = VecDeque::new();
+        for seed in seeds {
+            if queued.insert(seed.clone()) {
+                queue.push_back((seed.clone(), 0));
+            }
+        }
+        let mut pairs = BTreeSet::new();
+
+        while let Some((user_id, depth)) = queue.pop_front() {
+            if graph.nodes.len() >= max_nodes {
+                break;
+            }
+            let url = self.citations_url(&[("user", user_id.as_str()), ("hl", "en")])?;
+            let doc = match self.get_document(&url).await {
+                Ok(doc) => doc,
+                Err(Error::Blocked) => {
+                    log::warn!(
+                        "blocked after {} profiles, returning a partial co-author graph",
+                        graph.nodes.len()
+                    );
+                    graph.blocked = true;
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            let profile = parse_author_profile(&doc)?;
+            for coauthor in parse_coauthors(&doc)? {
+                if depth < max_depth && queued.insert(coauthor.clone()) {
+                    queue.push_back((coauthor.clone(), depth + 1));
+                }
+                pairs.insert(if user_id < coauthor {
+                    (user_id.clone(), coauthor)
+                } else {
+                    (coauthor, user_id.clone())
+                });
+            }
+            graph.nodes.push(CoauthorNode {
+                user_id,
+                name: profile.name,
+                affiliation: profile.affiliation,
+                citations: parse_author_metrics(&doc)?.citations,
+                depth,
+            });
+            report(
+                &progress,
+                Progress {
+                    operation: "coauthor_graph",
+                    completed: graph.nodes.len() as u64,
+                    total: None,
+                    message: graph.nodes.last().map(|node| node.name.clone()),
+                },
+            );
+        }
+
+        let fetched = graph
+            .nodes
+            .iter()
+            .map(|node| &node.user_id)
+            .collect::<HashSet<_>>();
+        graph.edges = pairs
+            .into_iter()
+            .filter(|(a, b)| fetched.contains(a) && fetched.contains(b))
+            .collect();
+        Ok(graph)
+    }
+}
+
+impl CoauthorGraph {
+    // Graphviz DOT, an undirected graph with the nodes labelled by name,
+    // eg: for `dot -Tsvg`
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph coauthors {\n");
+        for node in &self.nodes {
+            let _ = writeln!(
+                dot,
+                "  \"{}\" [label=\"{}\"];",
+                dot_escape(node.user_id.as_str()),
+                dot_escape(&node.name)
+            );
+        }
+        for (a, b) in &self.edges {
+            let _ = writeln!(
+                dot,
+                "  \"{}\" -- \"{}\";",
+                dot_escape(a.as_str()),
+                dot_escape(b.as_str())
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    // GraphML with the name, affiliation, citations and depth of each node
+    // as data, eg: for Gephi or networkx. Unknown values are left out.
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+\x20 <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n\
+\x20 <key id=\"affiliation\" for=\"node\" attr.name=\"affiliation\" attr.type=\"string\"/>\n\
+\x20 <key id=\"citations\" for=\"node\" attr.name=\"citations\" attr.type=\"long\"/>\n\
+\x20 <key id=\"depth\" for=\"node\" attr.name=\"depth\" attr.type=\"int\"/>\n\
+\x20 <graph id=\"coauthors\" edgedefault=\"undirected\">\n",
+        );
+        for node in &self.nodes {
+            let _ = writeln!(xml, "    <node id=\"{}\">", escape(node.user_id.as_str()));
+            let _ = writeln!(
+                xml,
+                "      <data key=\"name\">{}</data>",
+                escape(&node.name)
+            );
+            if let Some(affiliation) = &node.affiliation {
+                let _ = writeln!(
+                    xml,
+                    "      <data key=\"affiliation\">{}</data>",
+                    escape(affiliation)
+                );
+            }
+            if let Some(citations) = node.citations {
+                let _ = writeln!(xml, "      <data key=\"citations\">{}</data>", citations);
+            }
+            let _ = writeln!(xml, "      <data key=\"depth\">{}</data>", node.depth);
+            xml.push_str("    </node>\n");
+        }
+        for (a, b) in &self.edges {
+            let _ = writeln!(
+                xml,
+                "    <edge source=\"{}\" target=\"{}\"/>",
+                escape(a.as_str()),
+                escape(b.as_str())
+            );
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{MockServer, ProfilePage};
+    use std::sync::Mutex;
+
+    // a 12 character profile id starting with `c`
+    fn id(c: char) -> AuthorId {
+        format!("{}AAAAAAAAAAJ", c).parse().unwrap()
+    }
+
+    fn profile(c: char, coauthors: &str) -> (u16, String) {
+        let page = ProfilePage {
+            name: format!("Author {}", c),
+            affiliation: Some("Example University".to_string()),
+            metrics: [(c as u32 - 'A' as u32 + 1) * 100, 0, 0],
+            coauthors: coauthors
+                .chars()
+                .map(|co| (id(co).as_str().to_string(), format!("Author {}", co)))
+                .collect(),
+            ..Default::default()
+        };
+        (200, page.html())
+    }
+
+    // A lists B and C, B lists A and D, C lists A, D lists E
+    async fn crawl(
+        responses: Vec<(u16, String)>,
+        max_depth: u8,
+        max_nodes: usize,
+    ) -> (CoauthorGraph, Vec<String>) {
+        let server = MockServer::start(responses).await;
+        let client = server.client();
+        let graph = client
+            .coauthor_graph(&[id('A')], max_depth, max_nodes)
+            .await
+            .unwrap();
+        (graph, server.requests())
+    }
+
+    fn ids(graph: &CoauthorGraph) -> String {
+        graph
+            .nodes
+            .iter()
+            .map(|node| format!("{}{}", &node.user_id.as_str()[..1], node.depth))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn edges(graph: &CoauthorGraph) -> String {
+        graph
+            .edges
+            .iter()
+            .map(|(a, b)| format!("{}{}", &a.as_str()[..1], &b.as_str()[..1]))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[tokio::test]
+    async fn stops_at_max_depth() {
+        let (graph, requests) = crawl(
+            vec![profile('A', "BC"), profile('B', "AD"), profile('C', "A")],
+            1,
+            100,
+        )
+        .await;
+
+        assert_eq!(ids(&graph), "A0 B1 C1");
+        // B and D co-authored, but D is two hops away
+        assert_eq!(edges(&graph), "AB AC");
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].contains("user=BAAAAAAAAAAJ"), "{}", requests[1]);
+        let a = &graph.nodes[0];
+        assert_eq!(a.name, "Author A");
+        assert_eq!(a.affiliation.as_deref(), Some("Example University"));
+        assert_eq!(a.citations, Some(100));
+        assert!(!graph.blocked);
+    }
+
+    #[tokio::test]
+    async fn stops_at_max_nodes() {
+        let (graph, requests) = crawl(
+            vec![
+                profile('A', "BC"),
+                profile('B', "AD"),
+                profile('C', "A"),
+                profile('D', "E"),
+            ],
+            5,
+            3,
+        )
+        .await;
+
+        assert_eq!(ids(&graph), "A0 B1 C1");
+        assert_eq!(edges(&graph), "AB AC");
+        assert_eq!(requests.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn blocked_returns_the_partial_graph() {
+        let captcha = (
+            200,
+            "<html><div id=\"gs_captcha_f\"></div></html>".to_string(),
+        );
+        let server = MockServer::start(vec![profile('A', "BC"), profile('B', "AC"), captcha]).await;
+        let client = server.client();
+        let seen = Mutex::new(Vec::new());
+
+        let graph = client
+            .coauthor_graph_with_progress(&[id('A')], 3, 100, |progress: &Progress| {
+                seen.lock().unwrap().push(progress.completed)
+            })
+            .await
+            .unwrap();
+
+        assert!(graph.blocked);
+        assert_eq!(ids(&graph), "A0 B1");
+        // B lists C, which was never fetched
+        assert_eq!(edges(&graph), "AB");
+        assert_eq!(*seen.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn exports() {
+        let graph = CoauthorGraph {
+            nodes: vec![
+                CoauthorNode {
+                    user_id: id('A'),
+                    name: "Jane \"JD\" Doe".to_string(),
+                    affiliation: Some("R&D Lab".to_string()),
+                    citations: Some(100),
+                    depth: 0,
+                },
+                CoauthorNode {
+                    user_id: id('B'),
+                    name: "John Roe".to_string(),
+                    affiliation: None,
+                    citations: None,
+                    depth: 1,
+                },
+            ],
+            edges: vec![(id('A'), id('B'))],
+            blocked: false,
+        };
+
+        assert_eq!(
+            graph.to_dot(),
+            "graph coauthors {\n\
+\x20 \"AAAAAAAAAAAJ\" [label=\"Jane \\\"JD\\\" Doe\"];\n\
+\x20 \"BAAAAAAAAAAJ\" [label=\"John Roe\"];\n\
+\x20 \"AAAAAAAAAAAJ\" -- \"BAAAAAAAAAAJ\";\n\
+}\n"
+        );
+        let graphml = graph.to_graphml();
+        assert!(graphml.contains(
+            "    <node id=\"AAAAAAAAAAAJ\">\n\
+\x20     <data key=\"name\">Jane &quot;JD&quot; Doe</data>\n\
+\x20     <data key=\"affiliation\">R&amp;D Lab</data>\n\
+\x20     <data key=\"citations\">100</data>\n\
+\x20     <data key=\"depth\">0</data>\n\
+\x20   </node>\n"
+        ));
+        assert!(graphml.contains(
+            "    <node id=\"BAAAAAAAAAAJ\">\n\
+\x20     <data key=\"name\">John Roe</data>\n\
+\x20     <data key=\"depth\">1</data>\n"
+        ));
+        assert!(graphml.contains("<edge source=\"AAAAAAAAAAAJ\" target=\"BAAAAAAAAAAJ\"/>"));
+        assert!(graphml.ends_with("</graph>\n</graphml>\n"));
+    }
+}
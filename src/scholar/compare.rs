@@ -0,0 +1,261 @@
+// Author profiles side by side, eg: for a department review. See
+// format::author_table for the text rendering.
+
+use super::harvest::current_year;
+use super::ids::AuthorId;
+use super::profile::{
+    parse_author_metrics, parse_author_profile, parse_citations_per_year, parse_recent_citations,
+    AuthorMetrics, ProfileArticle,
+};
+use super::scholar::{Client, Error};
+
+// publications in the current year and the four before count as recent
+const RECENT_PUBLICATION_YEARS: u16 = 5;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthorComparison {
+    // first year of the recent citations window, the profiles' "Since"
+    // column. None when no profile shows one
+    pub citations_since: Option<u16>,
+    // first year of the recent publications window
+    pub publications_since: u16,
+    // common axis of the per year series, every year from the first to the
+    // last of any profile's chart
+    pub years: Vec<u16>,
+    // in the order of the ids
+    pub authors: Vec<AuthorSummary>,
+}
+
+// A row of AuthorComparison
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthorSummary {
+    pub user_id: AuthorId,
+    pub name: String,
+    pub metrics: AuthorMetrics,
+    // citations since AuthorComparison::citations_since
+    pub recent_citations: Option<u32>,
+    pub publications: usize,
+    // publications since AuthorComparison::publications_since
+    pub recent_publications: usize,
+    // citations of each year of AuthorComparison::years, 0 for the years
+    // the profile's chart doesn't reach
+    pub citations_per_year: Vec<u32>,
+}
+
+// a profile as fetched, before the series are aligned
+struct Fetched {
+    summary: AuthorSummary,
+    citations_since: Option<u16>,
+    per_year: Vec<(u16, u32)>,
+}
+
+impl Client {
+    // Fetches the profile of each of `user_ids` in turn, at the client's
+    // delay, with all its articles (see fetch_author_articles) and compares
+    // them. Fails on the first profile that can't be fetched.
+    pub async fn compare_authors(&self, user_ids: &[AuthorId]) -> Result<AuthorComparison, Error> {
+        let publications_since = current_year().saturating_sub(RECENT_PUBLICATION_YEARS - 1);
+        let mut fetched = Vec::with_capacity(user_ids.len());
+        for user_id in user_ids {
+            let (articles, page) = self.fetch_author_listing(user_id).await?;
+            let recent = parse_recent_citations(&page)?;
+            fetched.push(Fetched {
+                summary: AuthorSummary {
+                    user_id: user_id.clone(),
+                    name: parse_author_profile(&page)?.name,
+                    metrics: parse_author_metrics(&page)?,
+                    recent_citations: recent.map(|(_, citations)| citations),
+                    publications: articles.len(),
+                    recent_publications: count_since(&articles, publications_since),
+                    citations_per_year: Vec::new(),
+                },
+                citations_since: recent.map(|(since, _)| since),
+                per_year: parse_citations_per_year(&page)?,
+            });
+        }
+        Ok(align(fetched, publications_since))
+    }
+}
+
+fn count_since(articles: &[ProfileArticle], since: u16) -> usize {
+    articles
+        .iter()
+        .filter(|article| article.year.is_some_and(|year| year >= since))
+        .count()
+}
+
+fn align(fetched: Vec<Fetched>, publications_since: u16) -> AuthorComparison {
+    let charted = fetched
+        .iter()
+        .flat_map(|author| author.per_year.iter().map(|(year, _)| *year));
+    let years = match (charted.clone().min(), charted.max()) {
+        (Some(first), Some(last)) => (first..=last).collect(),
+        _ => Vec::new(),
+    };
+    let citations_since = fetched.iter().find_map(|author| author.citations_since);
+    let authors = fetched
+        .into_iter()
+        .map(|author| {
+            let per_year = author.per_year;
+            AuthorSummary {
+                citations_per_year: years
+                    .iter()
+                    .map(|year| {
+                        per_year
+                            .iter()
+                            .find(|(charted, _)| charted == year)
+                            .map_or(0, |(_, citations)| *citations)
+                    })
+                    .collect(),
+                ..author.summary
+            }
+        })
+        .collect();
+    AuthorComparison {
+        citations_since,
+        publications_since,
+        years,
+        authors,
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AuthorComparison {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut comparison = serializer.serialize_struct("AuthorComparison", 4)?;
+        comparison.serialize_field("citations_since", &self.citations_since)?;
+        comparison.serialize_field("publications_since", &self.publications_since)?;
+        comparison.serialize_field("years", &self.years)?;
+        comparison.serialize_field("authors", &self.authors)?;
+        comparison.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AuthorSummary {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut summary = serializer.serialize_struct("AuthorSummary", 9)?;
+        summary.serialize_field("user_id", &self.user_id)?;
+        summary.serialize_field("name", &self.name)?;
+        summary.serialize_field("citations", &self.metrics.citations)?;
+        summary.serialize_field("h_index", &self.metrics.h_index)?;
+        summary.serialize_field("i10_index", &self.metrics.i10_index)?;
+        summary.serialize_field("recent_citations", &self.recent_citations)?;
+        summary.serialize_field("publications", &self.publications)?;
+        summary.serialize_field("recent_publications", &self.recent_publications)?;
+        summary.serialize_field("citations_per_year", &self.citations_per_year)?;
+        summary.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::author_table;
+    use crate::scholar::mock::{profile_article, MockServer, ProfilePage};
+
+    // a profile page with an article per year of `years` and a chart with
+    // a bar per (year, citations), years of `chart` without a bar left out
+    fn profile_page(
+        name: &str,
+        metrics: [u32; 4],
+        years: &[u16],
+        chart: &[(u16, Option<u32>)],
+    ) -> String {
+        ProfilePage {
+            name: name.to_string(),
+            metrics: [metrics[0], metrics[2], metrics[3]],
+            since: (2020, metrics[1]),
+            chart: chart.to_vec(),
+            articles: years
+                .iter()
+                .enumerate()
+                .map(|(i, year)| {
+                    profile_article(&i.to_string(), &format!("Paper {}", i), *year, None)
+                })
+                .collect(),
+            ..Default::default()
+        }
+        .html()
+    }
+
+    #[tokio::test]
+    async fn two_profiles_side_by_side() {
+        let now = current_year();
+        let server = MockServer::start(vec![
+            (
+                200,
+                profile_page(
+                    "Jane Doe",
+                    [1200, 800, 15, 20],
+                    &[now, now - 2, now - 4, now - 5, now - 10],
+                    &[(2018, Some(100)), (2019, None), (2020, Some(300))],
+                ),
+            ),
+            (
+                200,
+                profile_page(
+                    "John Roe",
+                    [90, 60, 4, 2],
+                    &[now - 1, now - 7],
+                    &[(2020, Some(20)), (2021, Some(40))],
+                ),
+            ),
+        ])
+        .await;
+        let client = server.client();
+
+        let comparison = client
+            .compare_authors(&[
+                "JicYPdAAAAAJ".parse().unwrap(),
+                "qc6CJjYAAAAJ".parse().unwrap(),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(comparison.years, vec![2018, 2019, 2020, 2021]);
+        assert_eq!(comparison.citations_since, Some(2020));
+        assert_eq!(comparison.publications_since, now - 4);
+        let jane = &comparison.authors[0];
+        assert_eq!(jane.user_id.as_str(), "JicYPdAAAAAJ");
+        assert_eq!(jane.citations_per_year, vec![100, 0, 300, 0]);
+        assert_eq!((jane.publications, jane.recent_publications), (5, 3));
+        let john = &comparison.authors[1];
+        assert_eq!(john.citations_per_year, vec![0, 0, 20, 40]);
+        assert_eq!((john.publications, john.recent_publications), (2, 1));
+        assert!(server.requests()[1].contains("user=qc6CJjYAAAAJ"));
+
+        assert_eq!(
+            author_table(&comparison),
+            format!(
+                "\
+Author    Citations  Since 2020  h-index  i10-index  Publications  Since {}
+Jane Doe       1200         800       15         20             5           3
+John Roe         90          60        4          2             2           1
+
+Citations per year
+Author    2018  2019  2020  2021
+Jane Doe   100     0   300     0
+John Roe     0     0    20    40
+",
+                now - 4
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_at_a_failed_profile() {
+        let server = MockServer::start(vec![(404, String::new())]).await;
+        let client = server.client();
+
+        let comparison = client
+            .compare_authors(&["JicYPdAAAAAJ".parse().unwrap()])
+            .await;
+
+        assert!(comparison.is_err());
+    }
+}
@@ -0,0 +1,153 @@
+// DNS-over-HTTPS lookups for ClientBuilder::doh, through the JSON flavor of
+// the protocol (application/dns-json) Cloudflare and Google serve.
+
+use std::error::Error as StdError;
+use std::net::{IpAddr, SocketAddr};
+
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+
+pub const DEFAULT_DOH_ENDPOINT: &str = "https://cloudflare-dns.com/dns-query";
+
+// record types looked up, by name and number
+const RECORD_TYPES: [(&str, u64); 2] = [("A", 1), ("AAAA", 28)];
+
+type LookupError = Box<dyn StdError + Send + Sync>;
+
+pub(crate) struct DohResolver {
+    // looks the endpoint itself up with the system resolver, unless its
+    // host is pinned with ClientBuilder::resolve or is an ip
+    client: reqwest::Client,
+    endpoint: String,
+}
+
+impl DohResolver {
+    pub(crate) fn new(client: reqwest::Client, endpoint: &str) -> DohResolver {
+        DohResolver {
+            client,
+            endpoint: endpoint.to_string(),
+        }
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let client = self.client.clone();
+        let endpoint = self.endpoint.clone();
+        Box::pin(async move {
+            let mut addrs = Vec::new();
+            for (record, number) in RECORD_TYPES.iter() {
+                addrs.extend(lookup(&client, &endpoint, name.as_str(), record, *number).await?);
+            }
+            if addrs.is_empty() {
+                return Err(format!("no address for {}", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+// The addresses in the answer, none for NXDOMAIN. The port is 0, the one of
+// the url is used.
+async fn lookup(
+    client: &reqwest::Client,
+    endpoint: &str,
+    host: &str,
+    record: &str,
+    number: u64,
+) -> Result<Vec<SocketAddr>, LookupError> {
+    let resp = client
+        .get(endpoint)
+        .query(&[("name", host), ("type", record)])
+        .header(reqwest::header::ACCEPT, "application/dns-json")
+        .send()
+        .await?
+        .error_for_status()?;
+    let body: serde_json::Value = serde_json::from_slice(&resp.bytes().await?)?;
+    match body["Status"].as_u64() {
+        Some(0) => {}
+        Some(3) => return Ok(Vec::new()),
+        status => return Err(format!("DoH lookup of {host} failed: status {status:?}").into()),
+    }
+    // CNAMEs are followed by the server, only the records asked for count
+    let answers = body["Answer"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    Ok(answers
+        .iter()
+        .filter(|answer| answer["type"].as_u64() == Some(number))
+        .filter_map(|answer| answer["data"].as_str()?.parse::<IpAddr>().ok())
+        .map(|ip| SocketAddr::new(ip, 0))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scholar::mock::{results_page, MockServer};
+    use crate::scholar::{Client, Error, ScholarArgs};
+
+    fn answer(ip: &str) -> (u16, String) {
+        let body = format!(
+            r#"{{"Status":0,"Answer":[{{"name":"scholar.test","type":5,"data":"alias.test."}},{{"name":"alias.test","type":1,"TTL":300,"data":"{ip}"}}]}}"#
+        );
+        (200, body)
+    }
+
+    fn scholar_at(doh: &MockServer, base_url: &str) -> Client {
+        Client::builder()
+            .base_url(&base_url.replace("127.0.0.1", "scholar.test"))
+            .doh(&doh.url("/dns-query"))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn doh_answers_are_used() {
+        let scholar = MockServer::start(vec![(200, results_page(None, 1))]).await;
+        let doh = MockServer::start(vec![
+            answer("127.0.0.1"),
+            (200, r#"{"Status":0}"#.to_string()),
+        ])
+        .await;
+
+        let results = scholar_at(&doh, &scholar.base_url())
+            .scrape_scholar(&ScholarArgs::new("abcd"))
+            .await;
+
+        assert_eq!(results.unwrap().len(), 1);
+        assert_eq!(
+            doh.requests(),
+            vec![
+                "GET /dns-query?name=scholar.test&type=A HTTP/1.1",
+                "GET /dns-query?name=scholar.test&type=AAAA HTTP/1.1",
+            ]
+        );
+        assert_eq!(
+            doh.header(0, "accept").as_deref(),
+            Some("application/dns-json")
+        );
+    }
+
+    #[tokio::test]
+    async fn doh_failures_are_dns_errors() {
+        let nxdomain = || (200, r#"{"Status":3}"#.to_string());
+        let failures = [
+            vec![(500, String::from("upstream down"))],
+            vec![(200, r#"{"Status":2}"#.to_string())],
+            vec![(200, String::from("<html>not json</html>"))],
+            vec![nxdomain(), nxdomain()],
+        ];
+        for script in failures.iter() {
+            let doh = MockServer::start(script.clone()).await;
+
+            let results = scholar_at(&doh, "http://127.0.0.1:1/scholar?")
+                .scrape_scholar(&ScholarArgs::new("abcd"))
+                .await;
+
+            assert!(
+                matches!(&results, Err(Error::Dns(url)) if url.starts_with("http://scholar.test:1/")),
+                "{:?}: {:?}",
+                script,
+                results
+            );
+        }
+    }
+}
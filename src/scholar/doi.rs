@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+use super::scholar::{Client, ScholarArgs, ScholarResult};
+use crate::urlnorm::find_doi;
+
+// searches resolve_dois runs at once, on top of the client's delay
+const DOI_CONCURRENCY: usize = 4;
+
+impl Client {
+    // The scholar entry of each DOI, in the order of `dois`, by searching
+    // the quoted DOI. Best effort: scholar doesn't look DOIs up, it matches
+    // them as text, so the entry is the result whose link or source links
+    // carry the DOI, or else the only result of the search. Anything else,
+    // including a failed request, is None. DOIs may be given bare, as
+    // "doi:..." or as a doi.org url; the returned strings are as given.
+    pub async fn resolve_dois(&self, dois: &[&str]) -> Vec<(String, Option<ScholarResult>)> {
+        let permits = Arc::new(Semaphore::new(DOI_CONCURRENCY));
+        let tasks = dois
+            .iter()
+            .map(|doi| {
+                let client = self.clone();
+                let permits = permits.clone();
+                let doi = doi.to_string();
+                tokio::spawn(async move {
+                    let _permit = permits.acquire().await;
+                    client.resolve_doi(&doi).await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut resolved = Vec::with_capacity(tasks.len());
+        for (task, doi) in tasks.into_iter().zip(dois) {
+            resolved.push((doi.to_string(), task.await.ok().flatten()));
+        }
+        resolved
+    }
+
+    async fn resolve_doi(&self, doi: &str) -> Option<ScholarResult> {
+        let doi = find_doi(doi).unwrap_or_else(|| doi.trim().to_lowercase());
+        let results = match self
+            .scrape_scholar(&ScholarArgs::new(&format!("\"{}\"", doi)))
+            .await
+        {
+            Ok(results) => results,
+            Err(e) => {
+                log::warn!("searching {} failed: {}", doi, e);
+                return None;
+            }
+        };
+        let carries_doi = |result: &ScholarResult| {
+            std::iter::once(result.link.as_str())
+                .chain(result.source_links.iter().map(|source| source.url.as_str()))
+                .any(|link| find_doi(link).as_deref() == Some(doi.as_str()))
+        };
+        match results.iter().position(carries_doi) {
+            Some(position) => results.into_iter().nth(position),
+            None if results.len() == 1 => results.into_iter().next(),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scholar::mock::{result_block, results_page_with, MockServer};
+
+    #[tokio::test]
+    async fn resolve_one_of_two() {
+        let with_doi = result_block(1).replace(
+            "https://example.org/paper1",
+            "https://dl.acm.org/doi/abs/10.1145/3065386",
+        );
+        // the searches run concurrently, so both get the same page
+        let page = results_page_with(None, vec![result_block(0), with_doi]);
+        let server = MockServer::start(vec![(200, page.clone()), (200, page)]).await;
+        let client = server.client();
+
+        let resolved = client
+            .resolve_dois(&["https://doi.org/10.1145/3065386", "10.9999/missing"])
+            .await;
+
+        assert_eq!(resolved.len(), 2);
+        assert_eq!(resolved[0].0, "https://doi.org/10.1145/3065386");
+        assert_eq!(resolved[0].1.as_ref().unwrap().title, "Paper 1");
+        assert_eq!(resolved[1].0, "10.9999/missing");
+        assert!(resolved[1].1.is_none());
+        let mut requests = server.requests();
+        requests.sort();
+        assert!(
+            requests[0].contains("q=%2210.1145/3065386%22"),
+            "{}",
+            requests[0]
+        );
+        assert!(
+            requests[1].contains("q=%2210.9999/missing%22"),
+            "{}",
+            requests[1]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashSet;
+
+use scraper::{ElementRef, Html, Selector};
+
+use super::scholar::{query_param, ScholarArgs};
+
+// The search state shown in the left sidebar of a results page. A None
+// field means the sidebar has no such section, eg: case law pages have no
+// patents or citations checkboxes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFacets {
+    // active year range, None for "Any time"
+    pub from_year: Option<u16>,
+    pub to_year: Option<u16>,
+    // "Sort by date" selected rather than "Sort by relevance"
+    pub sort_by_date: Option<bool>,
+    // state of the "include patents" checkbox
+    pub include_patents: Option<bool>,
+    // state of the "include citations" checkbox
+    pub include_citations: Option<bool>,
+}
+
+impl SearchFacets {
+    // Whether scholar applied the filters `args` asked for. Only the filters
+    // set in `args` are compared, and sections missing from the sidebar
+    // can't contradict them.
+    pub fn matches(&self, args: &ScholarArgs) -> bool {
+        let years = args.from_year.is_none_or(|y| self.from_year == Some(y))
+            && args.to_year.is_none_or(|y| self.to_year == Some(y));
+        // scisbd=0 is relevance, 1 and 2 sort by date
+        let sort = match (args.sort_by, self.sort_by_date) {
+            (Some(sort_by), Some(by_date)) if sort_by < 3 => (sort_by > 0) == by_date,
+            _ => true,
+        };
+        let citations = match (args.include_citations, self.include_citations) {
+            (Some(requested), Some(shown)) => requested == shown,
+            _ => true,
+        };
+        years && sort && citations
+    }
+}
+
+// Sections are told apart by the parameters their links change rather than
+// by their localized labels.
+pub(crate) fn parse_facets(document: &str) -> SearchFacets {
+    let fragment = Html::parse_document(document);
+    let mut facets = SearchFacets::default();
+    let (section, link, selected, checkbox, range) = match (
+        Selector::parse("#gs_bdy_sb .gs_bdy_sb_sec"),
+        Selector::parse("a[href]"),
+        Selector::parse(".gs_bdy_sb_sel a[href]"),
+        Selector::parse("#gs_bdy_sb a[role=\"checkbox\"]"),
+        Selector::parse("#gs_bdy_sb input[name=\"as_ylo\"], #gs_bdy_sb input[name=\"as_yhi\"]"),
+    ) {
+        (Ok(a), Ok(b), Ok(c), Ok(d), Ok(e)) => (a, b, c, d, e),
+        _ => return facets,
+    };
+
+    for sec in fragment.select(&section) {
+        // the links of a section keep the rest of the search state, only
+        // the parameter the section is about differs between them
+        let varies = |name: &str| {
+            let values = sec
+                .select(&link)
+                .map(|a| href_param(a, name))
+                .collect::<HashSet<_>>();
+            values.len() > 1
+        };
+        let active = sec.select(&selected).next();
+        if varies("as_ylo") {
+            facets.from_year = active.and_then(|a| year_param(a, "as_ylo"));
+            facets.to_year = active.and_then(|a| year_param(a, "as_yhi"));
+        } else if varies("scisbd") {
+            facets.sort_by_date =
+                active.map(|a| href_param(a, "scisbd").is_some_and(|sort| sort != "0"));
+        }
+    }
+
+    // the custom range box holds the active range, including ranges that
+    // have no link of their own
+    for input in fragment.select(&range) {
+        let year = input
+            .value()
+            .attr("value")
+            .and_then(|v| v.trim().parse().ok());
+        match input.value().attr("name") {
+            Some("as_ylo") if year.is_some() => facets.from_year = year,
+            Some("as_yhi") if year.is_some() => facets.to_year = year,
+            _ => {}
+        }
+    }
+
+    for cb in fragment.select(&checkbox) {
+        let checked = cb.value().attr("aria-checked") == Some("true");
+        if href_param(cb, "as_vis").is_some() {
+            facets.include_citations = Some(checked);
+        } else {
+            facets.include_patents = Some(checked);
+        }
+    }
+    facets
+}
+
+fn href_param(link: ElementRef, name: &str) -> Option<String> {
+    query_param(link.value().attr("href")?, name)
+}
+
+fn year_param(link: ElementRef, name: &str) -> Option<u16> {
+    href_param(link, name)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{results_page, results_page_with, MockServer};
+
+    fn with_sidebar(sidebar: &str) -> String {
+        results_page(None, 1).replacen(
+            "<body>",
+            &format!("<body><div id=\"gs_bdy_sb\">{}</div>", sidebar),
+            1,
+        )
+    }
+
+    const FILTERED: &str = r#"<ul class="gs_bdy_sb_sec">
+<li class="gs_ind"><a href="/scholar?hl=en&amp;as_sdt=0,5&amp;q=abcd">Any time</a></li>
+<li class="gs_ind"><a href="/scholar?as_ylo=2024&amp;hl=en&amp;as_sdt=0,5&amp;q=abcd">Since 2024</a></li>
+<li class="gs_ind gs_bdy_sb_sel"><a href="/scholar?as_ylo=2020&amp;hl=en&amp;as_sdt=0,5&amp;q=abcd">Since 2020</a></li>
+<li class="gs_ind"><a href="javascript:void(0)" id="gs_res_sb_yyc">Custom range...</a></li>
+</ul>
+<form id="gs_res_sb_yyf"><input type="text" name="as_ylo" value="2020"> — <input type="text" name="as_yhi" value="2022"></form>
+<ul class="gs_bdy_sb_sec">
+<li class="gs_ind"><a href="/scholar?as_ylo=2020&amp;as_yhi=2022&amp;hl=en&amp;as_sdt=0,5&amp;q=abcd">Sort by relevance</a></li>
+<li class="gs_ind gs_bdy_sb_sel"><a href="/scholar?as_ylo=2020&amp;as_yhi=2022&amp;hl=en&amp;as_sdt=0,5&amp;q=abcd&amp;scisbd=1">Sort by date</a></li>
+</ul>
+<ul class="gs_bdy_sb_sec">
+<li class="gs_inw"><a role="checkbox" aria-checked="false" class="gs_cb_gen gs_in_cb" href="/scholar?as_ylo=2020&amp;as_yhi=2022&amp;hl=en&amp;as_sdt=1,5&amp;q=abcd&amp;scisbd=1"><span class="gs_lbl">include patents</span></a></li>
+<li class="gs_inw"><a role="checkbox" aria-checked="true" class="gs_cb_gen gs_in_cb gs_sel" href="/scholar?as_ylo=2020&amp;as_yhi=2022&amp;hl=en&amp;as_sdt=0,5&amp;as_vis=1&amp;q=abcd&amp;scisbd=1"><span class="gs_lbl">include citations</span></a></li>
+</ul>"#;
+
+    const UNFILTERED: &str = r#"<ul class="gs_bdy_sb_sec">
+<li class="gs_ind gs_bdy_sb_sel"><a href="/scholar?hl=en&amp;as_sdt=0,5&amp;q=abcd">Any time</a></li>
+<li class="gs_ind"><a href="/scholar?as_ylo=2024&amp;hl=en&amp;as_sdt=0,5&amp;q=abcd">Since 2024</a></li>
+</ul>
+<form id="gs_res_sb_yyf"><input type="text" name="as_ylo" value=""> — <input type="text" name="as_yhi" value=""></form>
+<ul class="gs_bdy_sb_sec">
+<li class="gs_ind gs_bdy_sb_sel"><a href="/scholar?hl=en&amp;as_sdt=0,5&amp;q=abcd">Sort by relevance</a></li>
+<li class="gs_ind"><a href="/scholar?hl=en&amp;as_sdt=0,5&amp;q=abcd&amp;scisbd=1">Sort by date</a></li>
+</ul>
+<ul class="gs_bdy_sb_sec">
+<li class="gs_inw"><a role="checkbox" aria-checked="true" class="gs_cb_gen gs_in_cb gs_sel" href="/scholar?hl=en&amp;as_sdt=1,5&amp;q=abcd"><span class="gs_lbl">include patents</span></a></li>
+<li class="gs_inw"><a role="checkbox" aria-checked="true" class="gs_cb_gen gs_in_cb gs_sel" href="/scholar?hl=en&amp;as_sdt=0,5&amp;as_vis=1&amp;q=abcd"><span class="gs_lbl">include citations</span></a></li>
+</ul>"#;
+
+    // case law: courts instead of the checkboxes, no sort section
+    const CASE_LAW: &str = r#"<ul class="gs_bdy_sb_sec">
+<li class="gs_ind gs_bdy_sb_sel"><a href="/scholar?hl=en&amp;as_sdt=2006&amp;q=abcd">Any time</a></li>
+<li class="gs_ind"><a href="/scholar?as_ylo=2024&amp;hl=en&amp;as_sdt=2006&amp;q=abcd">Since 2024</a></li>
+</ul>
+<ul class="gs_bdy_sb_sec">
+<li class="gs_ind gs_bdy_sb_sel"><a href="/scholar?hl=en&amp;as_sdt=2006&amp;q=abcd">Federal courts</a></li>
+<li class="gs_ind"><a href="/scholar?hl=en&amp;as_sdt=4,33&amp;q=abcd">California courts</a></li>
+</ul>"#;
+
+    #[test]
+    fn parse_filtered_sidebar() {
+        let facets = parse_facets(&with_sidebar(FILTERED));
+
+        assert_eq!(
+            facets,
+            SearchFacets {
+                from_year: Some(2020),
+                to_year: Some(2022),
+                sort_by_date: Some(true),
+                include_patents: Some(false),
+                include_citations: Some(true),
+            }
+        );
+        let mut args = ScholarArgs::new("abcd");
+        args.from_year = Some(2020);
+        args.to_year = Some(2022);
+        args.sort_by = Some(1);
+        args.include_citations = Some(true);
+        assert!(facets.matches(&args));
+        args.include_citations = Some(false);
+        assert!(!facets.matches(&args));
+    }
+
+    #[test]
+    fn parse_unfiltered_sidebar() {
+        let facets = parse_facets(&with_sidebar(UNFILTERED));
+
+        assert_eq!(
+            facets,
+            SearchFacets {
+                from_year: None,
+                to_year: None,
+                sort_by_date: Some(false),
+                include_patents: Some(true),
+                include_citations: Some(true),
+            }
+        );
+        assert!(facets.matches(&ScholarArgs::new("abcd")));
+        // the year range was dropped
+        let mut args = ScholarArgs::new("abcd");
+        args.from_year = Some(2020);
+        assert!(!facets.matches(&args));
+    }
+
+    #[test]
+    fn parse_case_law_sidebar() {
+        let facets = parse_facets(&with_sidebar(CASE_LAW));
+
+        assert_eq!(facets, SearchFacets::default());
+        let mut args = ScholarArgs::new("abcd");
+        args.sort_by = Some(1);
+        args.include_citations = Some(false);
+        assert!(facets.matches(&args));
+        assert_eq!(
+            parse_facets(&results_page_with(None, vec![])),
+            SearchFacets::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn scrape_response_has_facets() {
+        let server = MockServer::start(vec![(200, with_sidebar(UNFILTERED))]).await;
+        let client = server.client();
+        let mut args = ScholarArgs::new("abcd");
+        args.from_year = Some(2020);
+
+        let response = client.scrape_response(&args).await.unwrap();
+
+        assert_eq!(response.facets.sort_by_date, Some(false));
+        assert!(!response.facets.matches(&args));
+    }
+}
@@ -0,0 +1,60 @@
+use bitflags::bitflags;
+
+bitflags! {
+    // parts of a result scrape_serialize extracts, see
+    // ClientBuilder::parse_fields
+    pub struct FieldSet: u8 {
+        // title and raw_title
+        const TITLE = 0b00001;
+        const LINK = 0b00010;
+        // abs
+        const ABSTRACT = 0b00100;
+        // author, authors, venue and year
+        const BYLINE = 0b01000;
+        // cite_id, cited_by, cluster_id, result_id and has_export, from the
+        // links under the result
+        const FOOTER = 0b10000;
+    }
+}
+
+impl Default for FieldSet {
+    fn default() -> Self {
+        FieldSet::all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::results_page;
+    use crate::scholar::Client;
+    use std::time::Instant;
+
+    // Pages per second scrape_serialize parses with every field and with
+    // titles only. Timings mean little in debug builds, run it with:
+    // cargo test --release parse_throughput -- --ignored --nocapture
+    #[test]
+    #[ignore = "benchmark"]
+    fn parse_throughput() {
+        const PAGES: u32 = 200;
+        let page = results_page(Some("About 1,000 results (0.03 sec)"), 20);
+
+        for (name, fields) in [("all", FieldSet::all()), ("title", FieldSet::TITLE)].iter() {
+            let client = Client::builder().parse_fields(*fields).build().unwrap();
+            // warm up, and check the page parses at all
+            assert_eq!(client.scrape_serialize(page.clone()).unwrap().len(), 20);
+
+            let start = Instant::now();
+            for _ in 0..PAGES {
+                client.scrape_serialize(page.clone()).unwrap();
+            }
+            let elapsed = start.elapsed();
+            println!(
+                "{:>5} fields: {:.1} pages/s, {:?} per page",
+                name,
+                f64::from(PAGES) / elapsed.as_secs_f64(),
+                elapsed / PAGES
+            );
+        }
+    }
+}
@@ -0,0 +1,237 @@
+// Filtering applied to parsed results on our side, for what scholar has no
+// parameter for, eg: leaving out the results of some sites.
+
+use futures_util::StreamExt;
+
+use super::scholar::{Client, Error, ScholarArgs, ScholarResult};
+
+// Domains a result's links are checked against. A domain matches itself and
+// its subdomains: "springer.com" and "*.springer.com" both match
+// "link.springer.com". Case and a leading "www." don't matter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DomainFilter {
+    // keep only results with a link on one of the domains
+    Allow(Vec<String>),
+    // drop results with a link on any of the domains
+    Deny(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultFilter {
+    domains: Option<DomainFilter>,
+    keep_linkless: bool,
+}
+
+impl Default for ResultFilter {
+    fn default() -> Self {
+        ResultFilter {
+            domains: None,
+            keep_linkless: true,
+        }
+    }
+}
+
+impl ResultFilter {
+    // checks the link and the source links (eg: "[PDF] arxiv.org") of
+    // each result against `filter`
+    pub fn domains(filter: DomainFilter) -> ResultFilter {
+        ResultFilter {
+            domains: Some(filter),
+            ..ResultFilter::default()
+        }
+    }
+
+    // whether results without any link, eg: [CITATION] entries, pass the
+    // domain filter. Default = true
+    pub fn keep_linkless(mut self, keep: bool) -> Self {
+        self.keep_linkless = keep;
+        self
+    }
+
+    pub fn matches(&self, result: &ScholarResult) -> bool {
+        let filter = match &self.domains {
+            Some(filter) => filter,
+            None => return true,
+        };
+        let hosts = std::iter::once(result.link.as_str())
+            .chain(result.source_links.iter().map(|source| source.url.as_str()))
+            .filter_map(host)
+            .collect::<Vec<_>>();
+        if hosts.is_empty() {
+            return self.keep_linkless;
+        }
+        let listed = |domains: &[String]| {
+            hosts
+                .iter()
+                .any(|host| domains.iter().any(|domain| on_domain(host, domain)))
+        };
+        match filter {
+            DomainFilter::Allow(domains) => listed(domains),
+            DomainFilter::Deny(domains) => !listed(domains),
+        }
+    }
+}
+
+impl Client {
+    // Pages through `args` like scrape_until, keeping the results `filter`
+    // matches, until `max_results` are kept or the query runs out. Filtered
+    // out results don't count, so this may fetch many more pages than
+    // max_results alone would need.
+    pub async fn scrape_filtered(
+        &self,
+        args: &ScholarArgs,
+        filter: &ResultFilter,
+        max_results: usize,
+    ) -> Result<Vec<ScholarResult>, Error> {
+        let mut kept = Vec::new();
+        let pages = self.page_stream(args);
+        futures_util::pin_mut!(pages);
+        while kept.len() < max_results {
+            match pages.next().await {
+                Some(page) => kept.extend(page?.into_iter().filter(|r| filter.matches(r))),
+                None => break,
+            }
+        }
+        kept.truncate(max_results);
+        Ok(kept)
+    }
+}
+
+pub(crate) fn host(link: &str) -> Option<String> {
+    let url = url::Url::parse(link).ok()?;
+    let host = url.host_str()?.trim_end_matches('.').to_lowercase();
+    Some(host)
+}
+
+pub(crate) fn on_domain(host: &str, domain: &str) -> bool {
+    let domain = domain.trim().trim_start_matches("*.").to_lowercase();
+    let domain = domain.trim_start_matches("www.");
+    let host = host.trim_start_matches("www.");
+    !domain.is_empty()
+        && (host == domain
+            || host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{result_block, results_page_with, MockServer};
+    use crate::scholar::SourceLink;
+
+    fn result(link: &str, sources: &[&str]) -> ScholarResult {
+        ScholarResult {
+            link: link.to_string(),
+            source_links: sources
+                .iter()
+                .map(|url| SourceLink {
+                    label: "[PDF] source".to_string(),
+                    url: url.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn domains(list: &[&str]) -> Vec<String> {
+        list.iter().map(|d| d.to_string()).collect()
+    }
+
+    #[test]
+    fn allow_list() {
+        let filter = ResultFilter::domains(DomainFilter::Allow(domains(&["arxiv.org", "acm.org"])));
+
+        assert!(filter.matches(&result("https://arxiv.org/abs/1", &[])));
+        assert!(filter.matches(&result("https://dl.acm.org/doi/1", &[])));
+        // the eprint is on an allowed site
+        assert!(filter.matches(&result(
+            "https://publisher.example/1",
+            &["https://arxiv.org/pdf/1"]
+        )));
+        assert!(!filter.matches(&result("https://publisher.example/1", &[])));
+        // not a subdomain, only the same suffix
+        assert!(!filter.matches(&result("https://notarxiv.org/1", &[])));
+    }
+
+    #[test]
+    fn deny_list_with_subdomains() {
+        let filter = ResultFilter::domains(DomainFilter::Deny(domains(&[
+            "*.springer.com",
+            "Predatory.example",
+        ])));
+
+        assert!(!filter.matches(&result("https://link.springer.com/article/1", &[])));
+        assert!(!filter.matches(&result("https://springer.com/1", &[])));
+        assert!(!filter.matches(&result("https://www.predatory.example/1", &[])));
+        assert!(!filter.matches(&result(
+            "https://arxiv.org/abs/1",
+            &["https://journals.predatory.example/pdf"]
+        )));
+        assert!(filter.matches(&result("https://arxiv.org/abs/1", &[])));
+        assert!(filter.matches(&result("https://springer.com.example/1", &[])));
+    }
+
+    #[test]
+    fn linkless_policy() {
+        let citation = result("", &[]);
+        let allow = ResultFilter::domains(DomainFilter::Allow(domains(&["arxiv.org"])));
+
+        assert!(allow.matches(&citation));
+        assert!(!allow.clone().keep_linkless(false).matches(&citation));
+        let deny = ResultFilter::domains(DomainFilter::Deny(domains(&["arxiv.org"])));
+        assert!(!deny.keep_linkless(false).matches(&citation));
+        assert!(ResultFilter::default().matches(&citation));
+    }
+
+    // results `first`.. linking to `links`
+    fn page(first: usize, links: &[&str]) -> String {
+        let blocks = links
+            .iter()
+            .zip(first..)
+            .map(|(link, i)| {
+                result_block(i).replace(&format!("https://example.org/paper{}", i), link)
+            })
+            .collect();
+        results_page_with(None, blocks)
+    }
+
+    #[tokio::test]
+    async fn scrape_filtered_pages_until_enough_kept() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                page(0, &["https://arxiv.org/1", "https://spam.example/2"]),
+            ),
+            (
+                200,
+                page(2, &["https://spam.example/3", "https://arxiv.org/4"]),
+            ),
+            (
+                200,
+                page(4, &["https://arxiv.org/5", "https://arxiv.org/6"]),
+            ),
+        ])
+        .await;
+        let client = server.client();
+        let mut args = ScholarArgs::new("abcd");
+        args.limit = Some(2);
+        let filter = ResultFilter::domains(DomainFilter::Deny(domains(&["spam.example"])));
+
+        let results = client.scrape_filtered(&args, &filter, 3).await.unwrap();
+
+        let links = results.iter().map(|r| r.link.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            vec![
+                "https://arxiv.org/1",
+                "https://arxiv.org/4",
+                "https://arxiv.org/5"
+            ]
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].contains("start=4"));
+    }
+}
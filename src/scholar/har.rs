@@ -0,0 +1,345 @@
+// The HTTP activity of a client, kept in memory and written out as a HAR 1.2
+// file (http://www.softwareishard.com/blog/har-12-spec/), eg: to open in
+// the browser devtools or attach to an issue when scraping misbehaves.
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+use serde_json::{json, Value};
+
+use super::scholar::Client;
+
+// values replaced by "[redacted]" unless ClientBuilder::har_keep_sensitive
+const SENSITIVE_HEADERS: &[&str] = &[
+    "cookie",
+    "set-cookie",
+    "authorization",
+    "proxy-authorization",
+];
+
+pub(crate) struct HarLog {
+    keep_sensitive: bool,
+    body_limit: Option<usize>,
+    // reqwest adds the client's User-Agent after the request is built
+    user_agent: Option<String>,
+    entries: Mutex<Vec<Value>>,
+}
+
+// a request sent, waiting for its response
+pub(crate) struct PendingEntry {
+    started: SystemTime,
+    sent: Instant,
+    request: Value,
+    response: Option<Value>,
+    wait: Duration,
+}
+
+impl HarLog {
+    pub(crate) fn new(
+        keep_sensitive: bool,
+        body_limit: Option<usize>,
+        user_agent: Option<String>,
+    ) -> HarLog {
+        HarLog {
+            keep_sensitive,
+            body_limit,
+            user_agent,
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn request(&self, request: &reqwest::Request) -> PendingEntry {
+        let mut headers = self.headers(request.headers());
+        if let Some(user_agent) = &self.user_agent {
+            if !request.headers().contains_key(reqwest::header::USER_AGENT) {
+                headers.push(json!({"name": "user-agent", "value": user_agent}));
+            }
+        }
+        let query = request
+            .url()
+            .query_pairs()
+            .map(|(name, value)| json!({"name": name, "value": value}))
+            .collect::<Vec<_>>();
+        PendingEntry {
+            started: SystemTime::now(),
+            sent: Instant::now(),
+            request: json!({
+                "method": request.method().as_str(),
+                "url": request.url().as_str(),
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": headers,
+                "queryString": query,
+                "headersSize": -1,
+                "bodySize": 0,
+            }),
+            response: None,
+            wait: Duration::default(),
+        }
+    }
+
+    pub(crate) fn response(&self, pending: &mut PendingEntry, response: &reqwest::Response) {
+        pending.wait = pending.sent.elapsed();
+        let status = response.status();
+        let redirect = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .unwrap_or_default();
+        let mime_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .unwrap_or_default();
+        let mut recorded = json!({
+            "status": status.as_u16(),
+            "statusText": status.canonical_reason().unwrap_or_default(),
+            "httpVersion": format!("{:?}", response.version()),
+            "cookies": [],
+            "headers": self.headers(response.headers()),
+            "content": {"size": 0, "mimeType": mime_type},
+            "redirectURL": redirect,
+            "headersSize": -1,
+            "bodySize": -1,
+        });
+        // redirects are followed, this is where they ended
+        if pending.request["url"] != response.url().as_str() {
+            recorded["_finalUrl"] = json!(response.url().as_str());
+        }
+        pending.request["httpVersion"] = recorded["httpVersion"].clone();
+        pending.response = Some(recorded);
+    }
+
+    // Adds the exchange to the log, with `body` when it was read. Requests
+    // that got no response, eg: refused connections, are not logged.
+    pub(crate) fn finish(&self, pending: PendingEntry, body: Option<&str>) {
+        let mut response = match pending.response {
+            Some(response) => response,
+            None => return,
+        };
+        let receive = pending.sent.elapsed().saturating_sub(pending.wait);
+        if let Some(body) = body {
+            response["bodySize"] = json!(body.len());
+            response["content"]["size"] = json!(body.len());
+            if let Some(limit) = self.body_limit {
+                let mut end = limit.min(body.len());
+                while !body.is_char_boundary(end) {
+                    end -= 1;
+                }
+                response["content"]["text"] = json!(&body[..end]);
+                if end < body.len() {
+                    response["content"]["comment"] =
+                        json!(format!("truncated to {} of {} bytes", end, body.len()));
+                }
+            }
+        }
+        let (wait, receive) = (millis(pending.wait), millis(receive));
+        let entry = json!({
+            "startedDateTime": iso8601(pending.started),
+            "time": wait + receive,
+            "request": pending.request,
+            "response": response,
+            "cache": {},
+            "timings": {"send": 0.0, "wait": wait, "receive": receive},
+        });
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(entry);
+    }
+
+    fn headers(&self, headers: &HeaderMap) -> Vec<Value> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if !self.keep_sensitive && SENSITIVE_HEADERS.contains(&name.as_str()) {
+                    "[redacted]".to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                json!({"name": name.as_str(), "value": value})
+            })
+            .collect()
+    }
+
+    fn to_json(&self) -> Value {
+        let entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": {"name": "gscholar", "version": env!("CARGO_PKG_VERSION")},
+                "pages": [],
+                "entries": entries,
+            }
+        })
+    }
+}
+
+impl Client {
+    // The requests recorded so far as a HAR document, None unless the
+    // client was built with ClientBuilder::har_recording
+    pub fn har(&self) -> Option<Value> {
+        self.har_log().map(HarLog::to_json)
+    }
+
+    // Writes har() to `path`, fails with an io::ErrorKind::Other error when
+    // recording is off
+    pub fn export_har(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let har = self
+            .har()
+            .ok_or_else(|| std::io::Error::other("har recording is off"))?;
+        let json = serde_json::to_string_pretty(&har)?;
+        std::fs::write(path, json)
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// eg: 2024-03-05T14:07:09.123Z
+fn iso8601(time: SystemTime) -> String {
+    let since = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs % 86_400 / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+        since.subsec_millis()
+    )
+}
+
+// days since 1970-01-01 to a gregorian date, after Howard Hinnant's
+// civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{results_page, MockServer};
+    use crate::scholar::{Error, ScholarArgs};
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn dates() {
+        assert_eq!(iso8601(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        let time = UNIX_EPOCH + Duration::from_millis(1_709_647_629_123);
+        assert_eq!(iso8601(time), "2024-03-05T14:07:09.123Z");
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    }
+
+    #[test]
+    fn sensitive_headers_redacted() {
+        let mut headers = HeaderMap::new();
+        headers.insert("cookie", HeaderValue::from_static("NID=secret"));
+        headers.insert("set-cookie", HeaderValue::from_static("GSP=secret"));
+        headers.insert("accept", HeaderValue::from_static("text/html"));
+
+        let redacted = HarLog::new(false, None, None).headers(&headers);
+        let kept = HarLog::new(true, None, None).headers(&headers);
+
+        let values = |headers: &[Value]| {
+            headers
+                .iter()
+                .map(|h| h["value"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            values(&redacted),
+            vec!["[redacted]", "[redacted]", "text/html"]
+        );
+        assert_eq!(values(&kept), vec!["NID=secret", "GSP=secret", "text/html"]);
+    }
+
+    #[tokio::test]
+    async fn export_recorded_requests() {
+        let server = MockServer::start(vec![
+            (200, results_page(None, 2)),
+            (429, String::from("slow down")),
+        ])
+        .await;
+        let client = Client::builder()
+            .base_url(&server.base_url())
+            .user_agent("gscholar-test")
+            .har_recording(true)
+            .har_body_limit(16)
+            .build()
+            .unwrap();
+        client
+            .scrape_scholar(&ScholarArgs::new("abcd"))
+            .await
+            .unwrap();
+        let blocked = client.scrape_scholar(&ScholarArgs::new("efgh")).await;
+        assert!(matches!(blocked, Err(Error::Blocked)));
+        let path = std::env::temp_dir().join(format!("gscholar-{}.har", std::process::id()));
+
+        client.export_har(&path).unwrap();
+
+        let har: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(har["log"]["creator"]["name"], "gscholar");
+        let entries = har["log"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let first = &entries[0];
+        assert_eq!(first["request"]["method"], "GET");
+        assert_eq!(first["request"]["url"], server.base_url() + "q=abcd");
+        assert_eq!(first["request"]["queryString"][0]["value"], "abcd");
+        assert!(first["request"]["headers"]
+            .as_array()
+            .unwrap()
+            .contains(&json!({"name": "user-agent", "value": "gscholar-test"})));
+        assert_eq!(first["response"]["status"], 200);
+        assert_eq!(first["response"]["httpVersion"], "HTTP/1.1");
+        assert_eq!(
+            first["response"]["content"]["mimeType"],
+            "text/html; charset=utf-8"
+        );
+        let size = results_page(None, 2).len();
+        assert_eq!(first["response"]["content"]["size"], size);
+        assert_eq!(first["response"]["content"]["text"], "<html><body><div");
+        let started = first["startedDateTime"].as_str().unwrap();
+        assert!(started.ends_with('Z') && started.len() == 24, "{}", started);
+        let timings = &first["timings"];
+        for timing in ["send", "wait", "receive"].iter() {
+            assert!(timings[*timing].as_f64().unwrap() >= 0.0);
+        }
+
+        assert_eq!(entries[1]["response"]["status"], 429);
+        assert_eq!(entries[1]["response"]["statusText"], "Too Many Requests");
+        assert_eq!(entries[1]["response"]["content"]["text"], "slow down");
+    }
+
+    #[test]
+    fn export_needs_recording() {
+        let client = Client::builder().build().unwrap();
+
+        assert!(client.har().is_none());
+        assert!(client
+            .export_har(std::env::temp_dir().join("unused.har"))
+            .is_err());
+    }
+}
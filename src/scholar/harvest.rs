@@ -0,0 +1,347 @@
+use std::collections::{HashSet, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::scholar::{parse_total_results, Client, Error, ScholarArgs, ScholarResult};
+use crate::normalize::TextNormalizer;
+use crate::urlnorm;
+
+impl Client {
+    // Scholar serves at most ~1000 results per query. This runs `args` once
+    // per year from `from_year` to `to_year` (as_ylo = as_yhi), pages through
+    // each year and merges the results, dropping papers already seen.
+    // Coverage grows with the number of years, and so does the request
+    // volume: at least one request per year plus one per extra page, all
+    // spaced by the client's delay and counted against its quota.
+    pub async fn harvest_by_year(
+        &self,
+        args: &ScholarArgs,
+        from_year: u16,
+        to_year: u16,
+    ) -> Result<Vec<ScholarResult>, Error> {
+        let mut merged = Merged::default();
+        for year in from_year..=to_year {
+            let mut year_args = args.clone();
+            year_args.from_year = Some(year);
+            year_args.to_year = Some(year);
+            merged.extend(self.scrape_until(&year_args, |_| false).await?);
+        }
+        Ok(merged.results)
+    }
+
+    // Crawls every result of `args`, even past scholar's ~1000 results cap.
+    // When the reported total is over options.ceiling, the year range is
+    // split in halves, recursively, until each bucket's total fits (or
+    // options.max_depth / a single year is reached), and each bucket is
+    // crawled in full. With options.undated_pass, a last crawl without year
+    // bounds picks up results that have no year. Results are merged in
+    // year order, dropping duplicates (by cluster id when present).
+    // Costs one count request per bucket on top of the result pages.
+    pub async fn scrape_exhaustive(
+        &self,
+        args: &ScholarArgs,
+        options: YearSplitOptions,
+    ) -> Result<Vec<ScholarResult>, Error> {
+        let mut merged = Merged::default();
+        let total = self.bucket_total(args).await?;
+        if total.is_none_or(|total| total <= options.ceiling) {
+            merged.extend(self.scrape_until(args, |_| false).await?);
+            return Ok(merged.results);
+        }
+
+        let first = args.from_year.unwrap_or(options.first_year);
+        let last = args.to_year.unwrap_or(options.last_year);
+        let mut buckets = VecDeque::from(vec![(first, last, 0)]);
+        while let Some((from, to, depth)) = buckets.pop_front() {
+            let mut bucket_args = args.clone();
+            bucket_args.from_year = Some(from);
+            bucket_args.to_year = Some(to);
+            // the whole range was counted above
+            let total = match depth {
+                0 => total,
+                _ => self.bucket_total(&bucket_args).await?,
+            };
+            let fits = total.is_none_or(|total| total <= options.ceiling);
+            if !fits && from < to && depth < options.max_depth {
+                split(&mut buckets, from, to, depth);
+                continue;
+            }
+            if !fits {
+                log::warn!(
+                    "{}-{} still has {:?} results, some will be missed",
+                    from,
+                    to,
+                    total
+                );
+            }
+            merged.extend(self.scrape_until(&bucket_args, |_| false).await?);
+        }
+
+        if options.undated_pass {
+            let undated = self.scrape_until(args, |_| false).await?;
+            merged.extend(undated.into_iter().filter(|r| r.year.is_none()));
+        }
+        Ok(merged.results)
+    }
+
+    // "About N results" for `args`, read from a one result page
+    async fn bucket_total(&self, args: &ScholarArgs) -> Result<Option<u64>, Error> {
+        let mut count_args = args.clone();
+        count_args.limit = Some(1);
+        count_args.offset = None;
+        let doc = self.get_document(&self.get_url(&count_args)?).await?;
+        Ok(parse_total_results(&doc))
+    }
+}
+
+// How scrape_exhaustive partitions a query by publication year
+#[derive(Debug, Clone)]
+pub struct YearSplitOptions {
+    // most results scholar pages through for one query. Default = 1000
+    pub ceiling: u64,
+    // year range split when the args set no as_ylo/as_yhi.
+    // Default = 1900 to the current year
+    pub first_year: u16,
+    pub last_year: u16,
+    // how many times a range may be halved. Default = 8, enough to get from
+    // a century down to single years
+    pub max_depth: u32,
+    // crawl once more without year bounds for results without a year.
+    // Default = true
+    pub undated_pass: bool,
+}
+
+impl Default for YearSplitOptions {
+    fn default() -> Self {
+        YearSplitOptions {
+            ceiling: 1000,
+            first_year: 1900,
+            last_year: current_year(),
+            max_depth: 8,
+            undated_pass: true,
+        }
+    }
+}
+
+// queues the two halves of from..=to in place of it, earlier years first
+fn split(buckets: &mut VecDeque<(u16, u16, u32)>, from: u16, to: u16, depth: u32) {
+    let mid = from + (to - from) / 2;
+    buckets.push_front((mid + 1, to, depth + 1));
+    buckets.push_front((from, mid, depth + 1));
+}
+
+pub(crate) fn current_year() -> u16 {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() / (24 * 60 * 60))
+        .unwrap_or(0);
+    // 365.2425 days per gregorian year
+    (1970 + days * 10_000 / 3_652_425) as u16
+}
+
+// results of several queries, without duplicates
+#[derive(Default)]
+pub(crate) struct Merged {
+    seen: HashSet<String>,
+    pub(crate) results: Vec<ScholarResult>,
+}
+
+impl Merged {
+    pub(crate) fn extend(&mut self, results: impl IntoIterator<Item = ScholarResult>) {
+        for result in results {
+            if self.seen.insert(dedup_key(&result)) {
+                self.results.push(result);
+            }
+        }
+    }
+}
+
+// the cluster or cited-by id identifies a paper across queries, otherwise
+// fall back to the link and then the title
+fn dedup_key(result: &ScholarResult) -> String {
+    if let Some(cluster_id) = &result.cluster_id {
+        return format!("cluster:{}", cluster_id);
+    }
+    match &result.cite_id {
+        Some(cite_id) => format!("cites:{}", cite_id),
+        None if !result.link.is_empty() => format!("link:{}", urlnorm::canonicalize(&result.link)),
+        None => format!(
+            "title:{}",
+            TextNormalizer::default().for_matching(&result.title)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{
+        result_block, result_block_with_byline, results_page, results_page_with, MockServer,
+    };
+
+    fn count_page(total: &str) -> (u16, String) {
+        (200, results_page(Some(total), 1))
+    }
+
+    fn split_options(max_depth: u32) -> YearSplitOptions {
+        YearSplitOptions {
+            ceiling: 3,
+            first_year: 2018,
+            last_year: 2021,
+            max_depth,
+            undated_pass: true,
+        }
+    }
+
+    fn bucket_ranges(requests: &[String]) -> Vec<String> {
+        requests
+            .iter()
+            .filter(|r| r.contains("&num=1"))
+            .map(|r| {
+                let years = r
+                    .split('&')
+                    .filter(|p| p.starts_with("as_y"))
+                    .collect::<Vec<_>>();
+                years.join("&")
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn exhaustive_splits_until_buckets_fit() {
+        let undated = result_block_with_byline(9, "A Author - example.org");
+        let server = MockServer::start(vec![
+            count_page("About 8 results"),
+            // 2018-2019
+            count_page("About 2 results"),
+            (
+                200,
+                results_page_with(None, vec![result_block(0), result_block(1)]),
+            ),
+            // 2020-2021
+            count_page("About 5 results"),
+            // 2020
+            count_page("About 3 results"),
+            (
+                200,
+                results_page_with(
+                    None,
+                    vec![result_block(1), result_block(2), result_block(3)],
+                ),
+            ),
+            // 2021
+            count_page("About 2 results"),
+            (
+                200,
+                results_page_with(None, vec![result_block(4), result_block(5)]),
+            ),
+            // undated pass
+            (200, results_page_with(None, vec![result_block(6), undated])),
+        ])
+        .await;
+        let client = server.client();
+
+        let results = client
+            .scrape_exhaustive(&ScholarArgs::new("abcd"), split_options(8))
+            .await
+            .unwrap();
+
+        let titles = results.iter().map(|r| r.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            titles,
+            vec!["Paper 0", "Paper 1", "Paper 2", "Paper 3", "Paper 4", "Paper 5", "Paper 9"]
+        );
+        assert_eq!(
+            bucket_ranges(&server.requests()),
+            vec![
+                "",
+                "as_ylo=2018&as_yhi=2019",
+                "as_ylo=2020&as_yhi=2021",
+                "as_ylo=2020&as_yhi=2020",
+                "as_ylo=2021&as_yhi=2021",
+            ]
+        );
+        assert_eq!(server.requests().len(), 9);
+    }
+
+    #[tokio::test]
+    async fn exhaustive_stops_splitting_at_max_depth() {
+        let server = MockServer::start(vec![
+            count_page("About 8 results"),
+            count_page("About 2 results"),
+            (
+                200,
+                results_page_with(None, vec![result_block(0), result_block(1)]),
+            ),
+            // over the ceiling, crawled as is at depth 1
+            count_page("About 5 results"),
+            (
+                200,
+                results_page_with(
+                    None,
+                    vec![result_block(2), result_block(3), result_block(4)],
+                ),
+            ),
+        ])
+        .await;
+        let client = server.client();
+        let options = YearSplitOptions {
+            undated_pass: false,
+            ..split_options(1)
+        };
+
+        let results = client
+            .scrape_exhaustive(&ScholarArgs::new("abcd"), options)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(
+            bucket_ranges(&server.requests()),
+            vec!["", "as_ylo=2018&as_yhi=2019", "as_ylo=2020&as_yhi=2021"]
+        );
+    }
+
+    #[tokio::test]
+    async fn exhaustive_under_ceiling_is_one_crawl() {
+        let server = MockServer::start(vec![
+            count_page("About 2 results"),
+            (200, results_page(None, 2)),
+        ])
+        .await;
+        let client = server.client();
+
+        let results = client
+            .scrape_exhaustive(&ScholarArgs::new("abcd"), split_options(8))
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn harvest_merges_year_buckets() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                results_page_with(None, vec![result_block(0), result_block(1)]),
+            ),
+            (
+                200,
+                results_page_with(None, vec![result_block(1), result_block(2)]),
+            ),
+        ])
+        .await;
+        let client = server.client();
+        let sc = ScholarArgs::in_source("Nature", "abcd");
+
+        let results = client.harvest_by_year(&sc, 2019, 2020).await.unwrap();
+
+        let titles = results.iter().map(|r| r.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Paper 0", "Paper 1", "Paper 2"]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("as_ylo=2019&as_yhi=2019"));
+        assert!(requests[1].contains("as_ylo=2020&as_yhi=2020"));
+    }
+}
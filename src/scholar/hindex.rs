@@ -0,0 +1,182 @@
+// h-index of the results of a query, eg: a topic or an author without a
+// profile, see metrics::h_index for the computation itself.
+
+use super::paging::{DEFAULT_PAGE_SIZE, RESULTS_CEILING};
+use super::scholar::{parse_total_results, Client, Error, ScholarArgs};
+use crate::metrics::h_index;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HIndexEstimate {
+    // h-index of the examined results
+    pub h_index: u32,
+    // false when paging stopped at max_pages or RESULTS_CEILING while
+    // unseen results could still raise the value: h_index is then a lower
+    // bound
+    pub exact: bool,
+    // number of results examined
+    pub examined: u32,
+    // number of results scholar reports, if shown
+    pub reported_total: Option<u64>,
+}
+
+impl Client {
+    // Pages through `args`, at most `max_pages` pages, and computes the
+    // h-index of the results, uncited ones counting as 0. Paging stops
+    // early once the value is settled: the query ran out of results, or
+    // too few results remain, going by the total scholar reports, to reach
+    // h-index + 1 even if each were cited more than any seen so far. The
+    // reported total is rounded ("About 1,230 results"), exact relies on
+    // it.
+    pub async fn h_index_for_query(
+        &self,
+        args: &ScholarArgs,
+        max_pages: u32,
+    ) -> Result<HIndexEstimate, Error> {
+        let page_size = args.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+        let mut page_args = args.clone();
+        page_args.limit = Some(page_size);
+        let mut offset = args.offset.unwrap_or(0);
+        let mut citations = Vec::new();
+        let mut estimate = HIndexEstimate {
+            h_index: 0,
+            exact: false,
+            examined: 0,
+            reported_total: None,
+        };
+
+        for page in 0..max_pages {
+            page_args.offset = Some(offset);
+            let url = self.get_url(&page_args)?;
+            let doc = self.get_document(&url).await?;
+            if page == 0 {
+                estimate.reported_total = parse_total_results(&doc);
+            }
+            let results = self.parse_page(&url, doc).await?;
+            let page_len = results.len() as u32;
+            citations.extend(results.iter().map(|r| r.cited_by.unwrap_or(0)));
+            estimate.examined += page_len;
+            estimate.h_index = h_index(citations.iter().copied());
+            offset += page_len;
+
+            if page_len < page_size {
+                estimate.exact = true;
+                break;
+            }
+            if let Some(total) = estimate.reported_total {
+                let remaining = total.saturating_sub(u64::from(offset));
+                if !could_rise(&citations, estimate.h_index, remaining) {
+                    estimate.exact = true;
+                    break;
+                }
+            }
+            if offset >= RESULTS_CEILING {
+                break;
+            }
+        }
+        Ok(estimate)
+    }
+}
+
+// Whether `remaining` more papers could take the h-index of `citations`
+// from `h` to h + 1. If h + 1 can't be reached, no higher value can.
+fn could_rise(citations: &[u32], h: u32, remaining: u64) -> bool {
+    let next = h + 1;
+    let above = citations.iter().filter(|count| **count >= next).count() as u64;
+    above + remaining >= u64::from(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{result_block, results_page_with, MockServer};
+
+    fn page(total: Option<&str>, first: usize, cited_by: &[u32]) -> String {
+        let blocks = cited_by
+            .iter()
+            .enumerate()
+            .map(|(i, count)| {
+                let i = first + i;
+                result_block(i).replace(
+                    &format!("Cited by 1{}<", i),
+                    &format!("Cited by {}<", count),
+                )
+            })
+            .collect();
+        results_page_with(total, blocks)
+    }
+
+    async fn estimate(pages: Vec<String>, max_pages: u32) -> (HIndexEstimate, usize) {
+        let server = MockServer::start(pages.into_iter().map(|p| (200, p)).collect()).await;
+        let client = server.client();
+        let mut args = ScholarArgs::new("abcd");
+        args.limit = Some(3);
+
+        let estimate = client.h_index_for_query(&args, max_pages).await.unwrap();
+        (estimate, server.requests().len())
+    }
+
+    #[test]
+    fn rise_needs_enough_papers() {
+        // h = 2 from [5, 3, 1]: 3 needs one more paper with 3 citations
+        assert!(could_rise(&[5, 3, 1], 2, 1));
+        assert!(!could_rise(&[5, 3, 1], 2, 0));
+        // h = 2 from [2, 2]: 3 needs one more above 3 of which there are none
+        assert!(!could_rise(&[2, 2], 2, 2));
+        assert!(could_rise(&[2, 2], 2, 3));
+    }
+
+    #[tokio::test]
+    async fn exact_when_results_run_out() {
+        let (estimate, requests) =
+            estimate(vec![page(None, 0, &[10, 4, 0]), page(None, 3, &[7, 3])], 5).await;
+
+        assert_eq!(
+            estimate,
+            HIndexEstimate {
+                h_index: 3,
+                exact: true,
+                examined: 5,
+                reported_total: None,
+            }
+        );
+        assert_eq!(requests, 2);
+    }
+
+    #[tokio::test]
+    async fn stops_once_settled() {
+        // h = 3 after the second page, with 2 results cited 4 times or more
+        // and 1 result left: not enough for 4
+        let (estimate, requests) = estimate(
+            vec![
+                page(Some("7 results"), 0, &[10, 3, 0]),
+                page(Some("7 results"), 3, &[7, 3, 1]),
+                page(Some("7 results"), 6, &[50]),
+            ],
+            5,
+        )
+        .await;
+
+        assert_eq!(estimate.h_index, 3);
+        assert!(estimate.exact);
+        assert_eq!(estimate.examined, 6);
+        assert_eq!(estimate.reported_total, Some(7));
+        assert_eq!(requests, 2);
+    }
+
+    #[tokio::test]
+    async fn lower_bound_at_page_cap() {
+        let (estimate, requests) = estimate(
+            vec![
+                page(Some("About 120 results"), 0, &[10, 4, 0]),
+                page(Some("About 120 results"), 3, &[7, 3, 1]),
+            ],
+            2,
+        )
+        .await;
+
+        assert_eq!(estimate.h_index, 3);
+        assert!(!estimate.exact);
+        assert_eq!(estimate.examined, 6);
+        assert_eq!(requests, 2);
+    }
+}
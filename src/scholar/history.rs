@@ -0,0 +1,281 @@
+use super::ids::CiteId;
+use super::progress::{no_progress, report, Progress, ProgressHandler};
+use super::scholar::{parse_total_results, Client, Error, ScholarArgs, ScholarResult};
+
+impl Client {
+    // Citations received per year, read from the "About N results" count of
+    // the cited-by query restricted to each single year (as_ylo = as_yhi).
+    // One request is sent per year, spaced by the client's delay.
+    pub async fn citation_history(
+        &self,
+        result: &ScholarResult,
+        from_year: u16,
+        to_year: u16,
+    ) -> Result<Vec<(u16, u64)>, Error> {
+        self.citation_history_with_progress(result, from_year, to_year, no_progress)
+            .await
+    }
+
+    // Same as citation_history, reporting each year to `progress`.
+    // `completed` counts years. If a year fails (eg: Error::Blocked) the
+    // years already fetched are returned in Error::HistoryIncomplete.
+    pub async fn citation_history_with_progress<H>(
+        &self,
+        result: &ScholarResult,
+        from_year: u16,
+        to_year: u16,
+        progress: H,
+    ) -> Result<Vec<(u16, u64)>, Error>
+    where
+        H: ProgressHandler,
+    {
+        let cite_id = result.cite_id.as_ref().ok_or(Error::RequiredFieldError)?;
+        let years = u64::from(to_year.saturating_sub(from_year)) + 1;
+
+        let mut history = Vec::new();
+        for year in from_year..=to_year {
+            match self.cited_by_count(cite_id, year).await {
+                Ok(count) => {
+                    history.push((year, count));
+                    report(
+                        &progress,
+                        Progress {
+                            operation: "citation_history",
+                            completed: history.len() as u64,
+                            total: Some(years),
+                            message: Some(format!("{}: {} citations", year, count)),
+                        },
+                    );
+                }
+                Err(cause) => {
+                    return Err(Error::HistoryIncomplete {
+                        completed: history,
+                        cause: Box::new(cause),
+                    })
+                }
+            }
+        }
+        Ok(history)
+    }
+
+    // Matching publications per year: the "About N results" count of `args`
+    // restricted to each single year from `from_year` to `to_year`, one
+    // request per year with num=1, spaced by the client's delay. The year
+    // range and paging of `args` are replaced. The count is None when
+    // scholar listed results without saying how many, not a guess; a year
+    // without any result counts 0.
+    pub async fn trend(
+        &self,
+        args: &ScholarArgs,
+        from_year: u16,
+        to_year: u16,
+    ) -> Result<Vec<(u16, Option<u64>)>, Error> {
+        self.trend_with_progress(args, from_year, to_year, no_progress)
+            .await
+    }
+
+    // Same as trend, reporting each year to `progress`. `completed` counts
+    // years.
+    pub async fn trend_with_progress<H>(
+        &self,
+        args: &ScholarArgs,
+        from_year: u16,
+        to_year: u16,
+        progress: H,
+    ) -> Result<Vec<(u16, Option<u64>)>, Error>
+    where
+        H: ProgressHandler,
+    {
+        let mut year_args = args.clone();
+        year_args.limit = Some(1);
+        year_args.offset = None;
+        let years = u64::from(to_year.saturating_sub(from_year)) + 1;
+
+        let mut trend = Vec::new();
+        for year in from_year..=to_year {
+            year_args.from_year = Some(year);
+            year_args.to_year = Some(year);
+            let doc = self.get_document(&self.get_url(&year_args)?).await?;
+            let count = match parse_total_results(&doc) {
+                Some(count) => Some(count),
+                None if self.scrape_serialize(doc)?.is_empty() => Some(0),
+                None => None,
+            };
+            trend.push((year, count));
+            report(
+                &progress,
+                Progress {
+                    operation: "trend",
+                    completed: trend.len() as u64,
+                    total: Some(years),
+                    message: Some(match count {
+                        Some(count) => format!("{}: {} results", year, count),
+                        None => format!("{}: count not shown", year),
+                    }),
+                },
+            );
+        }
+        Ok(trend)
+    }
+
+    async fn cited_by_count(&self, cite_id: &CiteId, year: u16) -> Result<u64, Error> {
+        let year = year.to_string();
+        let url = self.scholar_url(&[
+            ("cites", cite_id.as_str()),
+            ("as_ylo", &year),
+            ("as_yhi", &year),
+            ("num", "1"),
+        ])?;
+
+        let doc = self.get_document(&url).await?;
+        match parse_total_results(&doc) {
+            Some(count) => Ok(count),
+            // scholar omits the count when nothing matched
+            None if self.scrape_serialize(doc)?.is_empty() => Ok(0),
+            None => Err(Error::ParseError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{results_page, MockServer};
+
+    fn cited_result() -> ScholarResult {
+        ScholarResult {
+            title: "Paper".to_string(),
+            cite_id: Some("1234".parse().unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn citation_history_per_year() {
+        let server = MockServer::start(vec![
+            (200, results_page(Some("About 1,204 results (0.03 sec)"), 1)),
+            (200, results_page(Some("3 results (0.01 sec)"), 1)),
+            (200, results_page(None, 0)),
+        ])
+        .await;
+        let client = server.client();
+
+        let seen = std::sync::Mutex::new(Vec::new());
+        let history = client
+            .citation_history_with_progress(&cited_result(), 2019, 2021, |progress: &Progress| {
+                seen.lock().unwrap().push(progress.clone())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(history, vec![(2019, 1204), (2020, 3), (2021, 0)]);
+        let seen = seen.into_inner().unwrap();
+        assert_eq!(
+            seen.iter()
+                .map(|progress| (progress.completed, progress.total))
+                .collect::<Vec<_>>(),
+            vec![(1, Some(3)), (2, Some(3)), (3, Some(3))]
+        );
+        assert_eq!(seen[0].operation, "citation_history");
+        assert_eq!(seen[1].message.as_deref(), Some("2020: 3 citations"));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(
+            requests[1].contains("cites=1234&as_ylo=2020&as_yhi=2020&num=1"),
+            "request was {}",
+            requests[1]
+        );
+    }
+
+    #[tokio::test]
+    async fn citation_history_blocked_keeps_partial() {
+        let server = MockServer::start(vec![
+            (200, results_page(Some("About 12 results"), 1)),
+            (429, String::new()),
+            (200, results_page(Some("About 7 results"), 1)),
+        ])
+        .await;
+        let client = server.client();
+
+        match client.citation_history(&cited_result(), 2019, 2021).await {
+            Err(Error::HistoryIncomplete { completed, cause }) => {
+                assert_eq!(completed, vec![(2019, 12)]);
+                assert!(matches!(*cause, Error::Blocked));
+            }
+            _ => panic!("expected a partial history"),
+        }
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn trend_per_year() {
+        let server = MockServer::start(vec![
+            (200, results_page(Some("About 1,230 results (0.03 sec)"), 1)),
+            // results without a banner: unknown, not 0
+            (200, results_page(None, 1)),
+            (200, results_page(None, 0)),
+            (200, results_page(Some("About 2,045 results"), 1)),
+        ])
+        .await;
+        let client = server.client();
+        let mut args = ScholarArgs::new("graph neural networks");
+        args.limit = Some(20);
+        args.offset = Some(40);
+        let seen = std::sync::Mutex::new(Vec::new());
+
+        let trend = client
+            .trend_with_progress(&args, 2019, 2022, |progress: &Progress| {
+                seen.lock()
+                    .unwrap()
+                    .push((progress.completed, progress.total))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            trend,
+            vec![
+                (2019, Some(1230)),
+                (2020, None),
+                (2021, Some(0)),
+                (2022, Some(2045))
+            ]
+        );
+        assert_eq!(
+            seen.into_inner().unwrap(),
+            vec![(1, Some(4)), (2, Some(4)), (3, Some(4)), (4, Some(4))]
+        );
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(
+            requests[1].contains("q=graph%20neural%20networks&as_ylo=2020&as_yhi=2020&num=1 "),
+            "request was {}",
+            requests[1]
+        );
+    }
+
+    #[test]
+    fn total_results_locales() {
+        let cases = [
+            ("About 1,230 results (0.04 sec)", Some(1230)),
+            ("Ungefähr 1.230 Ergebnisse (0,04 Sek.)", Some(1230)),
+            ("Environ 1\u{a0}230 résultats (0,04 s)", Some(1230)),
+            ("1 result (0.01 sec)", Some(1)),
+            ("Page 2 of about 5,000 results (0.05 sec)", Some(5000)),
+            (
+                "Seite 3 von ungefähr 1.230 Ergebnissen (0,04 Sek.)",
+                Some(1230),
+            ),
+            ("約 1,230 件 （0.04 秒）", Some(1230)),
+        ];
+        for (banner, expected) in cases.iter() {
+            assert_eq!(
+                parse_total_results(&results_page(Some(banner), 0)),
+                *expected,
+                "banner {}",
+                banner
+            );
+        }
+        assert_eq!(parse_total_results(&results_page(None, 2)), None);
+    }
+}
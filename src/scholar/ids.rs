@@ -0,0 +1,142 @@
+// Identifiers scholar uses in its urls. Each kind gets its own type so a
+// cite id can't be passed where a cluster id is expected. Parsing with
+// FromStr checks the expected shape, From<String> takes any string as is.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::scholar::Error;
+
+macro_rules! scholar_id {
+    ($name:ident, $kind:literal, $valid:expr) => {
+        #[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Error> {
+                let valid: fn(&str) -> bool = $valid;
+                if !valid(s) {
+                    return Err(Error::InvalidId {
+                        kind: $kind,
+                        value: s.to_string(),
+                    });
+                }
+                Ok($name(s.to_string()))
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(id: String) -> Self {
+                $name(id)
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.0)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map($name)
+            }
+        }
+    };
+}
+
+scholar_id!(
+    // cluster - groups all versions of a paper, eg: 2003256315327920340
+    ClusterId,
+    "cluster id",
+    is_numeric_id
+);
+
+scholar_id!(
+    // cites - lists the papers citing one, eg: 2003256315327920340
+    CiteId,
+    "cite id",
+    is_numeric_id
+);
+
+scholar_id!(
+    // user - an author profile, eg: JicYPdAAAAAJ
+    AuthorId,
+    "author id",
+    |s| s.len() == 12
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+);
+
+// scholar's cluster and cite ids are 64 bit unsigned integers
+fn is_numeric_id(s: &str) -> bool {
+    s.parse::<u64>().is_ok() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_expected_shapes() {
+        assert_eq!(
+            "2003256315327920340".parse::<ClusterId>().unwrap().as_str(),
+            "2003256315327920340"
+        );
+        assert!("1234".parse::<CiteId>().is_ok());
+        assert_eq!(
+            "JicYPdAAAAAJ".parse::<AuthorId>().unwrap().to_string(),
+            "JicYPdAAAAAJ"
+        );
+
+        for invalid in ["", "+12", "12a", "99999999999999999999"].iter() {
+            assert!(invalid.parse::<CiteId>().is_err(), "{}", invalid);
+        }
+        match "JicYPd".parse::<AuthorId>() {
+            Err(Error::InvalidId { kind, value }) => {
+                assert_eq!(kind, "author id");
+                assert_eq!(value, "JicYPd");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        // the unchecked escape hatch
+        assert_eq!(
+            ClusterId::from("not-numeric".to_string()).as_str(),
+            "not-numeric"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let cite_id: CiteId = "1234".parse().unwrap();
+        let json = serde_json::to_string(&cite_id).unwrap();
+        assert_eq!(json, "\"1234\"");
+        assert_eq!(serde_json::from_str::<CiteId>(&json).unwrap(), cite_id);
+
+        let author_id: AuthorId = serde_json::from_str("\"JicYPdAAAAAJ\"").unwrap();
+        assert_eq!(author_id.as_str(), "JicYPdAAAAAJ");
+    }
+}
@@ -0,0 +1,71 @@
+// Scope of a search over scholar's legal documents, sent as as_sdt. Without
+// one, ScholarArgs searches articles (scholar's as_sdt=0,5).
+//
+// as_sdt values:
+//   2006              case law of all courts
+//   2007              articles and case law of all courts
+//   4,<id>,<id>,...   case law of the listed courts only
+// Court ids are scholar's own, as they appear in the as_sdt of the url after
+// ticking courts on its "Select courts" page, eg: "3" for the US Supreme
+// Court. Federal and state courts each have their own ids, listing only
+// those of one level excludes the others.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LegalScope {
+    // as_sdt=2006
+    CaseLaw,
+    // as_sdt=2007
+    ArticlesAndCaseLaw,
+    // as_sdt=4,<ids>, CaseLaw when empty
+    Courts(Vec<String>),
+}
+
+impl LegalScope {
+    // value of as_sdt
+    pub fn as_sdt(&self) -> String {
+        match self {
+            LegalScope::CaseLaw => "2006".to_string(),
+            LegalScope::ArticlesAndCaseLaw => "2007".to_string(),
+            LegalScope::Courts(ids) => {
+                let ids = ids
+                    .iter()
+                    .map(|id| id.trim())
+                    .filter(|id| !id.is_empty())
+                    .collect::<Vec<_>>();
+                if ids.is_empty() {
+                    LegalScope::CaseLaw.as_sdt()
+                } else {
+                    format!("4,{}", ids.join(","))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::ScholarArgs;
+
+    fn url(scope: LegalScope) -> String {
+        let mut args = ScholarArgs::new("fourth amendment");
+        args.legal_scope = Some(scope);
+        args.get_url().unwrap()
+    }
+
+    #[test]
+    fn emitted_as_sdt() {
+        assert_eq!(
+            url(LegalScope::CaseLaw),
+            "https://scholar.google.com/scholar?q=fourth%20amendment&as_sdt=2006"
+        );
+        assert!(url(LegalScope::ArticlesAndCaseLaw).ends_with("&as_sdt=2007"));
+        let courts = LegalScope::Courts(vec!["3".to_string(), " 33 ".to_string()]);
+        assert!(url(courts).ends_with("&as_sdt=4,3,33"));
+        assert!(url(LegalScope::Courts(Vec::new())).ends_with("&as_sdt=2006"));
+        assert!(!ScholarArgs::new("abcd")
+            .get_url()
+            .unwrap()
+            .contains("as_sdt"));
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use tokio::sync::Semaphore;
+
+use super::scholar::{Client, ScholarResult};
+
+impl Client {
+    // Checks that the link and source links of each result still answer,
+    // setting link_status, link_ok and link_timed_out, and the same fields
+    // of each SourceLink. Up to `concurrency` links are checked at once, but
+    // only one per host so a single publisher isn't hammered. Each link gets
+    // a HEAD request, or a one byte GET when the server rejects HEAD, with
+    // the client's link_timeout, following redirects for MAX_REDIRECTS
+    // requests at most. Empty links are left as they are.
+    pub async fn validate_links(&self, results: &mut [ScholarResult], concurrency: usize) {
+        let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+        let mut hosts: HashMap<String, Arc<Semaphore>> = HashMap::new();
+
+        // (result, None for its link or Some(source link)), url
+        let links = results.iter().enumerate().flat_map(|(i, result)| {
+            let sources = result.source_links.iter().enumerate();
+            std::iter::once((i, None, &result.link))
+                .chain(sources.map(move |(j, source)| (i, Some(j), &source.url)))
+        });
+        let mut tasks = Vec::new();
+        for (i, source, link) in links {
+            if link.is_empty() {
+                continue;
+            }
+            let host = url::Url::parse(link)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_lowercase))
+                .unwrap_or_default();
+            let host_permit = hosts
+                .entry(host)
+                .or_insert_with(|| Arc::new(Semaphore::new(1)))
+                .clone();
+            let permits = permits.clone();
+            let http = self.http().clone();
+            let timeout = self.link_timeout();
+            let link = link.clone();
+            tasks.push((
+                i,
+                source,
+                tokio::spawn(async move {
+                    let _host = host_permit.acquire().await;
+                    let _permit = permits.acquire().await;
+                    link_status(&http, &link, timeout).await
+                }),
+            ));
+        }
+
+        for (i, source, task) in tasks {
+            let checked = task.await.unwrap_or(LinkCheck::Failed);
+            let status = match checked {
+                LinkCheck::Answered(status) => Some(status),
+                LinkCheck::TimedOut | LinkCheck::Failed => None,
+            };
+            let ok = status.is_some_and(|status| (200..300).contains(&status));
+            let timed_out = checked == LinkCheck::TimedOut;
+            let result = &mut results[i];
+            match source {
+                None => {
+                    result.link_status = status;
+                    result.link_ok = ok;
+                    result.link_timed_out = timed_out;
+                }
+                Some(j) => {
+                    let source = &mut result.source_links[j];
+                    source.status = status;
+                    source.ok = ok;
+                    source.timed_out = timed_out;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LinkCheck {
+    // final status once redirects are followed
+    Answered(u16),
+    // no answer within the link timeout
+    TimedOut,
+    // connection errors and too many redirects
+    Failed,
+}
+
+async fn link_status(http: &reqwest::Client, url: &str, timeout: Duration) -> LinkCheck {
+    let checked = async {
+        let resp = http.head(url).timeout(timeout).send().await?;
+        match resp.status() {
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED => http
+                .get(url)
+                .header(reqwest::header::RANGE, "bytes=0-0")
+                .timeout(timeout)
+                .send()
+                .await
+                .map(|resp| resp.status()),
+            status => Ok(status),
+        }
+    };
+    match checked.await {
+        Ok(status) => LinkCheck::Answered(status.as_u16()),
+        Err(e) if e.is_timeout() => LinkCheck::TimedOut,
+        Err(_) => LinkCheck::Failed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::MockServer;
+    use crate::scholar::{SourceLink, MAX_REDIRECTS};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn with_link(link: &str) -> ScholarResult {
+        ScholarResult {
+            link: link.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn validate_links_statuses() {
+        let server = MockServer::start(vec![
+            (200, String::new()),
+            (404, String::new()),
+            (405, String::new()),
+            (206, "%".to_string()),
+            (200, String::new()),
+        ])
+        .await;
+        let client = Client::builder().build().unwrap();
+        let mut results = vec![
+            with_link(&server.url("/live")),
+            with_link(&server.url("/gone")),
+            with_link(&server.url("/no-head")),
+            ScholarResult {
+                source_links: vec![SourceLink {
+                    label: "[PDF] example.org".to_string(),
+                    url: server.url("/pdf"),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        ];
+
+        client.validate_links(&mut results, 4).await;
+
+        let statuses = results
+            .iter()
+            .map(|r| (r.link_status, r.link_ok))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                (Some(200), true),
+                (Some(404), false),
+                (Some(206), true),
+                (None, false)
+            ]
+        );
+        let source = &results[3].source_links[0];
+        assert_eq!((source.status, source.ok), (Some(200), true));
+        // one host, so the links were checked one after the other
+        let requests = server.requests();
+        assert_eq!(
+            requests,
+            vec![
+                "HEAD /live HTTP/1.1",
+                "HEAD /gone HTTP/1.1",
+                "HEAD /no-head HTTP/1.1",
+                "GET /no-head HTTP/1.1",
+                "HEAD /pdf HTTP/1.1",
+            ]
+        );
+        assert_eq!(server.header(3, "range").as_deref(), Some("bytes=0-0"));
+    }
+
+    #[tokio::test]
+    async fn validate_links_timeout() {
+        // accepts connections and never answers
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+        let client = Client::builder()
+            .link_timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let mut results = vec![
+            with_link(&format!("http://127.0.0.1:{}/slow", port)),
+            // nothing listens on port 1
+            with_link("http://127.0.0.1:1/refused"),
+        ];
+
+        client.validate_links(&mut results, 1).await;
+
+        let checked = results
+            .iter()
+            .map(|r| (r.link_status, r.link_ok, r.link_timed_out))
+            .collect::<Vec<_>>();
+        assert_eq!(checked, vec![(None, false, true), (None, false, false)]);
+    }
+
+    #[tokio::test]
+    async fn validate_links_bounds_redirects() {
+        // redirects every request back to itself
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counted = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counted.fetch_add(1, Ordering::SeqCst);
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let response = "HTTP/1.1 302 Found\r\nLocation: /loop\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        let client = Client::builder().build().unwrap();
+        let mut results = vec![with_link(&format!("http://127.0.0.1:{}/loop", port))];
+
+        client.validate_links(&mut results, 1).await;
+
+        assert_eq!(
+            (results[0].link_status, results[0].link_timed_out),
+            (None, false)
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), MAX_REDIRECTS);
+    }
+}
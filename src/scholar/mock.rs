@@ -0,0 +1,298 @@
+// Minimal scripted HTTP server for tests. Each connection is answered with
+// the next response in the script, and the request heads are recorded so
+// tests can assert on the urls and headers the client sent.
+
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use super::{Client, ProfileArticle};
+
+// status, headers added to the default ones and body
+pub(crate) type RawResponse = (u16, Vec<(&'static str, &'static str)>, Vec<u8>);
+
+pub(crate) struct MockServer {
+    port: u16,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub(crate) async fn start(script: Vec<(u16, String)>) -> MockServer {
+        let script = script
+            .into_iter()
+            .map(|(status, body)| (status, Vec::new(), body.into_bytes()))
+            .collect();
+        MockServer::start_raw(script).await
+    }
+
+    // start with a body of any bytes and headers added to each response,
+    // eg: ("Content-Encoding", "gzip")
+    pub(crate) async fn start_raw(script: Vec<RawResponse>) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        tokio::spawn(async move {
+            let mut script = script.into_iter();
+            loop {
+                let (mut socket, _) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(_) => return,
+                };
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&head).into_owned());
+
+                let (status, headers, body) = script
+                    .next()
+                    .unwrap_or_else(|| (404, Vec::new(), b"not scripted".to_vec()));
+                let mut response = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    status,
+                    body.len()
+                );
+                for (name, value) in headers {
+                    response.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                response.push_str("\r\n");
+                let mut response = response.into_bytes();
+                response.extend_from_slice(&body);
+                let _ = socket.write_all(&response).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+
+        MockServer { port, requests }
+    }
+
+    pub(crate) fn base_url(&self) -> String {
+        self.url("/scholar?")
+    }
+
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:{}{}", self.port, path)
+    }
+
+    // a client with the default settings, searching on the server
+    pub(crate) fn client(&self) -> Client {
+        Client::builder()
+            .base_url(&self.base_url())
+            .build()
+            .unwrap()
+    }
+
+    // request lines, eg: "GET /scholar?q=abcd HTTP/1.1"
+    pub(crate) fn requests(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|head| head.lines().next().unwrap_or_default().to_string())
+            .collect()
+    }
+
+    // value of header `name` in the `index`th request
+    pub(crate) fn header(&self, index: usize, name: &str) -> Option<String> {
+        let requests = self.requests.lock().unwrap();
+        requests[index].lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name)
+                .then(|| value.trim().to_string())
+        })
+    }
+}
+
+// a results page in the shape scholar serves, with `count` results and an
+// optional "About N results" banner
+pub(crate) fn results_page(total: Option<&str>, count: usize) -> String {
+    results_page_with(total, (0..count).map(result_block).collect())
+}
+
+pub(crate) fn results_page_with(total: Option<&str>, blocks: Vec<String>) -> String {
+    let mut page = String::from("<html><body>");
+    if let Some(total) = total {
+        page.push_str(&format!(
+            "<div id=\"gs_ab_md\"><div class=\"gs_ab_mdw\">{}</div></div>",
+            total
+        ));
+    }
+    for block in blocks {
+        page.push_str(&block);
+    }
+    page.push_str("</body></html>");
+    page
+}
+
+// `page` with scholar's header search box showing `query`
+pub(crate) fn with_search_box(page: String, query: &str) -> String {
+    let value = query.replace('&', "&amp;").replace('"', "&quot;");
+    page.replacen(
+        "<body>",
+        &format!(
+            "<body><form id=\"gs_hdr_frm\"><input id=\"gs_hdr_tsi\" name=\"q\" value=\"{}\"></form>",
+            value
+        ),
+        1,
+    )
+}
+
+pub(crate) fn result_block(i: usize) -> String {
+    result_block_with_byline(
+        i,
+        "A Author, B Author - Journal of Examples, 2019 - example.org",
+    )
+}
+
+// a result as listed when sorting by date, with its age before the abstract
+pub(crate) fn result_block_with_age(i: usize, age: &str) -> String {
+    result_block_with_byline(i, "A Author, B Author - Journal of Examples - example.org").replace(
+        "<div class=\"gs_rs\">",
+        &format!(
+            "<div class=\"gs_rs\"><span class=\"gs_age\">{} - </span>",
+            age
+        ),
+    )
+}
+
+pub(crate) fn result_block_with_byline(i: usize, byline: &str) -> String {
+    format!(
+        r#"<div class="gs_r gs_or gs_scl" data-cid="cid{i}">
+<div class="gs_ri">
+<h3 class="gs_rt"><a id="cid{i}" href="https://example.org/paper{i}">Paper {i}</a></h3>
+<div class="gs_a">{byline}</div>
+<div class="gs_rs">Abstract of paper {i}</div>
+<div class="gs_fl gs_flb">
+<a href="javascript:void(0)" class="gs_or_cit gs_or_btn gs_nph">Cite</a>
+<a href="/scholar?cites=100{i}&amp;as_sdt=2005&amp;sciodt=0,5&amp;hl=en">Cited by 1{i}</a>
+<a href="/scholar?q=related:cid{i}:scholar.google.com/&amp;scioq=&amp;hl=en&amp;as_sdt=0,5">Related articles</a>
+<a href="/scholar?cluster=200{i}&amp;hl=en&amp;as_sdt=0,5" class="gs_nph">All 3 versions</a>
+</div>
+</div>
+</div>"#,
+        i = i,
+        byline = byline
+    )
+}
+
+// An author profile page in the shape scholar serves: the header, the
+// metrics table, the citations chart, the co-authors box and the article
+// list. Parts left empty are left out of the page.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProfilePage {
+    pub(crate) name: String,
+    pub(crate) affiliation: Option<String>,
+    // the "All" column: citations, h-index and i10-index
+    pub(crate) metrics: [u32; 3],
+    // first year and citations of the "Since" column
+    pub(crate) since: (u16, u32),
+    // bars of the citations chart by year, None for a year without a bar
+    pub(crate) chart: Vec<(u16, Option<u32>)>,
+    // (user id, name) of the listed co-authors
+    pub(crate) coauthors: Vec<(String, String)>,
+    pub(crate) articles: Vec<ProfileArticle>,
+}
+
+impl ProfilePage {
+    pub(crate) fn html(&self) -> String {
+        let mut page = format!("<html><body>\n<div id=\"gsc_prf_in\">{}</div>\n", self.name);
+        if let Some(affiliation) = &self.affiliation {
+            page.push_str(&format!(
+                "<div class=\"gsc_prf_il\">{}</div>\n",
+                affiliation
+            ));
+        }
+        let [citations, h_index, i10_index] = self.metrics;
+        let (since, recent) = self.since;
+        page.push_str(&format!(
+            r#"<table id="gsc_rsb_st"><thead><tr><th></th><th class="gsc_rsb_sth">All</th><th class="gsc_rsb_sth">Since {since}</th></tr></thead><tbody>
+<tr><td class="gsc_rsb_sc1"><a class="gsc_rsb_f">Citations</a></td><td class="gsc_rsb_std">{citations}</td><td class="gsc_rsb_std">{recent}</td></tr>
+<tr><td class="gsc_rsb_sc1"><a class="gsc_rsb_f">h-index</a></td><td class="gsc_rsb_std">{h_index}</td><td class="gsc_rsb_std">1</td></tr>
+<tr><td class="gsc_rsb_sc1"><a class="gsc_rsb_f">i10-index</a></td><td class="gsc_rsb_std">{i10_index}</td><td class="gsc_rsb_std">1</td></tr>
+</tbody></table>
+"#
+        ));
+        if !self.chart.is_empty() {
+            let bars = self.chart.len();
+            page.push_str("<div class=\"gsc_md_hist_b\">");
+            for (year, _) in &self.chart {
+                page.push_str(&format!("<span class=\"gsc_g_t\">{}</span>", year));
+            }
+            for (i, (_, citations)) in self.chart.iter().enumerate() {
+                if let Some(citations) = citations {
+                    page.push_str(&format!(
+                        r#"<a href="javascript:void(0)" class="gsc_g_a" style="right:{}px;height:9px;z-index:{}"><span class="gsc_g_al">{}</span></a>"#,
+                        (bars - i) * 32,
+                        bars - i,
+                        citations
+                    ));
+                }
+            }
+            page.push_str("</div>\n");
+        }
+        if !self.coauthors.is_empty() {
+            page.push_str("<div id=\"gsc_rsb_co\"><ul class=\"gsc_rsb_a\">");
+            for (user_id, name) in &self.coauthors {
+                page.push_str(&format!(
+                    r#"<li><div class="gsc_rsb_aa"><div class="gsc_rsb_a_desc"><a href="/citations?user={}&amp;hl=en">{}</a><span class="gsc_rsb_a_ext">Example University</span></div></div></li>"#,
+                    user_id, name
+                ));
+            }
+            page.push_str("</ul></div>\n");
+        }
+        page.push_str("<table id=\"gsc_a_t\"><tbody id=\"gsc_a_b\">");
+        for article in &self.articles {
+            let year = article.year.map(|y| y.to_string()).unwrap_or_default();
+            // the year is repeated after the venue, shown on small screens
+            let venue = match (&article.venue, article.year) {
+                (Some(venue), Some(year)) => {
+                    format!("{}<span class=\"gs_oph\">, {}</span>", venue, year)
+                }
+                (venue, _) => venue.clone().unwrap_or_default(),
+            };
+            let cited_by = article
+                .cited_by
+                .map(|count| {
+                    format!(
+                        r#"<a href="https://scholar.google.com/scholar?cites=1" class="gsc_a_ac gs_ibl">{}</a>"#,
+                        count
+                    )
+                })
+                .unwrap_or_default();
+            page.push_str(&format!(
+                r#"<tr class="gsc_a_tr"><td class="gsc_a_t"><a href="/citations?view_op=view_citation&amp;hl=en&amp;citation_for_view={}" class="gsc_a_at">{}</a><div class="gs_gray">{}</div><div class="gs_gray">{}</div></td><td class="gsc_a_c">{}</td><td class="gsc_a_y"><span class="gsc_a_h gsc_a_hc gs_ibl">{}</span></td></tr>"#,
+                article.id, article.title, article.authors, venue, cited_by, year
+            ));
+        }
+        page.push_str("</tbody></table>\n</body></html>");
+        page
+    }
+}
+
+// an article of the JicYPdAAAAAJ profile by J Doe and A Smith, with `id`
+// after the profile's id, eg: "aaa" for "JicYPdAAAAAJ:aaa"
+pub(crate) fn profile_article(
+    id: &str,
+    title: &str,
+    year: u16,
+    cited_by: Option<u32>,
+) -> ProfileArticle {
+    ProfileArticle {
+        id: format!("JicYPdAAAAAJ:{}", id),
+        title: title.to_string(),
+        authors: "J Doe, A Smith".to_string(),
+        venue: Some("Journal of Examples 1 (2), 3-4".to_string()),
+        year: Some(year),
+        cited_by,
+    }
+}
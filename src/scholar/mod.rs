@@ -1,3 +1,75 @@
+mod access;
+mod args;
+mod cite;
+mod citers;
+mod coauthors;
+mod compare;
+#[cfg(feature = "doh")]
+mod doh;
+mod doi;
+mod facets;
+mod fields;
+mod filter;
+#[cfg(feature = "har")]
+mod har;
+mod harvest;
+mod hindex;
+mod history;
+mod ids;
+mod legal;
+mod links;
+#[cfg(test)]
+pub(crate) mod mock;
+mod overflow;
+mod paging;
+mod permalinks;
+mod presets;
+mod profile;
+mod progress;
+mod proxy;
+mod quota;
+mod response;
+mod retry;
+#[allow(clippy::module_inception)]
 mod scholar;
+mod selectors;
+mod trace;
+mod venue_search;
+mod versions;
+mod watch;
+#[cfg(feature = "wayback")]
+mod wayback;
 
+pub use self::access::AccessStatus;
+pub use self::args::ScholarArgsBuilder;
+pub use self::cite::Citation;
+pub use self::citers::*;
+pub use self::coauthors::{CoauthorGraph, CoauthorNode};
+pub use self::compare::{AuthorComparison, AuthorSummary};
+#[cfg(feature = "doh")]
+pub use self::doh::DEFAULT_DOH_ENDPOINT;
+pub use self::facets::SearchFacets;
+pub use self::fields::FieldSet;
+pub use self::filter::{DomainFilter, ResultFilter};
+pub(crate) use self::harvest::current_year;
+pub use self::harvest::YearSplitOptions;
+pub use self::hindex::HIndexEstimate;
+pub use self::ids::{AuthorId, CiteId, ClusterId};
+pub use self::legal::LegalScope;
+pub use self::overflow::{QueryOverflowPolicy, MAX_QUERY_LENGTH};
+pub use self::paging::{SearchExhausted, StreamEnd, RESULTS_CEILING};
+pub use self::presets::most_cited;
+pub use self::profile::{merge_profile_and_search, AuthorMetrics, AuthorProfile, ProfileArticle};
+pub use self::progress::{Progress, ProgressHandler};
+pub use self::quota::Quota;
+pub use self::response::{Pagination, ScholarResponse};
+pub use self::retry::{Backoff, BackoffStrategy};
 pub use self::scholar::*;
+pub use self::selectors::SelectorSet;
+pub use self::venue_search::VenueSearchOptions;
+pub use self::versions::{
+    PublishedMatch, VersionClassifier, VersionKind, PREPRINT_DOMAINS, PUBLISHER_DOMAINS,
+};
+pub use self::watch::{AuthorUpdate, AuthorWatchState, MetricDeltas};
+#[cfg(feature = "wayback")]
+pub use self::wayback::ResolvedLink;
@@ -0,0 +1,234 @@
+// Scholar cuts queries past MAX_QUERY_LENGTH characters without saying so,
+// searching for something else than what was asked. Over-long queries are
+// an error unless the client splits them, see QueryOverflowPolicy. The
+// limit of a client is set with ClientBuilder::max_query_length.
+
+use super::harvest::Merged;
+use super::scholar::{Client, Error, ScholarArgs, ScholarResult};
+
+// longest q, in characters, scholar runs as sent. Found by trial, scholar
+// doesn't document it
+pub const MAX_QUERY_LENGTH: usize = 256;
+
+// What the client does with a query longer than its max_query_length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueryOverflowPolicy {
+    // fail with Error::QueryTooLong
+    #[default]
+    Error,
+    // Client::scrape_scholar breaks a query of OR-ed terms, eg: keywords
+    // from a list, into as few sub-queries of whole terms as fit, runs each
+    // and merges their results without duplicates. Queries without a top
+    // level OR, or with a term that alone is too long, still fail.
+    Split,
+}
+
+pub(crate) fn check_length(query: &str, max: usize) -> Result<(), Error> {
+    let length = query.chars().count();
+    if length > max {
+        return Err(Error::QueryTooLong { length, max });
+    }
+    Ok(())
+}
+
+// Groups the top level OR terms of `query` into queries of at most `max`
+// characters. OR inside quotes or parentheses is left alone.
+pub(crate) fn split_query(query: &str, max: usize) -> Result<Vec<String>, Error> {
+    let too_long = || Error::QueryTooLong {
+        length: query.chars().count(),
+        max,
+    };
+    let terms = or_terms(query);
+    if terms.len() < 2 {
+        return Err(too_long());
+    }
+
+    let mut queries: Vec<String> = Vec::new();
+    for term in terms {
+        if term.chars().count() > max {
+            return Err(too_long());
+        }
+        match queries.last_mut() {
+            Some(last) if last.chars().count() + " OR ".len() + term.chars().count() <= max => {
+                last.push_str(" OR ");
+                last.push_str(&term);
+            }
+            _ => queries.push(term),
+        }
+    }
+    Ok(queries)
+}
+
+fn or_terms(query: &str) -> Vec<String> {
+    let mut terms = vec![Vec::new()];
+    let (mut quoted, mut depth) = (false, 0usize);
+    for word in query.split_whitespace() {
+        let top_level = !quoted && depth == 0;
+        if top_level && (word == "OR" || word == "|") {
+            terms.push(Vec::new());
+            continue;
+        }
+        for c in word.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                '(' if !quoted => depth += 1,
+                ')' if !quoted => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        if let Some(term) = terms.last_mut() {
+            term.push(word);
+        }
+    }
+    terms
+        .into_iter()
+        .filter(|words| !words.is_empty())
+        .map(|words| words.join(" "))
+        .collect()
+}
+
+impl Client {
+    // one page for each sub-query of split_query, merged
+    pub(crate) async fn scrape_split(
+        &self,
+        args: &ScholarArgs,
+    ) -> Result<Vec<ScholarResult>, Error> {
+        let mut merged = Merged::default();
+        let mut sub_args = args.clone();
+        for query in split_query(&args.query, self.max_query_length())? {
+            sub_args.query = query;
+            let url = self.get_url(&sub_args)?;
+            let doc = self.get_document(&url).await?;
+            merged.extend(self.parse_page(&url, doc).await?);
+        }
+        Ok(merged.results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{results_page, MockServer};
+    use crate::scholar::SearchExhausted;
+
+    // `count` OR-ed quoted terms of 21 characters each
+    fn keywords(count: usize) -> String {
+        (0..count)
+            .map(|i| format!("\"keyword number {:04}\"", i))
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    }
+
+    #[test]
+    fn long_query_is_an_error() {
+        let args = ScholarArgs::new(&keywords(12));
+
+        match args.get_url() {
+            Err(Error::QueryTooLong { length, max }) => {
+                assert_eq!(length, 12 * 21 + 11 * 4);
+                assert_eq!(max, MAX_QUERY_LENGTH);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(ScholarArgs::new(&keywords(10)).get_url().is_ok());
+    }
+
+    #[test]
+    fn split_keeps_terms_whole() {
+        let queries = split_query("a OR (b OR c) OR \"d OR e\" OR f", 20).unwrap();
+        assert_eq!(queries, vec!["a OR (b OR c)", "\"d OR e\" OR f"]);
+
+        let queries = split_query(&keywords(12), MAX_QUERY_LENGTH).unwrap();
+        assert_eq!(queries.len(), 2);
+        assert!(queries
+            .iter()
+            .all(|q| q.chars().count() <= MAX_QUERY_LENGTH));
+        assert_eq!(queries.join(" OR "), keywords(12));
+    }
+
+    #[test]
+    fn configured_limit() {
+        let client = Client::builder().max_query_length(40).build().unwrap();
+        let args = ScholarArgs::new(&keywords(2));
+
+        match client.get_url(&args) {
+            Err(Error::QueryTooLong { length, max }) => {
+                assert_eq!(length, 2 * 21 + 4);
+                assert_eq!(max, 40);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(client.get_url(&ScholarArgs::new(&keywords(1))).is_ok());
+        // the default client takes it
+        assert!(Client::builder().build().unwrap().get_url(&args).is_ok());
+    }
+
+    #[tokio::test]
+    async fn split_merges_results() {
+        let server = MockServer::start(vec![
+            (200, results_page(None, 2)),
+            (200, results_page(None, 3)),
+        ])
+        .await;
+        let client = Client::builder()
+            .base_url(&server.base_url())
+            .query_overflow(QueryOverflowPolicy::Split)
+            .build()
+            .unwrap();
+
+        let results = client
+            .scrape_scholar(&ScholarArgs::new(&keywords(12)))
+            .await
+            .unwrap();
+
+        // the two results of the first page come again on the second
+        let titles = results.iter().map(|r| r.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Paper 0", "Paper 1", "Paper 2"]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("keyword%20number%200000"));
+        assert!(requests[1].contains("keyword%20number%200011"));
+    }
+
+    #[tokio::test]
+    async fn scrape_all_splits_too() {
+        let server = MockServer::start(vec![
+            (200, results_page(None, 2)),
+            (200, results_page(None, 3)),
+        ])
+        .await;
+        let client = Client::builder()
+            .base_url(&server.base_url())
+            .query_overflow(QueryOverflowPolicy::Split)
+            .build()
+            .unwrap();
+
+        let (results, end) = client
+            .scrape_all(&ScholarArgs::new(&keywords(12)))
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(end, SearchExhausted::Complete);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn split_needs_or_terms() {
+        let server = MockServer::start(vec![]).await;
+        let client = Client::builder()
+            .base_url(&server.base_url())
+            .query_overflow(QueryOverflowPolicy::Split)
+            .build()
+            .unwrap();
+        let phrase = format!("\"{}\"", "a very long phrase ".repeat(15).trim());
+
+        let result = client.scrape_scholar(&ScholarArgs::new(&phrase)).await;
+
+        assert!(matches!(result, Err(Error::QueryTooLong { max: 256, .. })));
+        // a phrase with OR inside its quotes can't be split either
+        let quoted_or = format!("\"{} OR {}\"", "x".repeat(200), "y".repeat(200));
+        assert!(split_query(&quoted_or, MAX_QUERY_LENGTH).is_err());
+        assert!(server.requests().is_empty());
+    }
+}
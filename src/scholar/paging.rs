@@ -0,0 +1,756 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use futures_util::stream::{self, Stream, StreamExt};
+
+use super::progress::{no_progress, report, Progress, ProgressHandler};
+use super::scholar::{Client, Error, ScholarArgs, ScholarResult};
+use super::trace::{span, Instrument};
+
+// scholar's page size when num is not set
+pub(crate) const DEFAULT_PAGE_SIZE: u32 = 10;
+
+impl Client {
+    // Pages through the results of `args`, starting at its offset, and
+    // returns them up to (excluding) the first one `stop_when` matches.
+    // Useful to fetch only what is new since the last run of a query.
+    pub async fn scrape_until<F>(
+        &self,
+        args: &ScholarArgs,
+        stop_when: F,
+    ) -> Result<Vec<ScholarResult>, Error>
+    where
+        F: Fn(&ScholarResult) -> bool,
+    {
+        self.scrape_until_with_progress(args, stop_when, no_progress)
+            .await
+    }
+
+    // Same as scrape_until, reporting each page of results to `progress`.
+    // `completed` counts pages, the total is unknown until the last one.
+    pub async fn scrape_until_with_progress<F, H>(
+        &self,
+        args: &ScholarArgs,
+        stop_when: F,
+        progress: H,
+    ) -> Result<Vec<ScholarResult>, Error>
+    where
+        F: Fn(&ScholarResult) -> bool,
+        H: ProgressHandler,
+    {
+        let (results, _) = self
+            .page_through("scrape_until", args, stop_when, progress)
+            .await?;
+        Ok(results)
+    }
+
+    // Pages through every result of `args`, like scrape_until without a
+    // stop condition, and tells why paging ended: the query ran out of
+    // results, or scholar stopped serving them at RESULTS_CEILING although
+    // it reports more.
+    pub async fn scrape_all(
+        &self,
+        args: &ScholarArgs,
+    ) -> Result<(Vec<ScholarResult>, SearchExhausted), Error> {
+        self.page_through("scrape_all", args, |_| false, no_progress)
+            .await
+    }
+
+    // The urls scrape_until would request to collect up to `max_results`
+    // results of `args`, one per page, assuming every page comes back full.
+    // Nothing is fetched: a real run stops earlier when the query runs out
+    // of results, and never goes past RESULTS_CEILING.
+    pub fn plan(&self, args: &ScholarArgs, max_results: u32) -> Result<Vec<String>, Error> {
+        let page_size = args.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+        let start = args.offset.unwrap_or(0);
+        let end = start.saturating_add(max_results).min(RESULTS_CEILING);
+        let mut page_args = args.clone();
+        (start..end)
+            .step_by(page_size as usize)
+            .map(|offset| {
+                page_args.offset = Some(offset);
+                self.get_url(&page_args)
+            })
+            .collect()
+    }
+
+    // The pages of `args` one at a time, starting at its offset, each as
+    // scholar returned it. Ends after a short, empty or repeated page, or
+    // after the first error. Nothing is fetched until the stream is polled.
+    // With ClientBuilder::probe_page_size a short first page sets the page
+    // size instead of ending the stream. See page_stream_with_end to learn
+    // why the stream ended.
+    pub fn page_stream<'a>(
+        &'a self,
+        args: &ScholarArgs,
+    ) -> impl Stream<Item = Result<Vec<ScholarResult>, Error>> + 'a {
+        self.page_stream_with_end(args).0
+    }
+
+    // page_stream, with a StreamEnd telling once the stream is done whether
+    // the query ran out of results or scholar stopped at RESULTS_CEILING,
+    // like scrape_all
+    pub fn page_stream_with_end<'a>(
+        &'a self,
+        args: &ScholarArgs,
+    ) -> (
+        impl Stream<Item = Result<Vec<ScholarResult>, Error>> + 'a,
+        StreamEnd,
+    ) {
+        let mut page_args = args.clone();
+        page_args.offset = Some(args.offset.unwrap_or(0));
+        let first = StreamPaging {
+            page_size: args.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1),
+            args: page_args,
+            pages: 0,
+            previous: Vec::new(),
+            reported_total: None,
+        };
+        let end = StreamEnd::default();
+        let ending = end.clone();
+
+        let pages = stream::unfold(Some(first), move |next| {
+            let end = ending.clone();
+            async move {
+                let mut paging = next?;
+                let offset = paging.args.offset.unwrap_or(0);
+                let (page, total) = match self
+                    .fetch_results_page(&paging.args)
+                    .instrument(span!("scrape_page", offset = offset))
+                    .await
+                {
+                    Ok(fetched) => fetched,
+                    Err(e) => return Some((Err(e), None)),
+                };
+                paging.reported_total = total.or(paging.reported_total);
+                let page_len = page.len() as u32;
+                // past the ceiling scholar may serve the last page again
+                let ids = page_ids(&page);
+                if page.is_empty() || ids == paging.previous {
+                    end.set(exhausted(offset + page_len, offset, paging.reported_total));
+                    return None;
+                }
+                paging.pages += 1;
+                // a short first page that holds every reported result is
+                // the last page, not the page size scholar serves
+                let more = paging
+                    .reported_total
+                    .is_none_or(|total| total > u64::from(offset + page_len));
+                paging.page_size =
+                    self.probed_page_size(paging.pages == 1 && more, paging.page_size, page_len);
+                if page_len < paging.page_size {
+                    let retrieved = offset + page_len;
+                    end.set(exhausted(retrieved, retrieved, paging.reported_total));
+                    return Some((Ok(page), None));
+                }
+                paging.previous = ids;
+                paging.args.offset = Some(offset + page_len);
+                Some((Ok(page), Some(paging)))
+            }
+        });
+        (pages, end)
+    }
+
+    // The results of page_stream one at a time, ending before the first one
+    // `stop_when` matches, like scrape_until. Pages are fetched as the
+    // stream is consumed, so once it ends, or the caller stops polling it,
+    // no further page is requested. For a condition on whole pages, eg:
+    // their size, use StreamExt::take_while on page_stream instead.
+    pub fn result_stream_until<'a, F>(
+        &'a self,
+        args: &ScholarArgs,
+        stop_when: F,
+    ) -> impl Stream<Item = Result<ScholarResult, Error>> + 'a
+    where
+        F: Fn(&ScholarResult) -> bool + 'a,
+    {
+        self.result_stream_until_with_end(args, stop_when).0
+    }
+
+    // result_stream_until with the StreamEnd of its pages, see
+    // page_stream_with_end. A match of `stop_when` ends it as Complete
+    pub fn result_stream_until_with_end<'a, F>(
+        &'a self,
+        args: &ScholarArgs,
+        stop_when: F,
+    ) -> (
+        impl Stream<Item = Result<ScholarResult, Error>> + 'a,
+        StreamEnd,
+    )
+    where
+        F: Fn(&ScholarResult) -> bool + 'a,
+    {
+        let (pages, end) = self.page_stream_with_end(args);
+        let stopped = end.clone();
+        let results = stream::unfold(
+            (Box::pin(pages), VecDeque::new(), stop_when),
+            move |(mut pages, mut buffered, stop_when)| {
+                let end = stopped.clone();
+                async move {
+                    loop {
+                        if let Some(result) = buffered.pop_front() {
+                            if stop_when(&result) {
+                                end.set(SearchExhausted::Complete);
+                                return None;
+                            }
+                            return Some((Ok(result), (pages, buffered, stop_when)));
+                        }
+                        match pages.next().await? {
+                            Ok(page) => buffered.extend(page),
+                            Err(e) => return Some((Err(e), (pages, buffered, stop_when))),
+                        }
+                    }
+                }
+            },
+        );
+        (results, end)
+    }
+
+    // scrape_until and scrape_all, collecting page_stream_with_end and
+    // reporting each page under `operation`
+    async fn page_through<F, H>(
+        &self,
+        operation: &'static str,
+        args: &ScholarArgs,
+        stop_when: F,
+        progress: H,
+    ) -> Result<(Vec<ScholarResult>, SearchExhausted), Error>
+    where
+        F: Fn(&ScholarResult) -> bool,
+        H: ProgressHandler,
+    {
+        let (pages, end) = self.page_stream_with_end(args);
+        let mut pages = Box::pin(pages);
+        let mut offset = args.offset.unwrap_or(0);
+        let mut results = Vec::new();
+        let mut completed = 0;
+
+        while let Some(page) = pages.next().await {
+            let page = page?;
+            completed += 1;
+            report(
+                &progress,
+                Progress {
+                    operation,
+                    completed,
+                    total: None,
+                    message: Some(format!("{} results from offset {}", page.len(), offset)),
+                },
+            );
+            offset += page.len() as u32;
+            for result in page {
+                if stop_when(&result) {
+                    return Ok((results, SearchExhausted::Complete));
+                }
+                results.push(result);
+            }
+        }
+        Ok((results, end.get().unwrap_or(SearchExhausted::Complete)))
+    }
+
+    // the page size to go on with after a page of `page_len` results, see
+    // ClientBuilder::probe_page_size
+    fn probed_page_size(&self, first: bool, page_size: u32, page_len: u32) -> u32 {
+        if first && self.probe_page_size() && page_len > 0 && page_len < page_size {
+            log::debug!(
+                "scholar served {} results of the {} asked for, paging by {}",
+                page_len,
+                page_size,
+                page_len
+            );
+            page_len
+        } else {
+            page_size
+        }
+    }
+}
+
+// Most results scholar pages through for one query: past start=1000 it
+// answers with an empty page, or repeats the last one.
+pub const RESULTS_CEILING: u32 = 1000;
+
+// Why a stream of page_stream_with_end or result_stream_until_with_end
+// ended, shared with the stream. None until it has ended, and for a stream
+// that ended on an error
+#[derive(Debug, Clone, Default)]
+pub struct StreamEnd(Arc<Mutex<Option<SearchExhausted>>>);
+
+impl StreamEnd {
+    pub fn get(&self) -> Option<SearchExhausted> {
+        *self.0.lock().unwrap()
+    }
+
+    fn set(&self, end: SearchExhausted) {
+        *self.0.lock().unwrap() = Some(end);
+    }
+}
+
+// where page_stream_with_end is in the query
+struct StreamPaging {
+    args: ScholarArgs,
+    page_size: u32,
+    pages: u32,
+    previous: Vec<(Option<String>, String)>,
+    reported_total: Option<u64>,
+}
+
+// Why paging through a query ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchExhausted {
+    // every result was retrieved, or the stop condition matched
+    Complete,
+    // scholar stopped at RESULTS_CEILING while reporting `reported_total`
+    // results, `retrieved` is the offset the last page ended at. Split the
+    // query, eg: with Client::scrape_exhaustive, to get the rest
+    Capped { retrieved: u32, reported_total: u64 },
+}
+
+// `requested` is how far paging asked for, `retrieved` how far the results
+// actually went
+fn exhausted(requested: u32, retrieved: u32, reported_total: Option<u64>) -> SearchExhausted {
+    match reported_total {
+        Some(total) if requested >= RESULTS_CEILING && total > u64::from(retrieved) => {
+            SearchExhausted::Capped {
+                retrieved,
+                reported_total: total,
+            }
+        }
+        _ => SearchExhausted::Complete,
+    }
+}
+
+fn page_ids(page: &[ScholarResult]) -> Vec<(Option<String>, String)> {
+    page.iter()
+        .map(|result| (result.result_id.clone(), result.title.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{result_block, results_page, results_page_with, MockServer};
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn scrape_until_stops_on_page_two() {
+        let server = MockServer::start(vec![
+            (200, results_page(None, 2)),
+            (
+                200,
+                results_page_with(None, vec![result_block(2), result_block(3)]),
+            ),
+            (
+                200,
+                results_page_with(None, vec![result_block(4), result_block(5)]),
+            ),
+        ])
+        .await;
+        let client = server.client();
+        let mut sc = ScholarArgs::in_source("Nature", "abcd");
+        sc.limit = Some(2);
+
+        let results = client
+            .scrape_until(&sc, |result| result.title == "Paper 3")
+            .await
+            .unwrap();
+
+        let titles = results.iter().map(|r| r.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Paper 0", "Paper 1", "Paper 2"]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("&num=2&start=0"));
+        assert!(requests[1].contains("&num=2&start=2"));
+    }
+
+    #[tokio::test]
+    async fn scrape_until_ends_with_last_page() {
+        let server = MockServer::start(vec![(200, results_page(None, 1))]).await;
+        let client = server.client();
+        let sc = ScholarArgs::in_source("Nature", "abcd");
+
+        let results = client.scrape_until(&sc, |_| false).await.unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn scrape_until_reports_each_page() {
+        let server = MockServer::start(vec![
+            (200, results_page(None, 2)),
+            (
+                200,
+                results_page_with(None, vec![result_block(2), result_block(3)]),
+            ),
+            (200, results_page_with(None, vec![result_block(4)])),
+        ])
+        .await;
+        let client = server.client();
+        let mut sc = ScholarArgs::in_source("Nature", "abcd");
+        sc.limit = Some(2);
+        let events = Mutex::new(Vec::new());
+
+        let results = client
+            .scrape_until_with_progress(
+                &sc,
+                |_| false,
+                |progress: &Progress| events.lock().unwrap().push(progress.clone()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 5);
+        let events = events.into_inner().unwrap();
+        let summary = events
+            .iter()
+            .map(|e| (e.operation, e.completed, e.total, e.message.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("scrape_until", 1, None, Some("2 results from offset 0")),
+                ("scrape_until", 2, None, Some("2 results from offset 2")),
+                ("scrape_until", 3, None, Some("1 results from offset 4")),
+            ]
+        );
+    }
+
+    // three pages of 2 results from `offset`, with the banner scholar shows
+    // past the first page, eg: "Page 499 of about 5,000 results"
+    fn pages_near_ceiling(total: &str, offset: u32, last: Vec<String>) -> Vec<(u16, String)> {
+        let banner = |page: u32| {
+            format!(
+                "Page {} of about {} results (0.05 sec)",
+                offset / 2 + page,
+                total
+            )
+        };
+        vec![
+            (
+                200,
+                results_page_with(Some(&banner(1)), vec![result_block(0), result_block(1)]),
+            ),
+            (
+                200,
+                results_page_with(Some(&banner(2)), vec![result_block(2), result_block(3)]),
+            ),
+            (200, results_page_with(Some(&banner(3)), last)),
+        ]
+    }
+
+    async fn scrape_all_from(
+        script: Vec<(u16, String)>,
+        offset: u32,
+    ) -> (Vec<ScholarResult>, SearchExhausted) {
+        let server = MockServer::start(script).await;
+        let client = server.client();
+        let mut sc = ScholarArgs::new("abcd");
+        sc.limit = Some(2);
+        sc.offset = Some(offset);
+        client.scrape_all(&sc).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn scrape_all_exhausts_query() {
+        let script = vec![
+            (200, results_page(Some("About 3 results"), 2)),
+            (
+                200,
+                results_page_with(Some("About 3 results"), vec![result_block(2)]),
+            ),
+        ];
+
+        let (results, end) = scrape_all_from(script, 0).await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(end, SearchExhausted::Complete);
+    }
+
+    #[tokio::test]
+    async fn scrape_all_capped_by_empty_page() {
+        let script = pages_near_ceiling("5,000", 996, vec![]);
+
+        let (results, end) = scrape_all_from(script, 996).await;
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            end,
+            SearchExhausted::Capped {
+                retrieved: 1000,
+                reported_total: 5000
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn scrape_all_capped_by_repeated_page() {
+        let script = pages_near_ceiling("5,000", 994, vec![result_block(2), result_block(3)]);
+
+        let (results, end) = scrape_all_from(script, 994).await;
+
+        let titles = results.iter().map(|r| r.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Paper 0", "Paper 1", "Paper 2", "Paper 3"]);
+        assert_eq!(
+            end,
+            SearchExhausted::Capped {
+                retrieved: 998,
+                reported_total: 5000
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn scrape_all_ending_at_ceiling_with_nothing_left() {
+        let script = pages_near_ceiling("1,000", 996, vec![]);
+
+        let (results, end) = scrape_all_from(script, 996).await;
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(end, SearchExhausted::Complete);
+    }
+
+    #[tokio::test]
+    async fn scrape_all_with_zero_limit_stops_on_empty_page() {
+        let server = MockServer::start(vec![(200, results_page_with(None, vec![]))]).await;
+        let client = server.client();
+        let mut sc = ScholarArgs::new("abcd");
+        sc.limit = Some(0);
+
+        let (results, end) = client.scrape_all(&sc).await.unwrap();
+
+        assert!(results.is_empty());
+        assert_eq!(end, SearchExhausted::Complete);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn page_stream_yields_whole_pages() {
+        let server = MockServer::start(vec![
+            (200, results_page(None, 2)),
+            (200, results_page_with(None, vec![result_block(2)])),
+        ])
+        .await;
+        let client = server.client();
+        let mut sc = ScholarArgs::new("abcd");
+        sc.limit = Some(2);
+
+        let pages = client
+            .page_stream(&sc)
+            .map(|page| {
+                let page = page.unwrap();
+                page.into_iter().map(|r| r.title).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(pages, vec![vec!["Paper 0", "Paper 1"], vec!["Paper 2"]]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains("&num=2&start=2"));
+    }
+
+    async fn stream_end_from(script: Vec<(u16, String)>, offset: u32) -> (usize, StreamEnd) {
+        let server = MockServer::start(script).await;
+        let client = server.client();
+        let mut sc = ScholarArgs::new("abcd");
+        sc.limit = Some(2);
+        sc.offset = Some(offset);
+        let (results, end) = client.result_stream_until_with_end(&sc, |_| false);
+        assert_eq!(end.get(), None);
+        let count = results.collect::<Vec<_>>().await.len();
+        (count, end)
+    }
+
+    #[tokio::test]
+    async fn stream_end_tells_capped_from_complete() {
+        let (count, end) = stream_end_from(pages_near_ceiling("5,000", 996, vec![]), 996).await;
+        assert_eq!(count, 4);
+        assert_eq!(
+            end.get(),
+            Some(SearchExhausted::Capped {
+                retrieved: 1000,
+                reported_total: 5000
+            })
+        );
+
+        let (count, end) = stream_end_from(
+            pages_near_ceiling("5,000", 994, vec![result_block(2), result_block(3)]),
+            994,
+        )
+        .await;
+        assert_eq!(count, 4);
+        assert_eq!(
+            end.get(),
+            Some(SearchExhausted::Capped {
+                retrieved: 998,
+                reported_total: 5000
+            })
+        );
+
+        let script = vec![
+            (200, results_page(Some("About 3 results"), 2)),
+            (
+                200,
+                results_page_with(Some("Page 2 of about 3 results"), vec![result_block(2)]),
+            ),
+        ];
+        let (count, end) = stream_end_from(script, 0).await;
+        assert_eq!(count, 3);
+        assert_eq!(end.get(), Some(SearchExhausted::Complete));
+
+        let (_, end) = stream_end_from(vec![(500, String::new())], 0).await;
+        assert_eq!(end.get(), None);
+    }
+
+    #[tokio::test]
+    async fn page_stream_ends_after_error() {
+        let server = MockServer::start(vec![(500, String::new())]).await;
+        let client = server.client();
+
+        let pages = client
+            .page_stream(&ScholarArgs::new("abcd"))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(pages.len(), 1);
+        assert!(matches!(pages[0], Err(Error::InvalidResponseError)));
+    }
+
+    #[tokio::test]
+    async fn result_stream_stops_mid_page() {
+        let server = MockServer::start(vec![
+            (200, results_page(None, 2)),
+            (
+                200,
+                results_page_with(None, vec![result_block(2), result_block(3)]),
+            ),
+            (
+                200,
+                results_page_with(None, vec![result_block(4), result_block(5)]),
+            ),
+        ])
+        .await;
+        let client = server.client();
+        let mut sc = ScholarArgs::new("abcd");
+        sc.limit = Some(2);
+
+        let titles = client
+            .result_stream_until(&sc, |r| r.title == "Paper 3")
+            .map(|result| result.unwrap().title)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(titles, vec!["Paper 0", "Paper 1", "Paper 2"]);
+        // the third page was never requested
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    fn page(first: usize, len: usize) -> String {
+        results_page_with(None, (first..first + len).map(result_block).collect())
+    }
+
+    #[tokio::test]
+    async fn probe_adapts_to_served_page_size() {
+        let server = MockServer::start(vec![
+            (200, page(0, 10)),
+            (200, page(10, 10)),
+            (200, page(20, 3)),
+        ])
+        .await;
+        let client = Client::builder()
+            .base_url(&server.base_url())
+            .probe_page_size(true)
+            .build()
+            .unwrap();
+        let mut sc = ScholarArgs::new("abcd");
+        sc.limit = Some(20);
+
+        let (results, end) = client.scrape_all(&sc).await.unwrap();
+
+        assert_eq!(results.len(), 23);
+        assert_eq!(results[10].title, "Paper 10");
+        assert_eq!(end, SearchExhausted::Complete);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].contains("&num=20&start=10 "));
+        assert!(requests[2].contains("&num=20&start=20 "));
+    }
+
+    #[tokio::test]
+    async fn probe_in_page_stream() {
+        let server = MockServer::start(vec![(200, page(0, 10)), (200, page(10, 4))]).await;
+        let client = Client::builder()
+            .base_url(&server.base_url())
+            .probe_page_size(true)
+            .build()
+            .unwrap();
+        let mut sc = ScholarArgs::new("abcd");
+        sc.limit = Some(20);
+
+        let sizes = client
+            .page_stream(&sc)
+            .map(|page| page.unwrap().len())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(sizes, vec![10, 4]);
+        assert!(server.requests()[1].contains("&num=20&start=10 "));
+    }
+
+    #[tokio::test]
+    async fn probe_trusts_reported_total() {
+        let server = MockServer::start(vec![(200, results_page(Some("About 3 results"), 3))]).await;
+        let client = Client::builder()
+            .base_url(&server.base_url())
+            .probe_page_size(true)
+            .build()
+            .unwrap();
+        let mut sc = ScholarArgs::new("abcd");
+        sc.limit = Some(20);
+
+        let (results, _) = client.scrape_all(&sc).await.unwrap();
+
+        assert_eq!(results.len(), 3);
+        // nothing left to probe for
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn short_page_ends_paging_without_probe() {
+        let server = MockServer::start(vec![(200, page(0, 10)), (200, page(10, 10))]).await;
+        let client = server.client();
+        let mut sc = ScholarArgs::new("abcd");
+        sc.limit = Some(20);
+
+        let (results, _) = client.scrape_all(&sc).await.unwrap();
+
+        assert_eq!(results.len(), 10);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn plan_lists_page_urls() {
+        let client = Client::builder()
+            .base_url("http://mirror.example.org/scholar?")
+            .build()
+            .unwrap();
+        let mut sc = ScholarArgs::new("abcd");
+        sc.limit = Some(20);
+        sc.offset = Some(10);
+
+        let urls = client.plan(&sc, 50).unwrap();
+
+        assert_eq!(
+            urls,
+            vec![
+                "http://mirror.example.org/scholar?q=abcd&num=20&start=10",
+                "http://mirror.example.org/scholar?q=abcd&num=20&start=30",
+                "http://mirror.example.org/scholar?q=abcd&num=20&start=50",
+            ]
+        );
+        // stops at the ceiling
+        sc.offset = Some(960);
+        assert_eq!(client.plan(&sc, 100).unwrap().len(), 2);
+        assert!(client.plan(&ScholarArgs::new(""), 10).is_err());
+    }
+}
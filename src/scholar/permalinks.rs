@@ -0,0 +1,161 @@
+// Scholar pages about a parsed result, to share instead of the publisher
+// link. Built on the base url of the client that parsed the result, or on
+// scholar.google.com for results built by hand.
+
+use super::scholar::{Client, Error, ScholarArgs, ScholarResult};
+
+const SCHOLAR_BASE_URL: &str = "https://scholar.google.com/scholar?";
+
+impl ScholarResult {
+    // "All N versions" page, eg: https://scholar.google.com/scholar?cluster=123
+    pub fn scholar_cluster_url(&self) -> Option<String> {
+        let cluster_id = self.cluster_id.as_ref()?;
+        self.scholar_page(&format!("cluster={}", cluster_id))
+    }
+
+    // "Cited by" page, eg: https://scholar.google.com/scholar?cites=123
+    pub fn cited_by_url(&self) -> Option<String> {
+        let cite_id = self.cite_id.as_ref()?;
+        self.scholar_page(&format!("cites={}", cite_id))
+    }
+
+    // "Related articles" page, looked up by the result's data-cid, eg:
+    // https://scholar.google.com/scholar?q=related:abc:scholar.google.com/
+    pub fn related_url(&self) -> Option<String> {
+        let result_id = self.result_id.as_ref()?;
+        self.scholar_page(&format!("q=related:{}:scholar.google.com/", result_id))
+    }
+
+    fn scholar_page(&self, query: &str) -> Option<String> {
+        let base = self.base_url.as_deref().unwrap_or(SCHOLAR_BASE_URL);
+        let mut url = url::Url::parse(base).ok()?;
+        url.set_query(Some(query));
+        Some(url.to_string())
+    }
+}
+
+impl Client {
+    // The "Related articles" page of `result`, parsed like any results page.
+    // Requested through this client, on its base url, like related_url
+    // points to. Error::RequiredFieldError when the result has no data-cid
+    // to look the page up by, eg: with FieldSet::FOOTER left out.
+    pub async fn fetch_related(&self, result: &ScholarResult) -> Result<Vec<ScholarResult>, Error> {
+        let result_id = result.result_id.as_ref().ok_or(Error::RequiredFieldError)?;
+        let args = ScholarArgs::new(&format!("related:{}:scholar.google.com/", result_id));
+        self.scrape_scholar(&args).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{
+        result_block, result_block_with_byline, results_page_with, MockServer,
+    };
+    use crate::scholar::Client;
+    use scraper::{Html, Selector};
+
+    // absolute footer links of `block`, as scholar emits them
+    fn footer_links(block: &str, base: &str) -> Vec<url::Url> {
+        let base = url::Url::parse(base).unwrap();
+        let fragment = Html::parse_fragment(block);
+        let selector = Selector::parse(".gs_fl a[href^=\"/scholar\"]").unwrap();
+        fragment
+            .select(&selector)
+            .map(|a| base.join(a.value().attr("href").unwrap()).unwrap())
+            .collect()
+    }
+
+    // same page and the same value for the parameter identifying it
+    fn same_page(built: &str, emitted: &url::Url, param: &str) -> bool {
+        let built = url::Url::parse(built).unwrap();
+        let value = |url: &url::Url| {
+            url.query_pairs()
+                .find(|(key, _)| key == param)
+                .map(|(_, value)| value.into_owned())
+        };
+        built.origin() == emitted.origin()
+            && built.path() == emitted.path()
+            && value(&built).is_some()
+            && value(&built) == value(emitted)
+    }
+
+    #[test]
+    fn urls_match_scholar_footer() {
+        for base in [
+            "https://scholar.google.com/scholar?",
+            "http://mirror.example.org/scholar?",
+        ]
+        .iter()
+        {
+            let block = result_block(3);
+            let client = Client::builder().base_url(base).build().unwrap();
+            let result = client
+                .scrape_serialize(results_page_with(None, vec![block.clone()]))
+                .unwrap()
+                .remove(0);
+            let emitted = footer_links(&block, base);
+
+            let cited_by = result.cited_by_url().unwrap();
+            assert!(same_page(&cited_by, &emitted[0], "cites"), "{}", cited_by);
+            let related = result.related_url().unwrap();
+            assert!(same_page(&related, &emitted[1], "q"), "{}", related);
+            let cluster = result.scholar_cluster_url().unwrap();
+            assert!(same_page(&cluster, &emitted[2], "cluster"), "{}", cluster);
+        }
+    }
+
+    #[test]
+    fn urls_need_their_ids() {
+        let result = ScholarResult {
+            cluster_id: Some("2003".parse().unwrap()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            result.scholar_cluster_url().as_deref(),
+            Some("https://scholar.google.com/scholar?cluster=2003")
+        );
+        assert_eq!(result.cited_by_url(), None);
+        assert_eq!(result.related_url(), None);
+    }
+
+    #[tokio::test]
+    async fn fetch_related_page() {
+        let related = results_page_with(
+            Some("About 101 results"),
+            vec![
+                result_block_with_byline(7, "C Author - Related Journal, 2020 - example.org"),
+                result_block(8),
+            ],
+        );
+        let server = MockServer::start(vec![(200, related)]).await;
+        let client = server.client();
+        let result = client
+            .scrape_serialize(results_page_with(None, vec![result_block(3)]))
+            .unwrap()
+            .remove(0);
+
+        let results = client.fetch_related(&result).await.unwrap();
+
+        let titles = results.iter().map(|r| r.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Paper 7", "Paper 8"]);
+        assert_eq!(results[0].year, Some(2020));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(
+            requests[0].contains("q=related:cid3:scholar.google.com/"),
+            "{}",
+            requests[0]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_related_needs_result_id() {
+        let client = Client::builder().build().unwrap();
+
+        let result = client.fetch_related(&ScholarResult::default()).await;
+
+        assert!(matches!(result, Err(Error::RequiredFieldError)));
+    }
+}
@@ -0,0 +1,124 @@
+use std::cmp::Reverse;
+
+use super::harvest::current_year;
+use super::scholar::{Client, Error, ScholarArgs, ScholarResult};
+
+// terms appended to the query by ScholarArgs::surveys
+const SURVEY_TERMS: &str = "survey OR review";
+
+impl ScholarArgs {
+    // `query` restricted to the last five years (as_ylo = this year - 4),
+    // newest first (scisbd=2)
+    pub fn recent(query: &str) -> ScholarArgs {
+        recent_from(query, current_year())
+    }
+
+    // `query` with scholar's default relevance ranking, see
+    // Client::search_highly_cited for the citation filter and ordering
+    pub fn highly_cited(query: &str) -> ScholarArgs {
+        ScholarArgs::new(query)
+    }
+
+    // `query` + " survey OR review", limited to review articles (as_rr=1)
+    pub fn surveys(query: &str) -> ScholarArgs {
+        let mut args = ScholarArgs::new(&format!("{} {}", query.trim(), SURVEY_TERMS));
+        args.review_articles = Some(true);
+        args
+    }
+}
+
+impl Client {
+    // ScholarArgs::highly_cited, keeping the results cited at least
+    // `min_citations` times, most cited first
+    pub async fn search_highly_cited(
+        &self,
+        query: &str,
+        min_citations: u32,
+    ) -> Result<Vec<ScholarResult>, Error> {
+        let results = self
+            .scrape_scholar(&ScholarArgs::highly_cited(query))
+            .await?;
+        Ok(most_cited(results, min_citations))
+    }
+}
+
+// results cited at least `min_citations` times, most cited first. Ties keep
+// scholar's order.
+pub fn most_cited(results: Vec<ScholarResult>, min_citations: u32) -> Vec<ScholarResult> {
+    let mut results = results
+        .into_iter()
+        .filter(|result| result.cited_by.unwrap_or(0) >= min_citations)
+        .collect::<Vec<_>>();
+    results.sort_by_key(|result| Reverse(result.cited_by.unwrap_or(0)));
+    results
+}
+
+fn recent_from(query: &str, this_year: u16) -> ScholarArgs {
+    let mut args = ScholarArgs::new(query);
+    args.from_year = Some(this_year - 4);
+    args.sort_by = Some(2);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{result_block, results_page_with, MockServer};
+
+    #[test]
+    fn preset_urls() {
+        assert_eq!(
+            recent_from("graph isomorphism", 2026).get_url().unwrap(),
+            "https://scholar.google.com/scholar?q=graph%20isomorphism&as_ylo=2022&scisbd=2"
+        );
+        assert_eq!(
+            ScholarArgs::recent("graph isomorphism").from_year,
+            Some(current_year() - 4)
+        );
+        assert_eq!(
+            ScholarArgs::highly_cited("graph isomorphism")
+                .get_url()
+                .unwrap(),
+            "https://scholar.google.com/scholar?q=graph%20isomorphism"
+        );
+        assert_eq!(
+            ScholarArgs::surveys(" graph isomorphism ")
+                .get_url()
+                .unwrap(),
+            "https://scholar.google.com/scholar?q=graph%20isomorphism%20survey%20OR%20review&as_rr=1"
+        );
+    }
+
+    #[tokio::test]
+    async fn search_highly_cited_filters_and_sorts() {
+        let blocks = [(0, 5), (1, 120), (2, 40), (3, 120)]
+            .iter()
+            .map(|(i, cited_by)| {
+                result_block(*i).replace(
+                    &format!(">Cited by 1{}<", i),
+                    &format!(">Cited by {}<", cited_by),
+                )
+            })
+            .collect();
+        let server = MockServer::start(vec![(200, results_page_with(None, blocks))]).await;
+        let client = server.client();
+
+        let results = client
+            .search_highly_cited("graph isomorphism", 10)
+            .await
+            .unwrap();
+
+        let ranked = results
+            .iter()
+            .map(|r| (r.title.as_str(), r.cited_by))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranked,
+            vec![
+                ("Paper 1", Some(120)),
+                ("Paper 3", Some(120)),
+                ("Paper 2", Some(40)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,571 @@
+use std::collections::HashMap;
+
+use scraper::{Html, Selector};
+
+use super::ids::AuthorId;
+use super::scholar::{
+    byline_authors_truncated, parse_byline, query_param, Client, Error, ScholarResult,
+};
+
+// rows of a profile's article list per request, scholar's maximum
+const ARTICLES_PAGE_SIZE: usize = 100;
+// pages fetch_author_articles reads at most
+const MAX_ARTICLE_PAGES: usize = 20;
+
+#[derive(Debug, Clone, Default)]
+pub struct AuthorProfile {
+    // user - id of the profile, eg: JicYPdAAAAAJ
+    pub user_id: AuthorId,
+    pub name: String,
+    pub affiliation: Option<String>,
+    // domain of the verified email, eg: "cs.stanford.edu"
+    pub verified_email_domain: Option<String>,
+}
+
+// An article as listed on an author profile
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfileArticle {
+    // citation_for_view - the article on this profile, eg:
+    // "JicYPdAAAAAJ:u5HHmVD_uO8C"
+    pub id: String,
+    pub title: String,
+    // as listed, eg: "J Doe, A Smith"
+    pub authors: String,
+    // as listed, eg: "Nature 521 (7553), 436-444"
+    pub venue: Option<String>,
+    pub year: Option<u16>,
+    pub cited_by: Option<u32>,
+}
+
+// The article as a search result, eg: for merge_profile_and_search. The
+// listed authors are split like a byline's, and the year the listing repeats
+// after the venue is dropped from it. The profile's citation_for_view id has
+// no ScholarResult counterpart and is left out.
+impl From<ProfileArticle> for ScholarResult {
+    fn from(article: ProfileArticle) -> Self {
+        let year = article.year;
+        let venue = article.venue.map(|venue| {
+            let year = year.map(|year| format!(", {}", year));
+            match year.and_then(|year| venue.strip_suffix(&year)) {
+                Some(stripped) => stripped.to_string(),
+                None => venue,
+            }
+        });
+        ScholarResult {
+            authors: parse_byline(&article.authors).0,
+            authors_truncated: byline_authors_truncated(&article.authors),
+            author: article.authors,
+            raw_title: article.title.clone(),
+            title: article.title,
+            venue: venue.filter(|venue| !venue.is_empty()),
+            year: article.year,
+            cited_by: article.cited_by,
+            ..Default::default()
+        }
+    }
+}
+
+// The "All" column of a profile's citation metrics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AuthorMetrics {
+    pub citations: Option<u32>,
+    pub h_index: Option<u32>,
+    pub i10_index: Option<u32>,
+}
+
+impl Client {
+    pub async fn fetch_author_profile(&self, user_id: &AuthorId) -> Result<AuthorProfile, Error> {
+        let url = self.citations_url(&[("user", user_id.as_str()), ("hl", "en")])?;
+        let doc = self.get_document(&url).await?;
+        let mut profile = parse_author_profile(&doc)?;
+        profile.user_id = user_id.clone();
+        Ok(profile)
+    }
+
+    // The articles of a profile, newest first, and its citation metrics.
+    // Reads the list a hundred articles at a time, up to 2000.
+    pub async fn fetch_author_articles(
+        &self,
+        user_id: &AuthorId,
+    ) -> Result<(Vec<ProfileArticle>, AuthorMetrics), Error> {
+        let (articles, first_page) = self.fetch_author_listing(user_id).await?;
+        Ok((articles, parse_author_metrics(&first_page)?))
+    }
+
+    // the articles of a profile, newest first, and its first page, which
+    // also shows the profile's header, metrics and citations chart
+    pub(crate) async fn fetch_author_listing(
+        &self,
+        user_id: &AuthorId,
+    ) -> Result<(Vec<ProfileArticle>, String), Error> {
+        let mut articles = Vec::new();
+        let mut first_page = String::new();
+        for page in 0..MAX_ARTICLE_PAGES {
+            let cstart = (page * ARTICLES_PAGE_SIZE).to_string();
+            let pagesize = ARTICLES_PAGE_SIZE.to_string();
+            let url = self.citations_url(&[
+                ("user", user_id.as_str()),
+                ("hl", "en"),
+                ("cstart", &cstart),
+                ("pagesize", &pagesize),
+                ("sortby", "pubdate"),
+            ])?;
+            let doc = self.get_document(&url).await?;
+            let rows = parse_profile_articles(&doc)?;
+            if page == 0 {
+                first_page = doc;
+            }
+            let full = rows.len() == ARTICLES_PAGE_SIZE;
+            articles.extend(rows);
+            if !full {
+                break;
+            }
+        }
+        Ok((articles, first_page))
+    }
+}
+
+pub(crate) fn parse_profile_articles(document: &str) -> Result<Vec<ProfileArticle>, Error> {
+    let fragment = Html::parse_document(document);
+    let parse = |selector| Selector::parse(selector).map_err(|_| Error::ParseError);
+    let row_selector = parse("tr.gsc_a_tr")?;
+    let title_selector = parse("a.gsc_a_at")?;
+    let gray_selector = parse(".gs_gray")?;
+    let cited_selector = parse(".gsc_a_c a")?;
+    let year_selector = parse(".gsc_a_y")?;
+
+    let text = |element: scraper::ElementRef| {
+        element
+            .text()
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    Ok(fragment
+        .select(&row_selector)
+        .filter_map(|row| {
+            let title = row.select(&title_selector).next()?;
+            let id = query_param(title.value().attr("href")?, "citation_for_view")?;
+            let mut gray = row.select(&gray_selector).map(text);
+            Some(ProfileArticle {
+                id,
+                title: text(title),
+                authors: gray.next().unwrap_or_default(),
+                venue: gray.next().filter(|venue| !venue.is_empty()),
+                year: row
+                    .select(&year_selector)
+                    .next()
+                    .and_then(|year| text(year).parse().ok()),
+                cited_by: row
+                    .select(&cited_selector)
+                    .next()
+                    .and_then(|cited| text(cited).parse().ok()),
+            })
+        })
+        .collect())
+}
+
+// The labels are localized, the rows come in the order citations, h-index,
+// i10-index, each with the "All" value first
+pub(crate) fn parse_author_metrics(document: &str) -> Result<AuthorMetrics, Error> {
+    let fragment = Html::parse_document(document);
+    let row_selector = Selector::parse("#gsc_rsb_st tbody tr").map_err(|_| Error::ParseError)?;
+    let value_selector = Selector::parse("td.gsc_rsb_std").map_err(|_| Error::ParseError)?;
+    let mut values = fragment.select(&row_selector).map(|row| {
+        row.select(&value_selector)
+            .next()
+            .and_then(|value| value.text().collect::<String>().trim().parse().ok())
+    });
+    Ok(AuthorMetrics {
+        citations: values.next().flatten(),
+        h_index: values.next().flatten(),
+        i10_index: values.next().flatten(),
+    })
+}
+
+// An author's bibliography from the articles of their profile, converted
+// with ScholarResult::from, and the results of searches for their work,
+// each paper once. Profile articles come
+// first in their order, then the search results matching none of them in
+// theirs. Entries are the same paper when they share a cluster id, a
+// cited-by id, or title, first author and year (see ScholarResult::stable_id),
+// profile entries usually having no cluster id. Merged entries keep the
+// profile's fields, curated by the author, and take from the search result
+// what the profile lacks, eg: the link, abstract, source links and cluster.
+// The citation count is the larger of the two, they are counted at
+// different times.
+pub fn merge_profile_and_search(
+    profile_articles: Vec<ScholarResult>,
+    search_results: Vec<ScholarResult>,
+) -> Vec<ScholarResult> {
+    let mut merged: Vec<ScholarResult> = Vec::new();
+    let mut positions = HashMap::new();
+    for result in profile_articles.into_iter().chain(search_results) {
+        let position = match merge_keys(&result)
+            .iter()
+            .find_map(|key| positions.get(key).copied())
+        {
+            Some(position) => {
+                fill_missing(&mut merged[position], result);
+                position
+            }
+            None => {
+                merged.push(result);
+                merged.len() - 1
+            }
+        };
+        // a merged entry can gain ids, eg: the search result's cluster
+        for key in merge_keys(&merged[position]) {
+            positions.entry(key).or_insert(position);
+        }
+    }
+    merged
+}
+
+fn merge_keys(result: &ScholarResult) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Some(cluster_id) = &result.cluster_id {
+        keys.push(format!("cluster:{}", cluster_id));
+    }
+    if let Some(cite_id) = &result.cite_id {
+        keys.push(format!("cites:{}", cite_id));
+    }
+    if !result.title.trim().is_empty() {
+        keys.push(format!("text:{}", result.text_key()));
+    }
+    keys
+}
+
+// fills the fields `into` lacks from `from`
+fn fill_missing(into: &mut ScholarResult, from: ScholarResult) {
+    if into.title.is_empty() {
+        into.title = from.title;
+        into.raw_title = from.raw_title;
+    }
+    if into.authors.is_empty() {
+        into.author = from.author;
+        into.authors = from.authors;
+        into.authors_truncated = from.authors_truncated;
+    }
+    if into.abs.is_empty() {
+        into.abs = from.abs;
+        into.abstract_truncated = from.abstract_truncated;
+    }
+    if into.link.is_empty() {
+        into.link = from.link;
+        into.link_status = from.link_status;
+        into.link_ok = from.link_ok;
+        into.link_timed_out = from.link_timed_out;
+    }
+    if into.source_links.is_empty() {
+        into.source_links = from.source_links;
+        into.html_full_text_link = from.html_full_text_link;
+    }
+    into.venue = into.venue.take().or(from.venue);
+    into.year = into.year.or(from.year);
+    into.date = into.date.take().or(from.date);
+    into.pmid = into.pmid.take().or(from.pmid);
+    into.pmcid = into.pmcid.take().or(from.pmcid);
+    into.language = into.language.take().or(from.language);
+    into.cite_id = into.cite_id.take().or(from.cite_id);
+    into.cited_by = into.cited_by.max(from.cited_by);
+    into.cluster_id = into.cluster_id.take().or(from.cluster_id);
+    into.versions_url = into.versions_url.take().or(from.versions_url);
+    into.result_id = into.result_id.take().or(from.result_id);
+    into.has_export |= from.has_export;
+    into.raw_html = into.raw_html.take().or(from.raw_html);
+    into.base_url = into.base_url.take().or(from.base_url);
+    into.score = into.score.or(from.score);
+    for (name, value) in from.data_attrs {
+        into.data_attrs.entry(name).or_insert(value);
+    }
+}
+
+// The "Since" column of the citations row, with its first year, eg:
+// (2020, 567) for "Since 2020". None when the profile has no metrics.
+pub(crate) fn parse_recent_citations(document: &str) -> Result<Option<(u16, u32)>, Error> {
+    let fragment = Html::parse_document(document);
+    let header_selector =
+        Selector::parse("#gsc_rsb_st th.gsc_rsb_sth").map_err(|_| Error::ParseError)?;
+    let row_selector = Selector::parse("#gsc_rsb_st tbody tr").map_err(|_| Error::ParseError)?;
+    let value_selector = Selector::parse("td.gsc_rsb_std").map_err(|_| Error::ParseError)?;
+    // the label is localized, the year is its only number
+    let since = fragment.select(&header_selector).nth(1).and_then(|header| {
+        header
+            .text()
+            .collect::<String>()
+            .split(|c: char| !c.is_ascii_digit())
+            .find_map(|word| word.parse().ok())
+    });
+    let citations = fragment.select(&row_selector).next().and_then(|row| {
+        row.select(&value_selector)
+            .nth(1)
+            .and_then(|value| value.text().collect::<String>().trim().parse().ok())
+    });
+    Ok(since.zip(citations))
+}
+
+// Citations per year from the profile's chart, oldest first. Scholar draws
+// no bar for a year without citations; bars give their year by z-index,
+// counted from the last year, so such years are kept with 0.
+pub(crate) fn parse_citations_per_year(document: &str) -> Result<Vec<(u16, u32)>, Error> {
+    let fragment = Html::parse_document(document);
+    let year_selector =
+        Selector::parse(".gsc_md_hist_b .gsc_g_t").map_err(|_| Error::ParseError)?;
+    let bar_selector = Selector::parse(".gsc_md_hist_b .gsc_g_a").map_err(|_| Error::ParseError)?;
+    let count_selector = Selector::parse(".gsc_g_al").map_err(|_| Error::ParseError)?;
+
+    let mut series = fragment
+        .select(&year_selector)
+        .filter_map(|year| year.text().collect::<String>().trim().parse().ok())
+        .map(|year| (year, 0))
+        .collect::<Vec<(u16, u32)>>();
+    let years = series.len();
+    for (i, bar) in fragment.select(&bar_selector).enumerate() {
+        let z_index = bar.value().attr("style").and_then(|style| {
+            let (_, z_index) = style.split_once("z-index:")?;
+            z_index.split(';').next()?.trim().parse::<usize>().ok()
+        });
+        let position = match z_index {
+            Some(z_index) => years.checked_sub(z_index),
+            None => Some(i),
+        };
+        let count = bar
+            .select(&count_selector)
+            .next()
+            .and_then(|count| count.text().collect::<String>().trim().parse().ok());
+        if let (Some((_, total)), Some(count)) = (position.and_then(|p| series.get_mut(p)), count) {
+            *total = count;
+        }
+    }
+    Ok(series)
+}
+
+// the ids of the co-authors listed beside a profile
+pub(crate) fn parse_coauthors(document: &str) -> Result<Vec<AuthorId>, Error> {
+    let fragment = Html::parse_document(document);
+    let selector =
+        Selector::parse(".gsc_rsb_aa .gsc_rsb_a_desc a").map_err(|_| Error::ParseError)?;
+    Ok(fragment
+        .select(&selector)
+        .filter_map(|link| query_param(link.value().attr("href")?, "user"))
+        .map(AuthorId::from)
+        .collect())
+}
+
+pub(crate) fn parse_author_profile(document: &str) -> Result<AuthorProfile, Error> {
+    let fragment = Html::parse_document(document);
+    let name_selector = Selector::parse("#gsc_prf_in").map_err(|_| Error::ParseError)?;
+    let info_selector = Selector::parse(".gsc_prf_il").map_err(|_| Error::ParseError)?;
+    let email_selector = Selector::parse("#gsc_prf_ivh").map_err(|_| Error::ParseError)?;
+
+    let name = fragment
+        .select(&name_selector)
+        .next()
+        .ok_or(Error::ParseError)?
+        .text()
+        .collect::<String>();
+    let affiliation = fragment
+        .select(&info_selector)
+        .next()
+        .filter(|n| n.value().id() != Some("gsc_prf_ivh"))
+        .map(|n| n.text().collect::<String>().trim().to_string())
+        .filter(|affiliation| !affiliation.is_empty());
+    let verified_email_domain = fragment
+        .select(&email_selector)
+        .next()
+        .and_then(|n| email_domain(&n.text().collect::<String>()));
+
+    Ok(AuthorProfile {
+        user_id: AuthorId::default(),
+        name: name.trim().to_string(),
+        affiliation,
+        verified_email_domain,
+    })
+}
+
+// "Verified email at cs.stanford.edu - Homepage" -> "cs.stanford.edu"
+// The wording is localized, so the domain is the first word shaped like one.
+fn email_domain(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .find(|word| {
+            word.contains('.')
+                && word
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        })
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{profile_article, MockServer, ProfilePage};
+
+    const PROFILE: &str = r#"<html><body>
+<div id="gsc_prf_w">
+<div id="gsc_prf_in">Jane Doe</div>
+<div class="gsc_prf_il">Professor of Computer Science, <a href="/citations?view_op=view_org&amp;org=1" class="gsc_prf_ila">Example University</a></div>
+<div class="gsc_prf_il" id="gsc_prf_ivh">Verified email at cs.example.edu - <a href="https://example.edu/~jane" rel="nofollow" class="gsc_prf_ila">Homepage</a></div>
+<div class="gsc_prf_il" id="gsc_prf_int"><a href="/citations?view_op=search_authors&amp;mauthors=label:ml" class="gsc_prf_inta gs_ibl">Machine Learning</a></div>
+</div>
+</body></html>"#;
+
+    #[test]
+    fn profile_verified_email_domain() {
+        let profile = parse_author_profile(PROFILE).unwrap();
+
+        assert_eq!(profile.name, "Jane Doe");
+        assert_eq!(
+            profile.affiliation.as_deref(),
+            Some("Professor of Computer Science, Example University")
+        );
+        assert_eq!(
+            profile.verified_email_domain.as_deref(),
+            Some("cs.example.edu")
+        );
+    }
+
+    #[test]
+    fn profile_without_verified_email() {
+        let document = PROFILE.replace(
+            r#"Verified email at cs.example.edu - <a href="https://example.edu/~jane" rel="nofollow" class="gsc_prf_ila">Homepage</a>"#,
+            "No verified email",
+        );
+        let profile = parse_author_profile(&document).unwrap();
+
+        assert_eq!(profile.verified_email_domain, None);
+    }
+
+    #[tokio::test]
+    async fn fetch_author_profile_url() {
+        let server = MockServer::start(vec![(200, PROFILE.to_string())]).await;
+        let client = server.client();
+
+        let user_id = "JicYPdAAAAAJ".parse().unwrap();
+        let profile = client.fetch_author_profile(&user_id).await.unwrap();
+
+        assert_eq!(profile.user_id, user_id);
+        assert_eq!(profile.name, "Jane Doe");
+        assert!(server.requests()[0].starts_with("GET /citations?user=JicYPdAAAAAJ&hl=en "));
+    }
+
+    #[test]
+    fn merge_overlapping_sources() {
+        let article = |title: &str, year, cited_by| ScholarResult {
+            title: title.to_string(),
+            authors: vec!["J Doe".to_string(), "A Smith".to_string()],
+            venue: Some("Journal of Examples".to_string()),
+            year: Some(year),
+            cited_by: Some(cited_by),
+            ..Default::default()
+        };
+        let profile = vec![
+            ScholarResult {
+                cite_id: Some("1001".parse().unwrap()),
+                ..article("Deep nets", 2019, 40)
+            },
+            article("Shallow nets", 2020, 5),
+            article("Unsearched nets", 2021, 1),
+        ];
+        let search = vec![
+            ScholarResult {
+                title: "Other work".to_string(),
+                authors: vec!["B Other".to_string()],
+                cluster_id: Some("2009".parse().unwrap()),
+                ..Default::default()
+            },
+            // the same paper as the first profile article, by cited-by id
+            ScholarResult {
+                title: "Deep Nets".to_string(),
+                link: "https://example.org/deep".to_string(),
+                abs: "About deep nets".to_string(),
+                cite_id: Some("1001".parse().unwrap()),
+                cluster_id: Some("2001".parse().unwrap()),
+                ..article("Deep Nets", 2019, 38)
+            },
+            // by title, first author and year
+            ScholarResult {
+                venue: None,
+                link: "https://example.org/shallow".to_string(),
+                cluster_id: Some("2002".parse().unwrap()),
+                ..article("Shallow  nets", 2020, 7)
+            },
+            // again, now by cluster id
+            ScholarResult {
+                cluster_id: Some("2002".parse().unwrap()),
+                ..Default::default()
+            },
+        ];
+
+        let merged = merge_profile_and_search(profile, search);
+
+        let titles = merged.iter().map(|r| r.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            titles,
+            vec!["Deep nets", "Shallow nets", "Unsearched nets", "Other work"]
+        );
+        assert_eq!(merged[0].link, "https://example.org/deep");
+        assert_eq!(merged[0].abs, "About deep nets");
+        assert_eq!(merged[0].cluster_id.as_ref().unwrap().to_string(), "2001");
+        assert_eq!(merged[0].cited_by, Some(40));
+        assert_eq!(merged[1].link, "https://example.org/shallow");
+        assert_eq!(merged[1].venue.as_deref(), Some("Journal of Examples"));
+        assert_eq!(merged[1].cited_by, Some(7));
+        assert!(merged[2].link.is_empty());
+    }
+
+    #[tokio::test]
+    async fn merge_fetched_profile() {
+        let page = ProfilePage {
+            name: "Jane Doe".to_string(),
+            articles: vec![
+                profile_article("aaa", "Deep nets", 2019, Some(40)),
+                profile_article("bbb", "Shallow nets", 2020, None),
+            ],
+            ..Default::default()
+        };
+        let server = MockServer::start(vec![(200, page.html())]).await;
+        let client = server.client();
+        let (articles, _) = client
+            .fetch_author_articles(&"JicYPdAAAAAJ".parse().unwrap())
+            .await
+            .unwrap();
+        let search = vec![ScholarResult {
+            title: "Deep nets".to_string(),
+            authors: vec!["J Doe".to_string(), "A Smith".to_string()],
+            year: Some(2019),
+            link: "https://example.org/deep".to_string(),
+            cited_by: Some(38),
+            ..Default::default()
+        }];
+
+        let profile = articles.into_iter().map(ScholarResult::from).collect();
+        let merged = merge_profile_and_search(profile, search);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].authors, vec!["J Doe", "A Smith"]);
+        assert_eq!(
+            merged[0].venue.as_deref(),
+            Some("Journal of Examples 1 (2), 3-4")
+        );
+        assert_eq!(merged[0].link, "https://example.org/deep");
+        assert_eq!(merged[0].cited_by, Some(40));
+        assert_eq!(merged[1].title, "Shallow nets");
+        assert_eq!(merged[1].cited_by, None);
+    }
+
+    #[test]
+    fn email_domain_localized() {
+        assert_eq!(
+            email_domain("Bestätigte E-Mail-Adresse bei mpg.de - Startseite").as_deref(),
+            Some("mpg.de")
+        );
+        assert_eq!(
+            email_domain("Adresse e-mail validée de inria.fr").as_deref(),
+            Some("inria.fr")
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use std::panic::{self, AssertUnwindSafe};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    // name of the long-running call, eg: "scrape_until"
+    pub operation: &'static str,
+    // units of work done so far (pages, links, ...)
+    pub completed: u64,
+    // units of work expected in total, when known up front
+    pub total: Option<u64>,
+    pub message: Option<String>,
+}
+
+// Receives Progress updates from the *_with_progress calls. It runs inline
+// with the request loop, so it should return quickly. Closures taking a
+// &Progress implement it.
+pub trait ProgressHandler {
+    fn on_progress(&self, progress: &Progress);
+}
+
+impl<F> ProgressHandler for F
+where
+    F: Fn(&Progress),
+{
+    fn on_progress(&self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+// handler that ignores every update, used by the calls without progress
+pub(crate) fn no_progress(_: &Progress) {}
+
+// A panicking handler is logged and otherwise ignored, so it cannot abort
+// the operation it is watching.
+pub(crate) fn report<H: ProgressHandler + ?Sized>(handler: &H, progress: Progress) {
+    if panic::catch_unwind(AssertUnwindSafe(|| handler.on_progress(&progress))).is_err() {
+        log::warn!("progress handler panicked on {:?}", progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn report_survives_panicking_handler() {
+        let seen = Mutex::new(0);
+        let handler = |progress: &Progress| {
+            *seen.lock().unwrap() += 1;
+            if progress.completed == 1 {
+                panic!("handler bug");
+            }
+        };
+
+        for completed in 0..3 {
+            report(
+                &handler,
+                Progress {
+                    operation: "test",
+                    completed,
+                    total: Some(3),
+                    message: None,
+                },
+            );
+        }
+
+        assert_eq!(*seen.lock().unwrap(), 3);
+    }
+}
@@ -0,0 +1,107 @@
+use reqwest::StatusCode;
+
+use super::scholar::{Client, Error};
+
+impl Client {
+    // Requests scholar's home page once, through the configured proxy if
+    // any, to catch a misconfigured proxy before a long harvest. The request
+    // is not throttled nor counted against the daily quota, and waits at
+    // most the client's link_timeout.
+    pub async fn check_proxy(&self) -> Result<(), Error> {
+        let url = self.home_url()?;
+        let resp = self
+            .http()
+            .get(&url)
+            .timeout(self.link_timeout())
+            .send()
+            .await
+            .map_err(|e| {
+                let reason = if e.is_timeout() {
+                    "timed out"
+                } else if e.is_connect() {
+                    "could not connect"
+                } else {
+                    "request failed"
+                };
+                Error::ProxyError(format!("{url}: {reason}: {e}"))
+            })?;
+        match resp.status() {
+            status if status.is_success() => Ok(()),
+            StatusCode::PROXY_AUTHENTICATION_REQUIRED => Err(Error::ProxyError(
+                "the proxy requires authentication".to_string(),
+            )),
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::FORBIDDEN => Err(Error::Blocked),
+            status => Err(Error::ProxyError(format!("{url} answered {status}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::MockServer;
+    use std::net::TcpListener;
+
+    fn through(proxy: &str) -> Client {
+        Client::builder()
+            .base_url("http://scholar.example.org/scholar?")
+            .proxy(proxy)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn check_proxy_success() {
+        let proxy = MockServer::start(vec![(200, "<html></html>".to_string())]).await;
+
+        through(&proxy.url("")).check_proxy().await.unwrap();
+
+        let requests = proxy.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET http://scholar.example.org/ HTTP/1.1"));
+    }
+
+    #[tokio::test]
+    async fn check_proxy_failures() {
+        let proxy = MockServer::start(vec![(407, String::new())]).await;
+        match through(&proxy.url("")).check_proxy().await {
+            Err(Error::ProxyError(reason)) => assert!(reason.contains("authentication")),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // nothing listens on the port once the listener is dropped
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let client = through(&format!("http://127.0.0.1:{}", port));
+        match client.check_proxy().await {
+            Err(Error::ProxyError(reason)) => assert!(reason.contains("could not connect")),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert!(matches!(
+            Client::builder().proxy("not a proxy").build(),
+            Err(Error::ClientBuildError(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_proxy_hides_credentials() {
+        for proxy in [
+            "http://user:hunter2@[proxy:3128",
+            "user:hunter2@[proxy:3128/",
+        ]
+        .iter()
+        {
+            match Client::builder().proxy(proxy).build() {
+                Err(Error::ClientBuildError(reason)) => {
+                    assert!(!reason.contains("hunter2"), "{}", reason);
+                    assert!(reason.contains("***@[proxy:3128"), "{}", reason);
+                }
+                other => panic!("unexpected {:?}", other.map(|_| ())),
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::scholar::Error;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// Daily request budget persisted to a small state file so it holds across
+// process restarts. The file holds the UTC day number (days since the unix
+// epoch) and the requests used that day, eg: "20376 42". Clients and
+// processes given the same file share the budget: each request locks the
+// file and counts on from what it holds.
+pub struct Quota {
+    limit: u32,
+    path: PathBuf,
+    clock: Box<dyn Fn() -> SystemTime + Send + Sync>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct State {
+    day: u64,
+    used: u32,
+}
+
+impl Quota {
+    pub fn new(limit: u32, path: impl Into<PathBuf>) -> Quota {
+        Quota::with_clock(limit, path, SystemTime::now)
+    }
+
+    pub(crate) fn with_clock<F>(limit: u32, path: impl Into<PathBuf>, clock: F) -> Quota
+    where
+        F: Fn() -> SystemTime + Send + Sync + 'static,
+    {
+        let path = path.into();
+        let today = day_of(clock());
+        let state = match fs::read_to_string(&path) {
+            Ok(contents) => parse_state(&contents).unwrap_or_else(|| {
+                log::warn!(
+                    "resetting corrupt quota state in {}: {:?}",
+                    path.display(),
+                    contents
+                );
+                State {
+                    day: today,
+                    used: 0,
+                }
+            }),
+            Err(_) => State {
+                day: today,
+                used: 0,
+            },
+        };
+        Quota {
+            limit,
+            path,
+            clock: Box::new(clock),
+            state: Mutex::new(state),
+        }
+    }
+
+    // requests left for the current UTC day
+    pub fn remaining(&self) -> u32 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Ok(contents) = fs::read_to_string(&self.path) {
+            merge_saved(&mut state, &contents);
+        }
+        self.roll_over(&mut state);
+        self.limit.saturating_sub(state.used)
+    }
+
+    // Counts one request, or fails with Error::QuotaExceeded once the day's
+    // budget is used up.
+    pub(crate) fn acquire(&self) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        // held until the new count is written, so another process can't
+        // count from the same state in between
+        let mut file = self.open_locked();
+        if let Some(file) = &mut file {
+            let mut contents = String::new();
+            if file.read_to_string(&mut contents).is_ok() {
+                merge_saved(&mut state, &contents);
+            }
+        }
+        self.roll_over(&mut state);
+        if state.used >= self.limit {
+            return Err(Error::QuotaExceeded {
+                resets_at: UNIX_EPOCH + Duration::from_secs((state.day + 1) * SECONDS_PER_DAY),
+            });
+        }
+        state.used += 1;
+        let saved = match file {
+            Some(mut file) => save(&mut file, *state),
+            None => fs::write(&self.path, format!("{} {}\n", state.day, state.used)),
+        };
+        if let Err(e) = saved {
+            log::warn!(
+                "could not save quota state to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+        Ok(())
+    }
+
+    // the state file, created if missing and locked for this process
+    fn open_locked(&self) -> Option<File> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)
+            .and_then(|file| file.lock().map(|_| file));
+        match file {
+            Ok(file) => Some(file),
+            Err(e) => {
+                log::warn!(
+                    "could not lock quota state in {}: {}",
+                    self.path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    fn roll_over(&self, state: &mut State) {
+        let today = day_of((self.clock)());
+        if state.day != today {
+            *state = State {
+                day: today,
+                used: 0,
+            };
+        }
+    }
+}
+
+// takes the requests another client saved, eg: in another process
+fn merge_saved(state: &mut State, contents: &str) {
+    match parse_state(contents) {
+        Some(saved) if saved.day > state.day => *state = saved,
+        Some(saved) if saved.day == state.day => state.used = state.used.max(saved.used),
+        _ => {}
+    }
+}
+
+fn save(file: &mut File, state: State) -> std::io::Result<()> {
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    writeln!(file, "{} {}", state.day, state.used)
+}
+
+fn day_of(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or(0)
+}
+
+fn parse_state(contents: &str) -> Option<State> {
+    let mut fields = contents.split_whitespace();
+    let state = State {
+        day: fields.next()?.parse().ok()?,
+        used: fields.next()?.parse().ok()?,
+    };
+    match fields.next() {
+        Some(_) => None,
+        None => Some(state),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    fn state_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("gscholar-quota-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn mock_clock(now: &Arc<AtomicU64>) -> impl Fn() -> SystemTime + Send + Sync + 'static {
+        let now = now.clone();
+        move || UNIX_EPOCH + Duration::from_secs(now.load(Ordering::SeqCst))
+    }
+
+    // 2025-10-15 23:00:00 UTC
+    const LATE_EVENING: u64 = 20376 * SECONDS_PER_DAY + 23 * 3600;
+
+    #[test]
+    fn quota_exhaustion_and_midnight_reset() {
+        let path = state_path("exhaustion");
+        let now = Arc::new(AtomicU64::new(LATE_EVENING));
+        let quota = Quota::with_clock(2, &path, mock_clock(&now));
+
+        assert!(quota.acquire().is_ok());
+        assert!(quota.acquire().is_ok());
+        assert_eq!(quota.remaining(), 0);
+        match quota.acquire() {
+            Err(Error::QuotaExceeded { resets_at }) => assert_eq!(
+                resets_at,
+                UNIX_EPOCH + Duration::from_secs(20377 * SECONDS_PER_DAY)
+            ),
+            _ => panic!("expected Error::QuotaExceeded"),
+        }
+
+        now.store(20377 * SECONDS_PER_DAY + 1, Ordering::SeqCst);
+        assert_eq!(quota.remaining(), 2);
+        assert!(quota.acquire().is_ok());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "20377 1\n");
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn quota_persists_across_restarts() {
+        let path = state_path("restart");
+        let now = Arc::new(AtomicU64::new(LATE_EVENING));
+
+        let quota = Quota::with_clock(3, &path, mock_clock(&now));
+        assert!(quota.acquire().is_ok());
+        assert!(quota.acquire().is_ok());
+        drop(quota);
+
+        let quota = Quota::with_clock(3, &path, mock_clock(&now));
+        assert_eq!(quota.remaining(), 1);
+        assert!(quota.acquire().is_ok());
+        assert!(quota.acquire().is_err());
+
+        // the saved count is from yesterday once the day changes
+        now.store(LATE_EVENING + 3600, Ordering::SeqCst);
+        let quota = Quota::with_clock(3, &path, mock_clock(&now));
+        assert_eq!(quota.remaining(), 3);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn quota_shared_through_state_file() {
+        let path = state_path("shared");
+        let now = Arc::new(AtomicU64::new(LATE_EVENING));
+        // eg: two processes started the same morning
+        let first = Quota::with_clock(3, &path, mock_clock(&now));
+        let second = Quota::with_clock(3, &path, mock_clock(&now));
+
+        assert!(first.acquire().is_ok());
+        assert!(second.acquire().is_ok());
+        assert_eq!(first.remaining(), 1);
+        assert!(first.acquire().is_ok());
+        assert!(second.acquire().is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "20376 3\n");
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn corrupt_state_resets() {
+        let path = state_path("corrupt");
+        fs::write(&path, "not a quota").unwrap();
+        let now = Arc::new(AtomicU64::new(LATE_EVENING));
+
+        let quota = Quota::with_clock(5, &path, mock_clock(&now));
+        assert_eq!(quota.remaining(), 5);
+        assert!(quota.acquire().is_ok());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "20376 1\n");
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::HashSet;
+
+use scraper::{Html, Selector};
+
+use super::facets::{parse_facets, SearchFacets};
+use super::paging::{DEFAULT_PAGE_SIZE, RESULTS_CEILING};
+#[cfg(feature = "lang-detect")]
+use super::scholar::AutoLanguage;
+use super::scholar::{parse_total_results, query_param, Client, Error, ScholarArgs, ScholarResult};
+use crate::normalize::TextNormalizer;
+
+// A results page: the results plus what scholar says about the search
+#[derive(Debug, Clone, Default)]
+pub struct ScholarResponse {
+    pub results: Vec<ScholarResult>,
+    // q as sent
+    pub query: String,
+    // q as scholar shows it in the search box of the page, the query it
+    // actually ran. None when the page has no search box
+    pub effective_query: Option<String>,
+    // "About N results"
+    pub total_results: Option<u64>,
+    // filters scholar shows as applied, see SearchFacets::matches
+    pub facets: SearchFacets,
+    // "Related searches" at the bottom of the page, empty when absent
+    pub related_queries: Vec<String>,
+    // the page says the query matched nothing, see is_genuinely_empty
+    pub no_results_notice: bool,
+    pub pagination: Pagination,
+    // hl/lr the client added from the language of the query, None when it
+    // added none, see Client::language_choice
+    #[cfg(feature = "lang-detect")]
+    pub language: Option<AutoLanguage>,
+}
+
+// Where a results page sits among the pages of its search, for page
+// numbers in UIs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pagination {
+    // start - results before this page
+    pub current_offset: u32,
+    // num - results per page
+    pub page_size: u32,
+    // "About N results", None when the page doesn't say
+    pub total_results: Option<u64>,
+    // pages scholar serves for the total: it is an estimate and scholar
+    // stops at RESULTS_CEILING results. None when the total is unknown
+    pub estimated_pages: Option<u32>,
+}
+
+impl Pagination {
+    pub fn new(current_offset: u32, page_size: u32, total_results: Option<u64>) -> Pagination {
+        let page_size = page_size.max(1);
+        let estimated_pages = total_results.map(|total| {
+            let reachable = total.min(u64::from(RESULTS_CEILING));
+            reachable.div_ceil(u64::from(page_size)) as u32
+        });
+        Pagination {
+            current_offset,
+            page_size,
+            total_results,
+            estimated_pages,
+        }
+    }
+
+    // 1 for the first page
+    pub fn current_page(&self) -> u32 {
+        self.current_offset / self.page_size.max(1) + 1
+    }
+}
+
+impl ScholarResponse {
+    // Whether scholar ran a different query than the one sent, eg: with an
+    // operator dropped. Whitespace, case and typographic quotes are ignored.
+    // False when the effective query is unknown.
+    pub fn query_modified(&self) -> bool {
+        match &self.effective_query {
+            Some(effective) => comparable_query(effective) != comparable_query(&self.query),
+            None => false,
+        }
+    }
+
+    // No results because scholar says the query matched nothing, as opposed
+    // to results scholar sent but the parser missed, eg: after a markup
+    // change. Scholar's notice is read in english only, so pages in other
+    // languages (hl) are never genuinely empty.
+    pub fn is_genuinely_empty(&self) -> bool {
+        self.results.is_empty() && self.no_results_notice
+    }
+}
+
+impl Client {
+    // scrape_scholar, keeping the page details alongside the results
+    pub async fn scrape_response(&self, args: &ScholarArgs) -> Result<ScholarResponse, Error> {
+        let url = self.get_url(args)?;
+        let doc = self.get_document(&url).await?;
+        let effective_query = parse_effective_query(&doc);
+        let total_results = parse_total_results(&doc);
+        let facets = parse_facets(&doc);
+        let related_queries = parse_related_queries(&doc);
+        let no_results_notice = parse_no_results_notice(&doc);
+        let pagination = Pagination::new(
+            args.offset.unwrap_or(0),
+            args.limit.unwrap_or(DEFAULT_PAGE_SIZE),
+            total_results,
+        );
+
+        Ok(ScholarResponse {
+            results: self.parse_page(&url, doc).await?,
+            query: args.query.clone(),
+            effective_query,
+            total_results,
+            facets,
+            related_queries,
+            no_results_notice,
+            pagination,
+            #[cfg(feature = "lang-detect")]
+            language: self.language_choice(args),
+        })
+    }
+
+    // The related searches of `seed` and, one level deeper, those of each
+    // related search, in the order found and without duplicates or the
+    // seed itself. Costs one request per related search on top of the seed.
+    pub async fn expand_query(&self, seed: &ScholarArgs) -> Result<Vec<String>, Error> {
+        let mut seen = HashSet::new();
+        seen.insert(comparable_query(&seed.query));
+        let mut expanded = Vec::new();
+
+        let related = self.scrape_response(seed).await?.related_queries;
+        let mut args = seed.clone();
+        let mut deeper = Vec::new();
+        for query in &related {
+            args.query = query.clone();
+            deeper.extend(self.scrape_response(&args).await?.related_queries);
+        }
+        for query in related.into_iter().chain(deeper) {
+            if seen.insert(comparable_query(&query)) {
+                expanded.push(query);
+            }
+        }
+        Ok(expanded)
+    }
+}
+
+// value of the header search box
+pub(crate) fn parse_effective_query(document: &str) -> Option<String> {
+    let fragment = Html::parse_document(document);
+    let selector = Selector::parse("#gs_hdr_tsi, input[name=\"q\"]").ok()?;
+    fragment
+        .select(&selector)
+        .next()?
+        .value()
+        .attr("value")
+        .map(str::to_string)
+}
+
+// Queries of the related searches block, read from the q of its links so
+// the highlighting markup and localized heading don't matter
+pub(crate) fn parse_related_queries(document: &str) -> Vec<String> {
+    let fragment = Html::parse_document(document);
+    let selector = match Selector::parse("#gs_qsuggest a[href]") {
+        Ok(selector) => selector,
+        Err(_) => return Vec::new(),
+    };
+    let mut seen = HashSet::new();
+    fragment
+        .select(&selector)
+        .filter_map(|a| query_param(a.value().attr("href")?, "q"))
+        .map(|query| query.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|query| !query.is_empty() && seen.insert(comparable_query(query)))
+        .collect()
+}
+
+// "Your search - <b>...</b> - did not match any articles." in the results
+// column
+pub(crate) fn parse_no_results_notice(document: &str) -> bool {
+    let text = Html::parse_document(document)
+        .root_element()
+        .text()
+        .collect::<String>();
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .contains("did not match any articles")
+}
+
+fn comparable_query(query: &str) -> String {
+    let normalizer = TextNormalizer {
+        fold_diacritics: false,
+        ..TextNormalizer::default()
+    };
+    normalizer
+        .normalize(query)
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{results_page, with_search_box, MockServer};
+
+    fn response(query: &str, effective: Option<&str>) -> ScholarResponse {
+        ScholarResponse {
+            query: query.to_string(),
+            effective_query: effective.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn query_modified_ignores_formatting() {
+        let same = [
+            ("deep learning", "deep learning"),
+            ("  Deep   Learning ", "deep learning"),
+            ("“deep learning”", "\"deep learning\""),
+        ];
+        for (query, effective) in same.iter() {
+            assert!(!response(query, Some(effective)).query_modified());
+        }
+        assert!(response("deep learning author:lecun", Some("deep learning")).query_modified());
+        assert!(!response("deep learning", None).query_modified());
+    }
+
+    #[tokio::test]
+    async fn scrape_response_detects_rewritten_query() {
+        let page = with_search_box(
+            results_page(Some("About 1,230 results"), 2),
+            "\"deep learning\" lecun",
+        );
+        let server = MockServer::start(vec![(200, page)]).await;
+        let client = server.client();
+
+        let response = client
+            .scrape_response(&ScholarArgs::new("\"deep learning\" author:lecun"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.results.len(), 2);
+        assert_eq!(response.total_results, Some(1230));
+        assert_eq!(
+            response.effective_query.as_deref(),
+            Some("\"deep learning\" lecun")
+        );
+        assert!(response.query_modified());
+    }
+
+    #[tokio::test]
+    async fn pagination_from_total() {
+        let server = MockServer::start(vec![(
+            200,
+            results_page(Some("Page 3 of about 1,230 results (0.03 sec)"), 2),
+        )])
+        .await;
+        let client = server.client();
+        let mut args = ScholarArgs::new("abcd");
+        args.offset = Some(40);
+        args.limit = Some(20);
+
+        let response = client.scrape_response(&args).await.unwrap();
+
+        assert_eq!(
+            response.pagination,
+            Pagination {
+                current_offset: 40,
+                page_size: 20,
+                total_results: Some(1230),
+                // capped at the 1000 results scholar serves
+                estimated_pages: Some(50),
+            }
+        );
+        assert_eq!(response.pagination.current_page(), 3);
+    }
+
+    #[test]
+    fn pagination_estimates() {
+        assert_eq!(Pagination::new(0, 10, Some(95)).estimated_pages, Some(10));
+        assert_eq!(Pagination::new(0, 10, Some(100)).estimated_pages, Some(10));
+        assert_eq!(Pagination::new(0, 10, Some(0)).estimated_pages, Some(0));
+        assert_eq!(Pagination::new(10, 10, None).estimated_pages, None);
+        assert_eq!(Pagination::new(10, 10, None).current_page(), 2);
+    }
+
+    // scholar's page for a query without results
+    const NO_RESULTS_PAGE: &str = r#"<html><body><div id="gs_res_ccl_mid"><div class="gs_r"><div class="gs_med"><p>Your search - <b>xqzvw plorf</b> - did not match any articles.</p><p>Suggestions:</p><ul><li>Make sure all words are spelled correctly.</li><li>Try different keywords.</li></ul></div></div></div></body></html>"#;
+
+    // results whose markup the parser no longer knows
+    const CHANGED_MARKUP_PAGE: &str = r#"<html><body><div id="gs_res_ccl_mid"><article class="gs_result_v2"><h3><a href="https://example.org/paper0">Paper 0</a></h3><p>A Author - Journal of Examples, 2019</p></article></div></body></html>"#;
+
+    async fn response_of(page: &str) -> ScholarResponse {
+        let server = MockServer::start(vec![(200, page.to_string())]).await;
+        let client = server.client();
+        client
+            .scrape_response(&ScholarArgs::new("xqzvw plorf"))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn genuinely_empty_or_not_understood() {
+        let empty = response_of(NO_RESULTS_PAGE).await;
+        assert!(empty.results.is_empty());
+        assert!(empty.is_genuinely_empty());
+
+        let changed = response_of(CHANGED_MARKUP_PAGE).await;
+        assert!(changed.results.is_empty());
+        assert!(!changed.is_genuinely_empty());
+
+        let found = response_of(&results_page(None, 1)).await;
+        assert!(!found.is_genuinely_empty());
+    }
+
+    #[cfg(feature = "lang-detect")]
+    #[tokio::test]
+    async fn language_choice_is_reported() {
+        let server = MockServer::start(vec![(200, results_page(None, 1)); 2]).await;
+        let client = Client::builder()
+            .base_url(&server.base_url())
+            .auto_language(true)
+            .build()
+            .unwrap();
+
+        let german = client
+            .scrape_response(&ScholarArgs::new(
+                "Auswirkungen der Digitalisierung auf die Arbeit",
+            ))
+            .await
+            .unwrap();
+        let english = client
+            .scrape_response(&ScholarArgs::new("the effects of digitalization on work"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            german.language,
+            Some(AutoLanguage {
+                detected: "de",
+                lang: Some("de"),
+                lang_limit: None,
+            })
+        );
+        assert!(server.requests()[0].contains("&hl=de "));
+        assert_eq!(english.language, None);
+    }
+
+    fn with_related(page: String, queries: &[&str]) -> String {
+        let links = queries
+            .iter()
+            .map(|q| {
+                format!(
+                    "<li><a href=\"/scholar?q={}&amp;hl=en&amp;as_sdt=0,5&amp;qsp=1\">{}</a></li>",
+                    q.replace(' ', "+"),
+                    q.replace("learning", "<b>learning</b>")
+                )
+            })
+            .collect::<String>();
+        page.replacen(
+            "</body>",
+            &format!(
+                "<div id=\"gs_qsuggest\"><h3 class=\"gs_qsuggest_h\">Verwandte Suchanfragen</h3><ul>{}</ul></div></body>",
+                links
+            ),
+            1,
+        )
+    }
+
+    #[test]
+    fn parse_related_searches() {
+        let page = with_related(
+            results_page(None, 1),
+            &[
+                "deep learning review",
+                "Deep  Learning review",
+                "machine learning",
+            ],
+        );
+
+        assert_eq!(
+            parse_related_queries(&page),
+            vec!["deep learning review", "machine learning"]
+        );
+        assert!(parse_related_queries(&results_page(None, 1)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn expand_query_one_level_deep() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                with_related(results_page(None, 1), &["cnn", "deep learning review"]),
+            ),
+            (
+                200,
+                with_related(results_page(None, 1), &["rnn", "deep learning"]),
+            ),
+            (200, results_page(None, 1)),
+        ])
+        .await;
+        let client = server.client();
+
+        let expanded = client
+            .expand_query(&ScholarArgs::new("deep learning"))
+            .await
+            .unwrap();
+
+        assert_eq!(expanded, vec!["cnn", "deep learning review", "rnn"]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].contains("q=cnn"));
+        assert!(requests[2].contains("q=deep%20learning%20review"));
+    }
+}
@@ -0,0 +1,197 @@
+// Waits between retries of a failed request, see ClientBuilder::retries.
+// Requests are retried after connection errors, 429 and 5xx statuses, not
+// after a 403 or a captcha page: those don't go away by waiting a little.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackoffStrategy {
+    // the same wait before every retry
+    Fixed(Duration),
+    // base before the first retry, then doubling: base, 2 * base,
+    // 4 * base, ... never more than max
+    Exponential { base: Duration, max: Duration },
+    // a random wait between base and 3 times the previous wait (base before
+    // the first retry), never more than max. Spreads the retries of clients
+    // that failed together, see "Exponential Backoff And Jitter" on the AWS
+    // architecture blog
+    DecorrelatedJitter { base: Duration, max: Duration },
+}
+
+impl Default for BackoffStrategy {
+    fn default() -> Self {
+        BackoffStrategy::Exponential {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+        }
+    }
+}
+
+impl BackoffStrategy {
+    // the waits before the first, second, ... retry, endless
+    pub fn delays(&self) -> Backoff {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_nanos() as u64)
+            .unwrap_or_default();
+        self.delays_seeded(seed)
+    }
+
+    pub(crate) fn delays_seeded(&self, seed: u64) -> Backoff {
+        Backoff {
+            strategy: *self,
+            retry: 0,
+            previous: Duration::ZERO,
+            // xorshift gets stuck on 0
+            state: seed | 1,
+        }
+    }
+}
+
+pub struct Backoff {
+    strategy: BackoffStrategy,
+    retry: u32,
+    previous: Duration,
+    state: u64,
+}
+
+impl Backoff {
+    // xorshift64*, plenty for jitter
+    fn random(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+impl Iterator for Backoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let delay = match self.strategy {
+            BackoffStrategy::Fixed(delay) => delay,
+            BackoffStrategy::Exponential { base, max } => base
+                .checked_mul(2u32.saturating_pow(self.retry))
+                .map_or(max, |delay| delay.min(max)),
+            BackoffStrategy::DecorrelatedJitter { base, max } => {
+                let low = base.as_nanos() as u64;
+                let high = (self.previous.as_nanos() as u64).saturating_mul(3).max(low);
+                let spread = high - low;
+                let delay = match spread.checked_add(1) {
+                    Some(range) => low + self.random() % range,
+                    None => low + self.random(),
+                };
+                Duration::from_nanos(delay).min(max)
+            }
+        };
+        self.retry = self.retry.saturating_add(1);
+        self.previous = delay;
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{results_page, MockServer};
+    use crate::scholar::{Client, Error, ScholarArgs};
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn fixed_delays() {
+        let delays = BackoffStrategy::Fixed(ms(500)).delays().take(3);
+
+        assert_eq!(delays.collect::<Vec<_>>(), vec![ms(500); 3]);
+    }
+
+    #[test]
+    fn exponential_delays() {
+        let strategy = BackoffStrategy::Exponential {
+            base: ms(100),
+            max: ms(1000),
+        };
+
+        let delays = strategy.delays().take(6).collect::<Vec<_>>();
+
+        assert_eq!(
+            delays,
+            vec![ms(100), ms(200), ms(400), ms(800), ms(1000), ms(1000)]
+        );
+        // far retries don't overflow
+        assert_eq!(strategy.delays().nth(100), Some(ms(1000)));
+    }
+
+    #[test]
+    fn decorrelated_jitter_delays() {
+        let strategy = BackoffStrategy::DecorrelatedJitter {
+            base: ms(100),
+            max: ms(2000),
+        };
+
+        let delays = strategy.delays_seeded(42).take(50).collect::<Vec<_>>();
+
+        let mut previous = ms(100);
+        for delay in &delays {
+            assert!(*delay >= ms(100), "{:?}", delay);
+            assert!(*delay <= (previous * 3).min(ms(2000)), "{:?}", delay);
+            previous = *delay;
+        }
+        assert_eq!(delays[0], ms(100));
+        // it does spread
+        assert!(delays.iter().any(|delay| *delay > ms(300)));
+        assert_eq!(
+            strategy.delays_seeded(42).take(50).collect::<Vec<_>>(),
+            delays
+        );
+        assert_ne!(
+            strategy.delays_seeded(7).take(50).collect::<Vec<_>>(),
+            delays
+        );
+    }
+
+    async fn scrape_with_retries(
+        statuses: &[u16],
+        retries: u32,
+    ) -> (Result<Vec<crate::scholar::ScholarResult>, Error>, usize) {
+        let responses = statuses
+            .iter()
+            .map(|status| (*status, results_page(None, 1)))
+            .collect();
+        let server = MockServer::start(responses).await;
+        let client = Client::builder()
+            .base_url(&server.base_url())
+            .retries(retries)
+            .backoff(BackoffStrategy::Fixed(ms(1)))
+            .build()
+            .unwrap();
+
+        let results = client.scrape_scholar(&ScholarArgs::new("abcd")).await;
+        (results, server.requests().len())
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures() {
+        let (results, requests) = scrape_with_retries(&[503, 429, 200], 2).await;
+        assert_eq!(results.unwrap().len(), 1);
+        assert_eq!(requests, 3);
+
+        let (results, requests) = scrape_with_retries(&[503, 503, 200], 1).await;
+        assert!(matches!(results, Err(Error::InvalidResponseError)));
+        assert_eq!(requests, 2);
+    }
+
+    #[tokio::test]
+    async fn no_retry_when_blocked_or_off() {
+        let (results, requests) = scrape_with_retries(&[403, 200], 3).await;
+        assert!(matches!(results, Err(Error::Blocked)));
+        assert_eq!(requests, 1);
+
+        let (results, requests) = scrape_with_retries(&[503, 200], 0).await;
+        assert!(matches!(results, Err(Error::InvalidResponseError)));
+        assert_eq!(requests, 1);
+    }
+}
@@ -0,0 +1,132 @@
+// CSS selectors a results page is read with. The primary set matches
+// scholar's current markup; the fallback sets are tried in order when it
+// finds no result on a page, to get through small markup changes until the
+// primary set is updated.
+
+use scraper::Selector;
+
+// Selectors of each part of a result. `article` selects the result blocks,
+// the others are looked up within a block, except `source_link` which is
+// looked up in the .gs_r around it when there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorSet {
+    // names the set in logs
+    pub name: String,
+    pub article: String,
+    pub title: String,
+    // the title's link is the first match
+    pub link: String,
+    pub abstract_: String,
+    // within the abstract, eg: "3 days ago"
+    pub age: String,
+    pub author: String,
+    pub source_link: String,
+    pub footer_link: String,
+    pub cite_button: String,
+}
+
+// the primary set
+impl Default for SelectorSet {
+    fn default() -> Self {
+        SelectorSet {
+            name: "primary".to_string(),
+            article: ".gs_ri".to_string(),
+            title: ".gs_rt".to_string(),
+            link: "a".to_string(),
+            abstract_: ".gs_rs".to_string(),
+            age: ".gs_age".to_string(),
+            author: ".gs_a".to_string(),
+            source_link: ".gs_ggs .gs_or_ggsm a".to_string(),
+            footer_link: ".gs_fl a".to_string(),
+            cite_button: ".gs_fl .gs_or_cit".to_string(),
+        }
+    }
+}
+
+impl SelectorSet {
+    // The fallback sets tried by default, in order:
+    // - "legacy": result blocks without the .gs_ri wrapper, as on older
+    //   pages, the other classes unchanged
+    // - "attribute": blocks by their data-cid and parts by the ending of
+    //   their class, eg: "gs_a" renamed to "gs_r2_a", and the title as the
+    //   block's h3
+    pub fn fallbacks() -> Vec<SelectorSet> {
+        vec![
+            SelectorSet {
+                name: "legacy".to_string(),
+                article: ".gs_r".to_string(),
+                link: ".gs_rt a".to_string(),
+                ..Default::default()
+            },
+            SelectorSet {
+                name: "attribute".to_string(),
+                article: "[data-cid]".to_string(),
+                title: "h3".to_string(),
+                link: "h3 a".to_string(),
+                abstract_: "[class$=\"_rs\"]".to_string(),
+                age: "[class$=\"_age\"]".to_string(),
+                author: "[class$=\"_a\"]".to_string(),
+                source_link: "[class*=\"_ggs\"] a".to_string(),
+                footer_link: "[class*=\"_fl\"] a".to_string(),
+                cite_button: "[class*=\"_cit\"]".to_string(),
+            },
+        ]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ResultSelectors {
+    pub(crate) name: String,
+    pub(crate) article: Selector,
+    pub(crate) title: Selector,
+    pub(crate) link: Selector,
+    pub(crate) abstract_: Selector,
+    pub(crate) age: Selector,
+    pub(crate) author: Selector,
+    pub(crate) source_link: Selector,
+    pub(crate) footer_link: Selector,
+    pub(crate) cite_button: Selector,
+}
+
+impl ResultSelectors {
+    // Err names the selector that doesn't parse
+    pub(crate) fn compile(set: &SelectorSet) -> Result<Self, String> {
+        let parse = |selector: &str| {
+            Selector::parse(selector)
+                .map_err(|_| format!("invalid selector {:?} in set {}", selector, set.name))
+        };
+        Ok(ResultSelectors {
+            name: set.name.clone(),
+            article: parse(&set.article)?,
+            title: parse(&set.title)?,
+            link: parse(&set.link)?,
+            abstract_: parse(&set.abstract_)?,
+            age: parse(&set.age)?,
+            author: parse(&set.author)?,
+            source_link: parse(&set.source_link)?,
+            footer_link: parse(&set.footer_link)?,
+            cite_button: parse(&set.cite_button)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_sets_parse() {
+        for set in std::iter::once(SelectorSet::default()).chain(SelectorSet::fallbacks()) {
+            assert!(ResultSelectors::compile(&set).is_ok(), "{}", set.name);
+        }
+        let broken = SelectorSet {
+            name: "broken".to_string(),
+            title: "h3[".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            ResultSelectors::compile(&broken).err().as_deref(),
+            Some("invalid selector \"h3[\" in set broken")
+        );
+    }
+}
@@ -0,0 +1,144 @@
+// Spans around fetching, parsing and paging, with the `tracing` feature.
+// Without it span! and the helpers below compile to nothing. The log
+// records are emitted either way: spans add structure (url, status, result
+// counts) around them, they don't replace them.
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{Instrument, Span};
+
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($($args:tt)*) => {
+        tracing::debug_span!($($args)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($($args:tt)*) => {
+        $crate::scholar::trace::Span
+    };
+}
+
+pub(crate) use span;
+
+// `f` made to run under the current subscriber and span wherever it is
+// called, eg: on a blocking pool thread
+#[cfg(feature = "tracing")]
+pub(crate) fn in_current_scope<R>(f: impl FnOnce() -> R) -> impl FnOnce() -> R {
+    let dispatch = tracing::dispatcher::get_default(|dispatch| dispatch.clone());
+    let span = Span::current();
+    move || tracing::dispatcher::with_default(&dispatch, || span.in_scope(f))
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn in_current_scope<R>(f: impl FnOnce() -> R) -> impl FnOnce() -> R {
+    f
+}
+
+#[cfg(not(feature = "tracing"))]
+#[derive(Clone)]
+pub(crate) struct Span;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    pub(crate) fn record<V>(&self, _field: &str, _value: V) -> &Self {
+        self
+    }
+
+    pub(crate) fn entered(self) -> Span {
+        self
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) trait Instrument: Sized {
+    fn instrument(self, _span: Span) -> Self {
+        self
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl<T> Instrument for T {}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::scholar::mock::{results_page, MockServer};
+    use crate::scholar::ScholarArgs;
+
+    // span name -> fields recorded on it, in the order spans were created
+    type Spans = Arc<Mutex<Vec<(&'static str, HashMap<String, String>)>>>;
+
+    struct Recorder {
+        spans: Spans,
+    }
+
+    struct Fields<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        // only this crate's spans, not those of hyper
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.target().starts_with("gscholar")
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = HashMap::new();
+            span.record(&mut Fields(&mut fields));
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((span.metadata().name(), fields));
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            let (_, fields) = &mut spans[span.into_u64() as usize - 1];
+            values.record(&mut Fields(fields));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn spans_around_fetch_and_parse() {
+        let server = MockServer::start(vec![(200, results_page(None, 3))]).await;
+        let client = server.client();
+        let spans = Spans::default();
+        let _guard = tracing::subscriber::set_default(Recorder {
+            spans: spans.clone(),
+        });
+
+        client
+            .scrape_scholar(&ScholarArgs::new("abcd"))
+            .await
+            .unwrap();
+
+        let spans = spans.lock().unwrap();
+        let names = spans.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        assert_eq!(names, vec!["get_document", "scrape_serialize"]);
+        let fetch = &spans[0].1;
+        assert_eq!(fetch["url"], server.url("/scholar?q=abcd"));
+        assert_eq!(fetch["status"], "200");
+        assert_eq!(spans[1].1["results"], "3");
+    }
+}
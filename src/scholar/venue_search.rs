@@ -0,0 +1,182 @@
+use futures_util::{pin_mut, StreamExt};
+
+use super::harvest::Merged;
+use super::paging::RESULTS_CEILING;
+use super::scholar::{Client, Error, ScholarArgs, ScholarResult};
+
+// How search_venue searches a venue
+#[derive(Debug, Clone)]
+pub struct VenueSearchOptions {
+    // most results read per form of the venue. Default = RESULTS_CEILING,
+    // past which scholar serves no results anyway
+    pub max_results: usize,
+    // also search the venue's other form, its ISO 4 abbreviation or the
+    // title it abbreviates, see venue::expand. Needs the venue-normalize
+    // feature, without it only the venue as given is searched.
+    // Default = true
+    pub both_forms: bool,
+}
+
+impl Default for VenueSearchOptions {
+    fn default() -> Self {
+        VenueSearchOptions {
+            max_results: RESULTS_CEILING as usize,
+            both_forms: true,
+        }
+    }
+}
+
+impl Client {
+    // What `venue` published from `from` to `to` (publication years,
+    // inclusive), matching `keywords` when given, most cited first. Each
+    // form of the venue is searched with the source: operator (see
+    // ScholarArgs::in_source) and paged through until its results run out
+    // or options.max_results; results found under both forms are kept once,
+    // by cluster id. Results without a "Cited by" count come last, ties keep
+    // scholar's order.
+    pub async fn search_venue(
+        &self,
+        venue: &str,
+        keywords: Option<&str>,
+        from: u16,
+        to: u16,
+        options: VenueSearchOptions,
+    ) -> Result<Vec<ScholarResult>, Error> {
+        let mut forms = vec![venue.trim().to_string()];
+        if options.both_forms {
+            forms.extend(other_form(venue));
+        }
+
+        let mut merged = Merged::default();
+        for form in &forms {
+            let mut args = ScholarArgs::in_source(form, keywords.unwrap_or(""));
+            args.from_year = Some(from);
+            args.to_year = Some(to);
+            let results = self
+                .result_stream_until(&args, |_| false)
+                .take(options.max_results);
+            pin_mut!(results);
+            while let Some(result) = results.next().await {
+                merged.extend(std::iter::once(result?));
+            }
+        }
+        let mut results = merged.results;
+        results.sort_by_key(|result| std::cmp::Reverse(result.cited_by));
+        Ok(results)
+    }
+}
+
+#[cfg(feature = "venue-normalize")]
+fn other_form(venue: &str) -> Option<String> {
+    crate::venue::expand(venue).or_else(|| crate::venue::abbreviate(venue))
+}
+
+#[cfg(not(feature = "venue-normalize"))]
+fn other_form(_venue: &str) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{result_block, results_page_with, MockServer};
+
+    #[tokio::test]
+    async fn composes_the_search() {
+        let server = MockServer::start(vec![(
+            200,
+            results_page_with(
+                None,
+                vec![result_block(0), result_block(2), result_block(1)],
+            ),
+        )])
+        .await;
+
+        let results = server
+            .client()
+            .search_venue(
+                "Science & Technology Studies",
+                Some("peer review"),
+                2019,
+                2023,
+                VenueSearchOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(
+            requests[0].starts_with(
+                "GET /scholar?q=source:%22Science%20%26%20Technology%20Studies%22%20peer%20review&as_ylo=2019&as_yhi=2023&start=0 "
+            ),
+            "{}",
+            requests[0]
+        );
+        let cited_by = results.iter().map(|r| r.cited_by).collect::<Vec<_>>();
+        assert_eq!(cited_by, vec![Some(12), Some(11), Some(10)]);
+    }
+
+    #[tokio::test]
+    async fn stops_at_max_results() {
+        let page =
+            |first: usize| results_page_with(None, (first..first + 10).map(result_block).collect());
+        let server = MockServer::start(vec![(200, page(0)), (200, page(10))]).await;
+
+        let results = server
+            .client()
+            .search_venue(
+                "Nature",
+                None,
+                2020,
+                2020,
+                VenueSearchOptions {
+                    max_results: 5,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 5);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /scholar?q=source:Nature&as_ylo=2020"));
+    }
+
+    #[cfg(feature = "venue-normalize")]
+    #[tokio::test]
+    async fn merges_both_forms() {
+        let server = MockServer::start(vec![
+            (
+                200,
+                results_page_with(None, vec![result_block(0), result_block(1)]),
+            ),
+            (
+                200,
+                results_page_with(None, vec![result_block(2), result_block(1)]),
+            ),
+        ])
+        .await;
+
+        let results = server
+            .client()
+            .search_venue(
+                "Commun. ACM",
+                Some("compilers"),
+                2019,
+                2023,
+                VenueSearchOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert!(requests[0].starts_with("GET /scholar?q=source:%22Commun.%20ACM%22%20compilers&"));
+        assert!(requests[1].starts_with(
+            "GET /scholar?q=source:%22Communications%20of%20the%20ACM%22%20compilers&"
+        ));
+        let titles = results.iter().map(|r| r.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Paper 2", "Paper 1", "Paper 0"]);
+    }
+}
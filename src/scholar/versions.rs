@@ -0,0 +1,310 @@
+// Telling the preprint copies of a paper from its published version among
+// the versions scholar groups in its cluster ("All N versions").
+
+use super::filter::{host, on_domain};
+use super::scholar::{Client, Error, ScholarArgs, ScholarResult};
+use crate::urlnorm::find_doi;
+
+// Preprint servers and repositories: a version there is the author's copy,
+// not the published one. Subdomains match, see DomainFilter.
+pub const PREPRINT_DOMAINS: &[&str] = &[
+    "arxiv.org",
+    "biorxiv.org",
+    "medrxiv.org",
+    "chemrxiv.org",
+    "psyarxiv.com",
+    "osf.io",
+    "ssrn.com",
+    "researchgate.net",
+    "academia.edu",
+    "semanticscholar.org",
+    "core.ac.uk",
+    "hal.science",
+    "archives-ouvertes.fr",
+    "europepmc.org",
+    "ncbi.nlm.nih.gov",
+    "citeseerx.ist.psu.edu",
+    "zenodo.org",
+];
+
+// Publishers: a version there is the published one. Subdomains match.
+pub const PUBLISHER_DOMAINS: &[&str] = &[
+    "springer.com",
+    "nature.com",
+    "sciencedirect.com",
+    "elsevier.com",
+    "cell.com",
+    "wiley.com",
+    "ieee.org",
+    "acm.org",
+    "science.org",
+    "tandfonline.com",
+    "sagepub.com",
+    "oup.com",
+    "cambridge.org",
+    "mdpi.com",
+    "frontiersin.org",
+    "plos.org",
+    "jstor.org",
+    "aps.org",
+    "iop.org",
+    "acs.org",
+    "rsc.org",
+    "bmj.com",
+    "thelancet.com",
+    "nejm.org",
+    "pnas.org",
+    "degruyter.com",
+    "emerald.com",
+    "doi.org",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionKind {
+    // on a preprint or repository domain
+    Preprint,
+    // on a publisher domain, or elsewhere with a DOI in its link
+    Publisher,
+    // neither, eg: a copy on a personal page
+    Other,
+}
+
+// Domain lists sorting versions into VersionKind, PREPRINT_DOMAINS and
+// PUBLISHER_DOMAINS by default. A link on a domain of both lists is a
+// preprint.
+#[derive(Debug, Clone)]
+pub struct VersionClassifier {
+    preprint: Vec<String>,
+    publisher: Vec<String>,
+}
+
+impl Default for VersionClassifier {
+    fn default() -> Self {
+        VersionClassifier {
+            preprint: PREPRINT_DOMAINS.iter().map(|d| d.to_string()).collect(),
+            publisher: PUBLISHER_DOMAINS.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+}
+
+impl VersionClassifier {
+    // adds `domain` to the preprint list, eg: an institutional repository
+    pub fn preprint_domain(mut self, domain: &str) -> Self {
+        self.preprint.push(domain.to_string());
+        self
+    }
+
+    // adds `domain` to the publisher list, eg: a society's journal site
+    pub fn publisher_domain(mut self, domain: &str) -> Self {
+        self.publisher.push(domain.to_string());
+        self
+    }
+
+    pub fn classify(&self, version: &ScholarResult) -> VersionKind {
+        let host = match host(&version.link) {
+            Some(host) => host,
+            None => return VersionKind::Other,
+        };
+        let listed = |domains: &[String]| domains.iter().any(|domain| on_domain(&host, domain));
+        if listed(&self.preprint) {
+            VersionKind::Preprint
+        } else if listed(&self.publisher) || find_doi(&version.link).is_some() {
+            VersionKind::Publisher
+        } else {
+            VersionKind::Other
+        }
+    }
+}
+
+// the published version of a paper, see Client::resolve_published_version
+#[derive(Debug, Clone)]
+pub struct PublishedMatch {
+    pub link: String,
+    pub venue: Option<String>,
+    pub year: Option<u16>,
+    // DOI in the link, lowercase
+    pub doi: Option<String>,
+    // the version as listed in the cluster
+    pub version: ScholarResult,
+}
+
+impl Client {
+    // resolve_published_version_with the default VersionClassifier
+    pub async fn resolve_published_version(
+        &self,
+        result: &ScholarResult,
+    ) -> Result<Option<PublishedMatch>, Error> {
+        self.resolve_published_version_with(result, &VersionClassifier::default())
+            .await
+    }
+
+    // Fetches the first page of the versions of `result` (its cluster) and
+    // returns the publisher version, None when there are only preprints or
+    // other copies. Versions on a listed publisher domain come before those
+    // only bearing a DOI, and within each, scholar's order decides.
+    pub async fn resolve_published_version_with(
+        &self,
+        result: &ScholarResult,
+        classifier: &VersionClassifier,
+    ) -> Result<Option<PublishedMatch>, Error> {
+        let cluster_id = result.cluster_id.clone().ok_or(Error::RequiredFieldError)?;
+        let versions = self
+            .scrape_scholar(&ScholarArgs::versions(cluster_id))
+            .await?;
+
+        let on_publisher = |version: &ScholarResult| {
+            host(&version.link).is_some_and(|host| {
+                classifier
+                    .publisher
+                    .iter()
+                    .any(|domain| on_domain(&host, domain))
+            })
+        };
+        let best = versions
+            .into_iter()
+            .filter(|version| classifier.classify(version) == VersionKind::Publisher)
+            .enumerate()
+            .min_by_key(|(position, version)| (!on_publisher(version), *position))
+            .map(|(_, version)| version);
+        Ok(best.map(|version| PublishedMatch {
+            link: version.link.clone(),
+            venue: version.venue.clone(),
+            year: version.year,
+            doi: find_doi(&version.link),
+            version,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{result_block_with_byline, results_page_with, MockServer};
+
+    fn version(i: usize, link: &str, byline: &str) -> String {
+        result_block_with_byline(i, byline)
+            .replace(&format!("https://example.org/paper{}", i), link)
+    }
+
+    fn result(link: &str) -> ScholarResult {
+        ScholarResult {
+            link: link.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn classify_versions() {
+        let classifier = VersionClassifier::default();
+        let kind = |link: &str| classifier.classify(&result(link));
+
+        assert_eq!(
+            kind("https://arxiv.org/abs/1706.03762"),
+            VersionKind::Preprint
+        );
+        assert_eq!(
+            kind("https://www.biorxiv.org/content/1"),
+            VersionKind::Preprint
+        );
+        assert_eq!(
+            kind("https://link.springer.com/article/10.1007/s10994-020-1"),
+            VersionKind::Publisher
+        );
+        assert_eq!(
+            kind("https://journal.example/doi/10.5555/12345678"),
+            VersionKind::Publisher
+        );
+        assert_eq!(
+            kind("https://people.example.edu/~a/paper.pdf"),
+            VersionKind::Other
+        );
+        assert_eq!(kind(""), VersionKind::Other);
+
+        let extended = VersionClassifier::default()
+            .preprint_domain("eprints.example.edu")
+            .publisher_domain("journal.example");
+        assert_eq!(
+            extended.classify(&result("https://eprints.example.edu/1")),
+            VersionKind::Preprint
+        );
+        assert_eq!(
+            extended.classify(&result("https://journal.example/1")),
+            VersionKind::Publisher
+        );
+    }
+
+    async fn resolve(blocks: Vec<String>) -> (Option<PublishedMatch>, Vec<String>) {
+        let server = MockServer::start(vec![(200, results_page_with(None, blocks))]).await;
+        let client = server.client();
+        let result = ScholarResult {
+            cluster_id: Some("2001".parse().unwrap()),
+            ..Default::default()
+        };
+
+        let published = client.resolve_published_version(&result).await.unwrap();
+        (published, server.requests())
+    }
+
+    #[tokio::test]
+    async fn picks_the_publisher_copy() {
+        let (published, requests) = resolve(vec![
+            version(
+                0,
+                "https://arxiv.org/abs/1912.00001",
+                "A Author, B Author - arXiv preprint arXiv:1912.00001, 2019 - arxiv.org",
+            ),
+            version(
+                1,
+                "https://repository.example/doi/10.5555/99999",
+                "A Author, B Author - 2020 - repository.example",
+            ),
+            version(
+                2,
+                "https://link.springer.com/article/10.1007/s10994-020-05901-8",
+                "A Author, B Author - Machine Learning, 2020 - Springer",
+            ),
+        ])
+        .await;
+
+        let published = published.unwrap();
+        assert_eq!(
+            published.link,
+            "https://link.springer.com/article/10.1007/s10994-020-05901-8"
+        );
+        assert_eq!(published.venue.as_deref(), Some("Machine Learning"));
+        assert_eq!(published.year, Some(2020));
+        assert_eq!(published.doi.as_deref(), Some("10.1007/s10994-020-05901-8"));
+        assert_eq!(published.version.title, "Paper 2");
+        assert!(requests[0].contains("cluster=2001"), "{}", requests[0]);
+    }
+
+    #[tokio::test]
+    async fn none_with_only_preprints() {
+        let (published, _) = resolve(vec![
+            version(
+                0,
+                "https://arxiv.org/abs/1912.00001",
+                "A Author - arXiv preprint arXiv:1912.00001, 2019 - arxiv.org",
+            ),
+            version(
+                1,
+                "https://www.researchgate.net/publication/1",
+                "A Author - 2019 - researchgate.net",
+            ),
+        ])
+        .await;
+
+        assert!(published.is_none());
+    }
+
+    #[tokio::test]
+    async fn needs_a_cluster() {
+        let client = Client::builder().build().unwrap();
+
+        let published = client
+            .resolve_published_version(&ScholarResult::default())
+            .await;
+
+        assert!(matches!(published, Err(Error::RequiredFieldError)));
+    }
+}
@@ -0,0 +1,344 @@
+// Following an author profile over time: newly listed articles, articles
+// gone from the list and changes of the citation metrics.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use futures_util::stream::{self, Stream};
+
+use super::ids::AuthorId;
+use super::profile::{AuthorMetrics, ProfileArticle};
+use super::scholar::{Client, Error};
+
+// What watch_author knows of a profile from its last check. Persist it
+// (serde feature) and pass it to watch_author_from to resume without
+// announcing every article again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthorWatchState {
+    // citation_for_view ids of the articles listed at the last check
+    pub article_ids: BTreeSet<String>,
+    // metrics at the last check, all None before the first
+    pub metrics: AuthorMetrics,
+}
+
+// change of each metric since the last check, None when either value is
+// unknown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricDeltas {
+    pub citations: Option<i64>,
+    pub h_index: Option<i64>,
+    pub i10_index: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthorUpdate {
+    // listed now but not at the last check, newest first
+    pub new_articles: Vec<ProfileArticle>,
+    // ids listed at the last check but not anymore, eg: merged articles
+    pub removed_ids: Vec<String>,
+    pub metrics: AuthorMetrics,
+    pub deltas: MetricDeltas,
+    // the state after this check, to persist
+    pub state: AuthorWatchState,
+}
+
+impl Client {
+    // watch_author_from an empty state: the first check lists every article
+    // of the profile as new
+    pub fn watch_author<'a>(
+        &'a self,
+        user_id: &AuthorId,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<AuthorUpdate, Error>> + 'a {
+        self.watch_author_from(user_id, interval, AuthorWatchState::default())
+    }
+
+    // Checks the profile of `user_id` now and then every `interval`,
+    // yielding an update when articles were added or removed or a metric
+    // changed since `state`. A failed check, eg: Error::Blocked, is yielded
+    // as an error and logged, the watch goes on at the next interval. The
+    // stream never ends, drop it to stop watching.
+    pub fn watch_author_from<'a>(
+        &'a self,
+        user_id: &AuthorId,
+        interval: Duration,
+        state: AuthorWatchState,
+    ) -> impl Stream<Item = Result<AuthorUpdate, Error>> + 'a {
+        let user_id = user_id.clone();
+        stream::unfold((state, true), move |(mut state, mut first)| {
+            let user_id = user_id.clone();
+            async move {
+                loop {
+                    if !first {
+                        tokio::time::sleep(interval).await;
+                    }
+                    first = false;
+                    let (articles, metrics) = match self.fetch_author_articles(&user_id).await {
+                        Ok(listing) => listing,
+                        Err(e) => {
+                            log::warn!("checking the profile of {} failed: {}", user_id, e);
+                            return Some((Err(e), (state, first)));
+                        }
+                    };
+                    let update = diff(&state, articles, metrics);
+                    state = update.state.clone();
+                    let deltas = update.deltas;
+                    let moved = [deltas.citations, deltas.h_index, deltas.i10_index]
+                        .iter()
+                        .any(|delta| delta.is_some_and(|delta| delta != 0));
+                    if moved || !update.new_articles.is_empty() || !update.removed_ids.is_empty() {
+                        return Some((Ok(update), (state, first)));
+                    }
+                }
+            }
+        })
+    }
+}
+
+fn diff(
+    previous: &AuthorWatchState,
+    articles: Vec<ProfileArticle>,
+    metrics: AuthorMetrics,
+) -> AuthorUpdate {
+    let article_ids = articles
+        .iter()
+        .map(|article| article.id.clone())
+        .collect::<BTreeSet<_>>();
+    let removed_ids = previous
+        .article_ids
+        .difference(&article_ids)
+        .cloned()
+        .collect();
+    let new_articles = articles
+        .into_iter()
+        .filter(|article| !previous.article_ids.contains(&article.id))
+        .collect();
+    let delta = |old: Option<u32>, new: Option<u32>| Some(i64::from(new?) - i64::from(old?));
+    let old = previous.metrics;
+    AuthorUpdate {
+        new_articles,
+        removed_ids,
+        metrics,
+        deltas: MetricDeltas {
+            citations: delta(old.citations, metrics.citations),
+            h_index: delta(old.h_index, metrics.h_index),
+            i10_index: delta(old.i10_index, metrics.i10_index),
+        },
+        state: AuthorWatchState {
+            article_ids,
+            metrics,
+        },
+    }
+}
+
+#[cfg(feature = "serde")]
+const STATE_FIELDS: &[&str] = &["article_ids", "citations", "h_index", "i10_index"];
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AuthorWatchState {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("AuthorWatchState", STATE_FIELDS.len())?;
+        state.serialize_field("article_ids", &self.article_ids)?;
+        state.serialize_field("citations", &self.metrics.citations)?;
+        state.serialize_field("h_index", &self.metrics.h_index)?;
+        state.serialize_field("i10_index", &self.metrics.i10_index)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AuthorWatchState {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StateVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for StateVisitor {
+            type Value = AuthorWatchState;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("an author watch state")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<AuthorWatchState, A::Error> {
+                let mut state = AuthorWatchState::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "article_ids" => state.article_ids = map.next_value()?,
+                        "citations" => state.metrics.citations = map.next_value()?,
+                        "h_index" => state.metrics.h_index = map.next_value()?,
+                        "i10_index" => state.metrics.i10_index = map.next_value()?,
+                        _ => {
+                            map.next_value::<serde::de::IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(state)
+            }
+        }
+
+        deserializer.deserialize_struct("AuthorWatchState", STATE_FIELDS, StateVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{profile_article, MockServer, ProfilePage};
+    use futures_util::{pin_mut, StreamExt};
+
+    // a profile page listing `articles` (id suffix, title, year, cited by)
+    fn profile_page(articles: &[(&str, &str, u16, u32)], metrics: [u32; 3]) -> String {
+        ProfilePage {
+            name: "Jane Doe".to_string(),
+            metrics,
+            since: (2019, 1),
+            articles: articles
+                .iter()
+                .map(|(id, title, year, cited_by)| {
+                    profile_article(id, title, *year, Some(*cited_by))
+                })
+                .collect(),
+            ..Default::default()
+        }
+        .html()
+    }
+
+    #[test]
+    fn parse_profile_listing() {
+        let page = profile_page(&[("aaa", "Deep nets", 2020, 12)], [120, 5, 3]);
+
+        let articles = crate::scholar::profile::parse_profile_articles(&page).unwrap();
+        let metrics = crate::scholar::profile::parse_author_metrics(&page).unwrap();
+
+        assert_eq!(
+            articles,
+            vec![ProfileArticle {
+                id: "JicYPdAAAAAJ:aaa".to_string(),
+                title: "Deep nets".to_string(),
+                authors: "J Doe, A Smith".to_string(),
+                venue: Some("Journal of Examples 1 (2), 3-4, 2020".to_string()),
+                year: Some(2020),
+                cited_by: Some(12),
+            }]
+        );
+        assert_eq!(
+            metrics,
+            AuthorMetrics {
+                citations: Some(120),
+                h_index: Some(5),
+                i10_index: Some(3),
+            }
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn announces_changes_only() {
+        let first = profile_page(
+            &[("bbb", "Older", 2019, 10), ("aaa", "Oldest", 2018, 5)],
+            [15, 2, 1],
+        );
+        let server = MockServer::start(vec![
+            (200, first.clone()),
+            // nothing changed, no update
+            (200, first),
+            (429, String::new()),
+            (
+                200,
+                profile_page(
+                    &[("ccc", "Newest", 2024, 0), ("bbb", "Older", 2019, 12)],
+                    [17, 2, 2],
+                ),
+            ),
+        ])
+        .await;
+        let client = server.client();
+        let user_id = "JicYPdAAAAAJ".parse().unwrap();
+        let state = AuthorWatchState {
+            article_ids: std::iter::once("JicYPdAAAAAJ:aaa".to_string()).collect(),
+            metrics: AuthorMetrics {
+                citations: Some(14),
+                h_index: Some(2),
+                i10_index: Some(1),
+            },
+        };
+
+        let updates = client.watch_author_from(&user_id, Duration::from_secs(3600), state);
+        pin_mut!(updates);
+
+        let update = updates.next().await.unwrap().unwrap();
+        let titles = update
+            .new_articles
+            .iter()
+            .map(|a| a.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Older"]);
+        assert!(update.removed_ids.is_empty());
+        assert_eq!(
+            update.deltas,
+            MetricDeltas {
+                citations: Some(1),
+                h_index: Some(0),
+                i10_index: Some(0),
+            }
+        );
+
+        assert!(matches!(updates.next().await, Some(Err(Error::Blocked))));
+
+        let update = updates.next().await.unwrap().unwrap();
+        assert_eq!(update.new_articles.len(), 1);
+        assert_eq!(update.new_articles[0].id, "JicYPdAAAAAJ:ccc");
+        assert_eq!(update.removed_ids, vec!["JicYPdAAAAAJ:aaa".to_string()]);
+        assert_eq!(update.metrics.citations, Some(17));
+        assert_eq!(update.deltas.citations, Some(2));
+        assert_eq!(update.deltas.i10_index, Some(1));
+        assert_eq!(update.state.article_ids.len(), 2);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[0].starts_with(
+            "GET /citations?user=JicYPdAAAAAJ&hl=en&cstart=0&pagesize=100&sortby=pubdate "
+        ));
+    }
+
+    #[test]
+    fn deltas_need_both_values() {
+        let update = diff(
+            &AuthorWatchState::default(),
+            Vec::new(),
+            AuthorMetrics {
+                citations: Some(3),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(update.deltas, MetricDeltas::default());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn state_round_trip() {
+        let state = AuthorWatchState {
+            article_ids: std::iter::once("JicYPdAAAAAJ:aaa".to_string()).collect(),
+            metrics: AuthorMetrics {
+                citations: Some(15),
+                h_index: Some(2),
+                i10_index: None,
+            },
+        };
+
+        let json = serde_json::to_string(&state).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"article_ids":["JicYPdAAAAAJ:aaa"],"citations":15,"h_index":2,"i10_index":null}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<AuthorWatchState>(&json).unwrap(),
+            state
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+use super::progress::{no_progress, report, Progress, ProgressHandler};
+use super::scholar::{Client, Error};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedLink {
+    // the original link still answers
+    Live {
+        url: String,
+    },
+    // the original link is gone, this is the closest wayback snapshot
+    Archived {
+        snapshot_url: String,
+        // yyyymmddhhmmss, eg: 20130919044612
+        timestamp: String,
+    },
+    // the original link is gone and the wayback machine has no copy
+    Dead {
+        url: String,
+    },
+}
+
+impl Client {
+    // HEADs `url` and, when it is gone (404, 410 or the host does not
+    // resolve), looks up the closest snapshot on the wayback machine.
+    pub async fn resolve_or_archive(&self, url: &str) -> Result<ResolvedLink, Error> {
+        let gone = match self.http().head(url).send().await {
+            Ok(resp) => matches!(
+                resp.status(),
+                reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE
+            ),
+            Err(e) if e.is_connect() => true,
+            Err(_) => return Err(Error::ConnectionError(url.to_string())),
+        };
+        if !gone {
+            return Ok(ResolvedLink::Live {
+                url: url.to_string(),
+            });
+        }
+        self.closest_snapshot(url).await
+    }
+
+    // resolve_or_archive over many links, at most `concurrency` at a time.
+    // Results are in the order of `urls`.
+    pub async fn resolve_all_or_archive(
+        &self,
+        urls: &[&str],
+        concurrency: usize,
+    ) -> Vec<Result<ResolvedLink, Error>> {
+        self.resolve_all_or_archive_with_progress(urls, concurrency, no_progress)
+            .await
+    }
+
+    // Same as resolve_all_or_archive, reporting each link to `progress` in
+    // the order of `urls` as its result is collected.
+    pub async fn resolve_all_or_archive_with_progress<H: ProgressHandler>(
+        &self,
+        urls: &[&str],
+        concurrency: usize,
+        progress: H,
+    ) -> Vec<Result<ResolvedLink, Error>> {
+        let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+        let tasks = urls
+            .iter()
+            .map(|url| {
+                let client = self.clone();
+                let permits = permits.clone();
+                let url = url.to_string();
+                tokio::spawn(async move {
+                    let _permit = permits.acquire().await;
+                    client.resolve_or_archive(&url).await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut resolved = Vec::with_capacity(tasks.len());
+        for (task, url) in tasks.into_iter().zip(urls) {
+            resolved.push(
+                task.await
+                    .unwrap_or_else(|_| Err(Error::ConnectionError(url.to_string()))),
+            );
+            report(
+                &progress,
+                Progress {
+                    operation: "resolve_all_or_archive",
+                    completed: resolved.len() as u64,
+                    total: Some(urls.len() as u64),
+                    message: Some(url.to_string()),
+                },
+            );
+        }
+        resolved
+    }
+
+    async fn closest_snapshot(&self, url: &str) -> Result<ResolvedLink, Error> {
+        let endpoint = self.wayback_endpoint();
+        let resp = self
+            .http()
+            .get(endpoint)
+            .query(&[("url", url)])
+            .send()
+            .await
+            .map_err(|_| Error::ConnectionError(endpoint.to_string()))?;
+        if !resp.status().is_success() {
+            return Err(Error::InvalidResponseError);
+        }
+        let body: serde_json::Value = resp.json().await.map_err(|_| Error::ParseError)?;
+
+        let closest = &body["archived_snapshots"]["closest"];
+        match (
+            closest["available"].as_bool(),
+            closest["url"].as_str(),
+            closest["timestamp"].as_str(),
+        ) {
+            (Some(true), Some(snapshot_url), Some(timestamp)) => Ok(ResolvedLink::Archived {
+                snapshot_url: snapshot_url.to_string(),
+                timestamp: timestamp.to_string(),
+            }),
+            _ => Ok(ResolvedLink::Dead {
+                url: url.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::MockServer;
+
+    const SNAPSHOT: &str = r#"{"url": "example.org/gone", "archived_snapshots": {"closest": {"status": "200", "available": true, "url": "http://web.archive.org/web/20130919044612/http://example.org/gone", "timestamp": "20130919044612"}}}"#;
+    const NO_SNAPSHOT: &str = r#"{"url": "example.org/never", "archived_snapshots": {}}"#;
+
+    #[tokio::test]
+    async fn resolve_live_and_dead_links() {
+        let site = MockServer::start(vec![
+            (200, String::new()),
+            (404, String::new()),
+            (410, String::new()),
+        ])
+        .await;
+        let wayback = MockServer::start(vec![
+            (200, SNAPSHOT.to_string()),
+            (200, NO_SNAPSHOT.to_string()),
+        ])
+        .await;
+        let client = Client::builder()
+            .wayback_endpoint(&wayback.url("/wayback/available"))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            client.resolve_or_archive(&site.url("/live")).await.unwrap(),
+            ResolvedLink::Live {
+                url: site.url("/live")
+            }
+        );
+        assert_eq!(
+            client.resolve_or_archive(&site.url("/gone")).await.unwrap(),
+            ResolvedLink::Archived {
+                snapshot_url: "http://web.archive.org/web/20130919044612/http://example.org/gone"
+                    .to_string(),
+                timestamp: "20130919044612".to_string(),
+            }
+        );
+        assert_eq!(
+            client
+                .resolve_or_archive(&site.url("/never"))
+                .await
+                .unwrap(),
+            ResolvedLink::Dead {
+                url: site.url("/never")
+            }
+        );
+
+        assert!(site.requests()[0].starts_with("HEAD /live "));
+        let lookups = wayback.requests();
+        assert_eq!(lookups.len(), 2);
+        assert!(lookups[0].starts_with("GET /wayback/available?url=http%3A%2F%2F127.0.0.1"));
+    }
+
+    #[tokio::test]
+    async fn resolve_unreachable_host() {
+        let wayback = MockServer::start(vec![(200, NO_SNAPSHOT.to_string())]).await;
+        let client = Client::builder()
+            .wayback_endpoint(&wayback.url("/wayback/available"))
+            .build()
+            .unwrap();
+
+        // nothing listens on port 9 of localhost
+        let resolved = client
+            .resolve_all_or_archive(&["http://127.0.0.1:9/paper"], 2)
+            .await;
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(
+            resolved[0].as_ref().unwrap(),
+            &ResolvedLink::Dead {
+                url: "http://127.0.0.1:9/paper".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,241 @@
+// Local re-ranking of results with explicit weights, as opposed to scholar's
+// own ordering which mixes relevance and citations in unknown ways.
+
+use crate::scholar::{current_year, ScholarResult};
+
+// Weight of each signal in the composite score. Negative weights are treated
+// as 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Weights {
+    // position in scholar's ordering, first is best
+    pub rank: f64,
+    // "Cited by" count
+    pub citations: f64,
+    // publication year, newest is best
+    pub recency: f64,
+    // citations divided by the years since publication, counting the
+    // current one
+    pub citations_per_year: f64,
+}
+
+impl Weights {
+    // well cited papers first, tempered by their age and scholar's order
+    pub fn impact() -> Weights {
+        Weights {
+            rank: 0.2,
+            citations: 0.5,
+            recency: 0.0,
+            citations_per_year: 0.3,
+        }
+    }
+
+    // recent papers first, preferring those already picking up citations
+    pub fn fresh() -> Weights {
+        Weights {
+            rank: 0.2,
+            citations: 0.0,
+            recency: 0.5,
+            citations_per_year: 0.3,
+        }
+    }
+}
+
+// Scores `results` and sorts them by score, highest first. Ties keep their
+// order, so the sort is stable against scholar's.
+//
+// Each signal is min-max normalized within `results` to 0..=1:
+// (value - min) / (max - min), and 1 when every known value is the same. A
+// result missing the signal, eg: no year, gets 0 for it. The score is
+//   sum(weight * normalized) / sum(weight)
+// so it stays within 0..=1, or 0 when all weights are 0. `results` is
+// expected in the order scholar returned them, rank is read from it.
+pub fn rank(results: &mut [ScholarResult], weights: Weights) {
+    let this_year = current_year();
+    let count = results.len();
+    let signals = results
+        .iter()
+        .enumerate()
+        .map(|(position, result)| {
+            let citations = result.cited_by.map(f64::from);
+            let age = result
+                .year
+                .map(|year| f64::from(this_year.saturating_sub(year)) + 1.0);
+            [
+                Some((count - position) as f64),
+                citations,
+                result.year.map(f64::from),
+                citations.zip(age).map(|(citations, age)| citations / age),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let weights = [
+        weights.rank,
+        weights.citations,
+        weights.recency,
+        weights.citations_per_year,
+    ]
+    .map(|weight| weight.max(0.0));
+    let total_weight = weights.iter().sum::<f64>();
+
+    let normalized = (0..weights.len())
+        .map(|signal| normalize(signals.iter().map(|values| values[signal])))
+        .collect::<Vec<_>>();
+    for (i, result) in results.iter_mut().enumerate() {
+        let weighted = weights
+            .iter()
+            .zip(&normalized)
+            .map(|(weight, values)| weight * values[i])
+            .sum::<f64>();
+        result.score = Some(if total_weight > 0.0 {
+            weighted / total_weight
+        } else {
+            0.0
+        });
+    }
+    results.sort_by(|a, b| {
+        let (a, b) = (a.score.unwrap_or(0.0), b.score.unwrap_or(0.0));
+        b.total_cmp(&a)
+    });
+}
+
+fn normalize(values: impl Iterator<Item = Option<f64>>) -> Vec<f64> {
+    let values = values.collect::<Vec<_>>();
+    let known = values.iter().flatten();
+    let min = known.clone().copied().fold(f64::INFINITY, f64::min);
+    let max = known.copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|value| match value {
+            Some(_) if max <= min => 1.0,
+            Some(value) => (value - min) / (max - min),
+            None => 0.0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(title: &str, cited_by: Option<u32>, year: Option<u16>) -> ScholarResult {
+        ScholarResult {
+            title: title.to_string(),
+            cited_by,
+            year,
+            ..Default::default()
+        }
+    }
+
+    // in scholar's order
+    fn results() -> Vec<ScholarResult> {
+        let this_year = current_year();
+        vec![
+            result("relevant", Some(10), Some(this_year - 10)),
+            result("classic", Some(5000), Some(this_year - 30)),
+            result("new", Some(40), Some(this_year)),
+            result("undated", Some(100), None),
+            result("uncited", None, Some(this_year - 1)),
+        ]
+    }
+
+    fn ranked(weights: Weights) -> Vec<String> {
+        let mut results = results();
+        rank(&mut results, weights);
+        results.into_iter().map(|r| r.title).collect()
+    }
+
+    fn position(weights: Weights, title: &str) -> usize {
+        ranked(weights).iter().position(|t| t == title).unwrap()
+    }
+
+    #[test]
+    fn single_signals() {
+        let only = |set: fn(&mut Weights)| {
+            let mut weights = Weights::default();
+            set(&mut weights);
+            ranked(weights)
+        };
+
+        assert_eq!(
+            only(|w| w.rank = 1.0),
+            vec!["relevant", "classic", "new", "undated", "uncited"]
+        );
+        assert_eq!(
+            only(|w| w.citations = 1.0),
+            vec!["classic", "undated", "new", "relevant", "uncited"]
+        );
+        assert_eq!(
+            only(|w| w.recency = 1.0),
+            vec!["new", "uncited", "relevant", "classic", "undated"]
+        );
+        assert_eq!(
+            only(|w| w.citations_per_year = 1.0),
+            vec!["classic", "new", "relevant", "undated", "uncited"]
+        );
+        // no weight, no reordering
+        assert_eq!(ranked(Weights::default())[0], "relevant");
+    }
+
+    #[test]
+    fn scores_are_normalized() {
+        let mut results = results();
+        rank(&mut results, Weights::impact());
+
+        assert!(results.iter().all(|r| {
+            let score = r.score.unwrap();
+            (0.0..=1.0).contains(&score)
+        }));
+        let scores = results.iter().map(|r| r.score.unwrap()).collect::<Vec<_>>();
+        assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(results[0].title, "classic");
+        assert_eq!(ranked(Weights::fresh())[0], "new");
+    }
+
+    #[test]
+    fn monotonic_in_each_weight() {
+        type Set = fn(&mut Weights, f64);
+        let bump: [(Set, &str); 4] = [
+            (|w, v| w.rank = v, "relevant"),
+            (|w, v| w.citations = v, "classic"),
+            (|w, v| w.recency = v, "new"),
+            (|w, v| w.citations_per_year = v, "classic"),
+        ];
+        for (set, leader) in bump.iter() {
+            let mut previous = usize::MAX;
+            for weight in [0.0, 0.25, 0.5, 1.0, 4.0].iter() {
+                let mut weights = Weights {
+                    rank: 0.3,
+                    citations: 0.3,
+                    recency: 0.3,
+                    citations_per_year: 0.3,
+                };
+                set(&mut weights, *weight);
+                let now = position(weights, leader);
+                assert!(now <= previous, "{} at {} for {}", leader, now, weight);
+                previous = now;
+            }
+            assert_eq!(previous, 0, "{}", leader);
+        }
+    }
+
+    #[test]
+    fn ties_keep_scholar_order() {
+        let mut results = vec![
+            result("first", Some(10), Some(2020)),
+            result("second", Some(10), Some(2020)),
+            result("third", Some(10), Some(2020)),
+        ];
+
+        rank(
+            &mut results,
+            Weights {
+                citations: 1.0,
+                ..Weights::default()
+            },
+        );
+
+        let titles = results.iter().map(|r| r.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["first", "second", "third"]);
+        assert!(results.iter().all(|r| r.score == Some(1.0)));
+    }
+}
@@ -0,0 +1,385 @@
+// Overview of a collected result set: when, where and by whom the results
+// were published and how cited they are.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::names;
+use crate::scholar::ScholarResult;
+
+// how many venues and authors CorpusStats lists
+pub const TOP_N: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorpusStats {
+    pub results: usize,
+    // results per year, by year
+    pub per_year: Vec<(u16, usize)>,
+    pub without_year: usize,
+    // the TOP_N venues with most results, most first, ties by name
+    pub top_venues: Vec<(String, usize)>,
+    pub without_venue: usize,
+    // the TOP_N authors with most results, names merged with
+    // names::canonical, most first, ties by name
+    pub top_authors: Vec<(String, usize)>,
+    pub without_authors: usize,
+    // over the results with a "Cited by" count. None when none has one
+    pub citations: Option<Quartiles>,
+    // results without a count: scholar shows none for uncited papers
+    pub without_citations: usize,
+    // results with a full text link beside them, eg: "[PDF] arxiv.org"
+    pub with_full_text: usize,
+}
+
+// Citation count distribution, quartiles interpolated between the counts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quartiles {
+    pub min: u32,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: u32,
+}
+
+pub fn summarize(results: &[ScholarResult]) -> CorpusStats {
+    let mut years = HashMap::new();
+    let mut venues = HashMap::new();
+    let mut authors = HashMap::new();
+    let mut citations = Vec::new();
+    let mut stats = CorpusStats {
+        results: results.len(),
+        ..Default::default()
+    };
+    for result in results {
+        match result.year {
+            Some(year) => *years.entry(year).or_insert(0) += 1,
+            None => stats.without_year += 1,
+        }
+        match result.venue.as_deref().map(str::trim) {
+            Some(venue) if !venue.is_empty() => *venues.entry(venue.to_string()).or_insert(0) += 1,
+            _ => stats.without_venue += 1,
+        }
+        let mut names = result
+            .authors
+            .iter()
+            .map(|author| names::canonical(author))
+            .filter(|author| !author.is_empty())
+            .collect::<Vec<_>>();
+        // an author counts once per result
+        names.sort_unstable();
+        names.dedup();
+        if names.is_empty() {
+            stats.without_authors += 1;
+        }
+        for name in names {
+            *authors.entry(name).or_insert(0) += 1;
+        }
+        match result.cited_by {
+            Some(count) => citations.push(count),
+            None => stats.without_citations += 1,
+        }
+        if !result.source_links.is_empty() {
+            stats.with_full_text += 1;
+        }
+    }
+
+    stats.per_year = years.into_iter().collect();
+    stats.per_year.sort_unstable();
+    stats.top_venues = top(venues);
+    stats.top_authors = top(authors);
+    stats.citations = quartiles(citations);
+    stats
+}
+
+fn top(counts: HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_unstable_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    counts.truncate(TOP_N);
+    counts
+}
+
+fn quartiles(mut counts: Vec<u32>) -> Option<Quartiles> {
+    counts.sort_unstable();
+    let quantile = |q: f64| {
+        let position = q * (counts.len() - 1) as f64;
+        let (below, above) = (position.floor() as usize, position.ceil() as usize);
+        let fraction = position - below as f64;
+        f64::from(counts[below]) + fraction * (f64::from(counts[above]) - f64::from(counts[below]))
+    };
+    Some(Quartiles {
+        min: *counts.first()?,
+        q1: quantile(0.25),
+        median: quantile(0.5),
+        q3: quantile(0.75),
+        max: *counts.last()?,
+    })
+}
+
+// A plain text block, eg:
+//
+// 6 results
+// Per year: 2018: 1, 2019: 3 (2 results without a year)
+// Top venues:
+//   3  Journal of Examples
+// ...
+impl fmt::Display for CorpusStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} results", self.results)?;
+        let years = self
+            .per_year
+            .iter()
+            .map(|(year, count)| format!("{}: {}", year, count))
+            .collect::<Vec<_>>();
+        write!(f, "Per year: {}", none_if_empty(&years.join(", ")))?;
+        writeln!(f, "{}", missing(self.without_year, "year"))?;
+        write_top(
+            f,
+            "Top venues",
+            &self.top_venues,
+            self.without_venue,
+            "venue",
+        )?;
+        write_top(
+            f,
+            "Top authors",
+            &self.top_authors,
+            self.without_authors,
+            "author",
+        )?;
+        match &self.citations {
+            Some(q) => write!(
+                f,
+                "Citations: min {}, Q1 {}, median {}, Q3 {}, max {}",
+                q.min, q.q1, q.median, q.q3, q.max
+            )?,
+            None => write!(f, "Citations: none")?,
+        }
+        writeln!(f, "{}", missing(self.without_citations, "citation count"))?;
+        let share = match self.results {
+            0 => 0.0,
+            results => 100.0 * self.with_full_text as f64 / results as f64,
+        };
+        writeln!(
+            f,
+            "Free full text: {} of {} ({:.0}%)",
+            self.with_full_text, self.results, share
+        )
+    }
+}
+
+fn write_top(
+    f: &mut fmt::Formatter<'_>,
+    heading: &str,
+    counts: &[(String, usize)],
+    without: usize,
+    what: &str,
+) -> fmt::Result {
+    if counts.is_empty() {
+        write!(f, "{}: none", heading)?;
+        return writeln!(f, "{}", missing(without, what));
+    }
+    writeln!(f, "{}:", heading)?;
+    for (name, count) in counts {
+        writeln!(f, "  {:>4}  {}", count, name)?;
+    }
+    if without > 0 {
+        writeln!(f, "  {} without a{} {}", without, article(what), what)?;
+    }
+    Ok(())
+}
+
+// " (42 results without a year)", empty when there are none
+fn missing(count: usize, what: &str) -> String {
+    match count {
+        0 => String::new(),
+        1 => format!(" (1 result without a{} {})", article(what), what),
+        count => format!(" ({} results without a{} {})", count, article(what), what),
+    }
+}
+
+fn article(what: &str) -> &'static str {
+    if what.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        "n"
+    } else {
+        ""
+    }
+}
+
+fn none_if_empty(text: &str) -> &str {
+    if text.is_empty() {
+        "none"
+    } else {
+        text
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CorpusStats {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut stats = serializer.serialize_struct("CorpusStats", 10)?;
+        stats.serialize_field("results", &self.results)?;
+        stats.serialize_field("per_year", &self.per_year)?;
+        stats.serialize_field("without_year", &self.without_year)?;
+        stats.serialize_field("top_venues", &self.top_venues)?;
+        stats.serialize_field("without_venue", &self.without_venue)?;
+        stats.serialize_field("top_authors", &self.top_authors)?;
+        stats.serialize_field("without_authors", &self.without_authors)?;
+        stats.serialize_field("citations", &self.citations)?;
+        stats.serialize_field("without_citations", &self.without_citations)?;
+        stats.serialize_field("with_full_text", &self.with_full_text)?;
+        stats.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Quartiles {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut quartiles = serializer.serialize_struct("Quartiles", 5)?;
+        quartiles.serialize_field("min", &self.min)?;
+        quartiles.serialize_field("q1", &self.q1)?;
+        quartiles.serialize_field("median", &self.median)?;
+        quartiles.serialize_field("q3", &self.q3)?;
+        quartiles.serialize_field("max", &self.max)?;
+        quartiles.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::SourceLink;
+
+    fn corpus() -> Vec<ScholarResult> {
+        let result =
+            |authors: &[&str], venue: Option<&str>, year, cited_by, pdf: bool| ScholarResult {
+                authors: authors.iter().map(|a| a.to_string()).collect(),
+                venue: venue.map(str::to_string),
+                year,
+                cited_by,
+                source_links: if pdf {
+                    vec![SourceLink {
+                        label: "[PDF] arxiv.org".to_string(),
+                        url: "https://arxiv.org/pdf/1".to_string(),
+                        ..Default::default()
+                    }]
+                } else {
+                    Vec::new()
+                },
+                ..Default::default()
+            };
+        vec![
+            result(
+                &["J. A. Smith", "B Jones"],
+                Some("Nature"),
+                Some(2019),
+                Some(40),
+                true,
+            ),
+            result(&["JA Smith"], Some("Nature"), Some(2019), Some(10), false),
+            result(
+                &["J A Smith", "C Brown"],
+                Some("Science"),
+                Some(2020),
+                Some(2),
+                true,
+            ),
+            result(&["B Jones"], Some(" "), Some(2018), Some(4), false),
+            result(&[], None, None, None, false),
+            result(&["D Green"], Some("Nature"), None, Some(1), false),
+        ]
+    }
+
+    #[test]
+    fn aggregates() {
+        let stats = summarize(&corpus());
+
+        assert_eq!(stats.results, 6);
+        assert_eq!(stats.per_year, vec![(2018, 1), (2019, 2), (2020, 1)]);
+        assert_eq!(stats.without_year, 2);
+        assert_eq!(
+            stats.top_venues,
+            vec![("Nature".to_string(), 3), ("Science".to_string(), 1)]
+        );
+        assert_eq!(stats.without_venue, 2);
+        assert_eq!(
+            stats.top_authors,
+            vec![
+                ("JA Smith".to_string(), 3),
+                ("B Jones".to_string(), 2),
+                ("C Brown".to_string(), 1),
+                ("D Green".to_string(), 1),
+            ]
+        );
+        assert_eq!(stats.without_authors, 1);
+        // 1, 2, 4, 10, 40
+        assert_eq!(
+            stats.citations,
+            Some(Quartiles {
+                min: 1,
+                q1: 2.0,
+                median: 4.0,
+                q3: 10.0,
+                max: 40,
+            })
+        );
+        assert_eq!(stats.without_citations, 1);
+        assert_eq!(stats.with_full_text, 2);
+    }
+
+    #[test]
+    fn interpolated_quartiles() {
+        let q = quartiles(vec![10, 1, 4, 2]).unwrap();
+
+        assert_eq!((q.min, q.max), (1, 10));
+        assert_eq!((q.q1, q.median, q.q3), (1.75, 3.0, 5.5));
+        assert_eq!(quartiles(Vec::new()), None);
+    }
+
+    #[test]
+    fn plain_text_block() {
+        assert_eq!(
+            summarize(&corpus()).to_string(),
+            "6 results\n\
+Per year: 2018: 1, 2019: 2, 2020: 1 (2 results without a year)\n\
+Top venues:\n\
+\x20    3  Nature\n\
+\x20    1  Science\n\
+\x20 2 without a venue\n\
+Top authors:\n\
+\x20    3  JA Smith\n\
+\x20    2  B Jones\n\
+\x20    1  C Brown\n\
+\x20    1  D Green\n\
+\x20 1 without an author\n\
+Citations: min 1, Q1 2, median 4, Q3 10, max 40 (1 result without a citation count)\n\
+Free full text: 2 of 6 (33%)\n"
+        );
+        assert_eq!(
+            summarize(&[]).to_string(),
+            "0 results\n\
+Per year: none\n\
+Top venues: none\n\
+Top authors: none\n\
+Citations: none\n\
+Free full text: 0 of 0 (0%)\n"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes() {
+        let json = serde_json::to_value(summarize(&corpus())).unwrap();
+
+        assert_eq!(
+            json["per_year"],
+            serde_json::json!([[2018, 1], [2019, 2], [2020, 1]])
+        );
+        assert_eq!(json["top_venues"][0], serde_json::json!(["Nature", 3]));
+        assert_eq!(json["citations"]["median"], 4.0);
+        assert_eq!(json["without_citations"], 1);
+    }
+}
@@ -0,0 +1,311 @@
+// Citation counts of single papers polled over time, kept in a JSON state
+// file so the series grows across runs, eg:
+//
+// {"2001": [{"at": 1760486400, "count": 11}, {"at": 1760572800, "gap": true}]}
+//
+// A gap is a poll where the cluster was not found: scholar merged it into
+// another cluster or dropped it. It is kept in the series and skipped when
+// comparing counts.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Map, Value};
+
+use crate::scholar::{Client, ClusterId, Error, ScholarArgs};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackPoint {
+    // second precision, as persisted
+    pub at: SystemTime,
+    // None marks a gap
+    pub count: Option<u32>,
+}
+
+impl TrackPoint {
+    pub fn is_gap(&self) -> bool {
+        self.count.is_none()
+    }
+}
+
+pub struct CitationTracker {
+    path: PathBuf,
+    clock: Box<dyn Fn() -> SystemTime + Send + Sync>,
+    series: BTreeMap<ClusterId, Vec<TrackPoint>>,
+}
+
+impl CitationTracker {
+    // Loads the series in `state_path`, starting empty when the file does
+    // not exist yet. A file that is not a tracker state is an error rather
+    // than reset, it would be overwritten on the next track.
+    pub fn open(state_path: impl Into<PathBuf>) -> io::Result<CitationTracker> {
+        CitationTracker::with_clock(state_path, SystemTime::now)
+    }
+
+    pub(crate) fn with_clock<F>(state_path: impl Into<PathBuf>, clock: F) -> io::Result<Self>
+    where
+        F: Fn() -> SystemTime + Send + Sync + 'static,
+    {
+        let path = state_path.into();
+        let series = match fs::read_to_string(&path) {
+            Ok(contents) => parse_state(&contents).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} is not a citation tracker state", path.display()),
+                )
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(CitationTracker {
+            path,
+            clock: Box::new(clock),
+            series,
+        })
+    }
+
+    // Looks the cluster's "Cited by" count up and appends it to its series.
+    // The count is the one of the version listed under `cluster_id`; when no
+    // version is, the point is a gap. Failed requests are returned and leave
+    // the series as it was. The state is saved after each point, a failed
+    // save is logged.
+    pub async fn track(
+        &mut self,
+        client: &Client,
+        cluster_id: &ClusterId,
+    ) -> Result<TrackPoint, Error> {
+        let versions = client
+            .scrape_scholar(&ScholarArgs::versions(cluster_id.clone()))
+            .await?;
+        // a paper with a single version has no "All N versions" link and so
+        // no cluster id of its own
+        let count = versions
+            .iter()
+            .find(|version| {
+                version
+                    .cluster_id
+                    .as_ref()
+                    .is_none_or(|id| id == cluster_id)
+            })
+            // scholar shows no count for uncited papers
+            .map(|version| version.cited_by.unwrap_or(0));
+        if count.is_none() {
+            log::info!("cluster {} not found, recording a gap", cluster_id);
+        }
+
+        let point = TrackPoint {
+            at: truncate((self.clock)()),
+            count,
+        };
+        self.series
+            .entry(cluster_id.clone())
+            .or_default()
+            .push(point);
+        if let Err(e) = fs::write(&self.path, self.to_json()) {
+            log::warn!(
+                "could not save citation tracker state to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+        Ok(point)
+    }
+
+    // every point tracked for the cluster, oldest first, gaps included
+    pub fn history(&self, cluster_id: &ClusterId) -> &[TrackPoint] {
+        self.series.get(cluster_id).map_or(&[], Vec::as_slice)
+    }
+
+    // Citations gained since `duration` ago: the latest count less the last
+    // one tracked at or before then. None when either is missing, eg: the
+    // series does not reach back that far. Gaps are skipped.
+    pub fn delta_since(&self, cluster_id: &ClusterId, duration: Duration) -> Option<i64> {
+        let since = (self.clock)().checked_sub(duration)?;
+        let mut counts = self
+            .history(cluster_id)
+            .iter()
+            .filter_map(|point| Some((point.at, point.count?)));
+        let latest = counts.clone().next_back()?.1;
+        let then = counts.rfind(|(at, _)| *at <= since)?.1;
+        Some(i64::from(latest) - i64::from(then))
+    }
+
+    fn to_json(&self) -> String {
+        let series = self
+            .series
+            .iter()
+            .map(|(cluster_id, points)| {
+                let points = points
+                    .iter()
+                    .map(|point| match point.count {
+                        Some(count) => json!({"at": seconds(point.at), "count": count}),
+                        None => json!({"at": seconds(point.at), "gap": true}),
+                    })
+                    .collect();
+                (cluster_id.to_string(), Value::Array(points))
+            })
+            .collect::<Map<_, _>>();
+        let mut json = Value::Object(series).to_string();
+        json.push('\n');
+        json
+    }
+}
+
+fn parse_state(contents: &str) -> Option<BTreeMap<ClusterId, Vec<TrackPoint>>> {
+    let state = serde_json::from_str::<Value>(contents).ok()?;
+    state
+        .as_object()?
+        .iter()
+        .map(|(cluster_id, points)| {
+            let points = points
+                .as_array()?
+                .iter()
+                .map(parse_point)
+                .collect::<Option<Vec<_>>>()?;
+            Some((ClusterId::from(cluster_id.clone()), points))
+        })
+        .collect()
+}
+
+fn parse_point(point: &Value) -> Option<TrackPoint> {
+    let at = UNIX_EPOCH + Duration::from_secs(point.get("at")?.as_u64()?);
+    let count = match point.get("gap").and_then(Value::as_bool) {
+        Some(true) => None,
+        _ => Some(u32::try_from(point.get("count")?.as_u64()?).ok()?),
+    };
+    Some(TrackPoint { at, count })
+}
+
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0)
+}
+
+fn truncate(time: SystemTime) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{result_block, results_page_with, MockServer};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    const DAY: u64 = 24 * 60 * 60;
+    // 2025-10-15 00:00:00 UTC
+    const START: u64 = 20376 * DAY;
+
+    fn state_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("gscholar-tracker-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn mock_clock(now: &Arc<AtomicU64>) -> impl Fn() -> SystemTime + Send + Sync + 'static {
+        let now = now.clone();
+        move || UNIX_EPOCH + Duration::from_secs(now.load(Ordering::SeqCst))
+    }
+
+    fn point(at: u64, count: Option<u32>) -> TrackPoint {
+        TrackPoint {
+            at: UNIX_EPOCH + Duration::from_secs(at),
+            count,
+        }
+    }
+
+    #[tokio::test]
+    async fn three_polls_with_a_gap() {
+        let path = state_path("polls");
+        let now = Arc::new(AtomicU64::new(START));
+        // cluster 2001 is counted 11 times, then scholar lists 2002 in its
+        // place, then 2001 is back with 15
+        let server = MockServer::start(vec![
+            (200, results_page_with(None, vec![result_block(1)])),
+            (200, results_page_with(None, vec![result_block(2)])),
+            (
+                200,
+                results_page_with(
+                    None,
+                    vec![result_block(1).replace("Cited by 11", "Cited by 15")],
+                ),
+            ),
+        ])
+        .await;
+        let client = server.client();
+        let cluster_id = "2001".parse::<ClusterId>().unwrap();
+        let mut tracker = CitationTracker::with_clock(&path, mock_clock(&now)).unwrap();
+
+        for _ in 0..3 {
+            tracker.track(&client, &cluster_id).await.unwrap();
+            now.fetch_add(DAY, Ordering::SeqCst);
+        }
+
+        let expected = vec![
+            point(START, Some(11)),
+            point(START + DAY, None),
+            point(START + 2 * DAY, Some(15)),
+        ];
+        assert_eq!(tracker.history(&cluster_id), expected.as_slice());
+        assert!(server.requests()[0].contains("cluster=2001"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!(
+                "{{\"2001\":[{{\"at\":{},\"count\":11}},{{\"at\":{},\"gap\":true}},{{\"at\":{},\"count\":15}}]}}\n",
+                START,
+                START + DAY,
+                START + 2 * DAY
+            )
+        );
+
+        // now is START + 3 days: two days back the last count is 11, past
+        // the gap, and a day back it is the latest count
+        assert_eq!(
+            tracker.delta_since(&cluster_id, Duration::from_secs(2 * DAY)),
+            Some(4)
+        );
+        assert_eq!(
+            tracker.delta_since(&cluster_id, Duration::from_secs(DAY)),
+            Some(0)
+        );
+        assert_eq!(
+            tracker.delta_since(&cluster_id, Duration::from_secs(3 * DAY + 1)),
+            None
+        );
+        assert_eq!(
+            tracker.delta_since(&"2002".parse().unwrap(), Duration::from_secs(DAY)),
+            None
+        );
+
+        let reopened = CitationTracker::with_clock(&path, mock_clock(&now)).unwrap();
+        assert_eq!(reopened.history(&cluster_id), expected.as_slice());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn corrupt_state_is_an_error() {
+        let path = state_path("corrupt");
+        fs::write(&path, "20376 42\n").unwrap();
+
+        let opened = CitationTracker::open(&path);
+
+        assert_eq!(
+            opened.err().map(|e| e.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn missing_state_starts_empty() {
+        let tracker = CitationTracker::open(state_path("missing")).unwrap();
+
+        assert!(tracker.history(&"2001".parse().unwrap()).is_empty());
+    }
+}
@@ -0,0 +1,374 @@
+// An interactive results browser. The state lives here, apart from drawing
+// it: a front end feeds it key presses and finished fetches through
+// Browser::update and carries out the Command it returns, eg: opening a
+// link or fetching the citing papers with cited_by. Everything here runs
+// without a terminal, so the behavior is tested with synthetic keys. The
+// terminal front end is `run`, started by `gscholar tui "<query>"`.
+//
+// Keys:
+// - Up/Down or k/j move the cursor, Enter shows or hides the detail pane
+// - Space selects or deselects the result under the cursor
+// - o opens the result's link, c loads the papers citing it in place and
+//   Backspace goes back to the previous list
+// - b exports the selection, or the result under the cursor, to BibTeX
+// - Esc closes the detail pane and dismisses the error banner, q quits
+
+mod terminal;
+
+pub use self::terminal::{run, EXPORT_FILE};
+
+use crate::export;
+use crate::scholar::{CiteId, Client, Error, ScholarArgs, ScholarResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Enter,
+    Esc,
+    Backspace,
+    Char(char),
+}
+
+#[derive(Debug)]
+pub enum Event {
+    Key(Key),
+    // the results of the fetch a Command::CitedBy asked for
+    Loaded(Result<Vec<ScholarResult>, Error>),
+}
+
+// What the front end should do after an update
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    None,
+    // open the url in a browser
+    Open(String),
+    // fetch the papers citing this one, eg: with cited_by, and send back
+    // its Event::Loaded
+    CitedBy(CiteId),
+    // BibTeX of the exported results, to save or copy
+    Export(String),
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Idle,
+    // a fetch is running, eg: shown as a spinner with the message
+    Loading(String),
+    // banner until dismissed, Error::user_message of the failed fetch
+    Error(String),
+}
+
+// a list of results the browser can go back to
+#[derive(Debug, Clone)]
+struct Listing {
+    title: String,
+    results: Vec<ScholarResult>,
+    cursor: usize,
+    selected: Vec<bool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Browser {
+    listing: Listing,
+    // listings Backspace goes back to, latest last
+    previous: Vec<Listing>,
+    detail: bool,
+    status: Status,
+}
+
+impl Browser {
+    // a browser over `results` found for `query`
+    pub fn new(query: &str, results: Vec<ScholarResult>) -> Browser {
+        Browser {
+            listing: Listing::new(query.to_string(), results),
+            previous: Vec::new(),
+            detail: false,
+            status: Status::Idle,
+        }
+    }
+
+    // the query, or "Cited by: <title>" for citing papers
+    pub fn title(&self) -> &str {
+        &self.listing.title
+    }
+
+    pub fn results(&self) -> &[ScholarResult] {
+        &self.listing.results
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.listing.cursor
+    }
+
+    // the result under the cursor, None for an empty list
+    pub fn current(&self) -> Option<&ScholarResult> {
+        self.listing.results.get(self.listing.cursor)
+    }
+
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.listing.selected.get(index).copied().unwrap_or(false)
+    }
+
+    // whether the detail pane of the current result is shown
+    pub fn detail(&self) -> bool {
+        self.detail
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    pub fn update(&mut self, event: Event) -> Command {
+        match event {
+            Event::Key(key) => self.key(key),
+            Event::Loaded(loaded) => {
+                self.loaded(loaded);
+                Command::None
+            }
+        }
+    }
+
+    fn key(&mut self, key: Key) -> Command {
+        if key == Key::Char('q') {
+            return Command::Quit;
+        }
+        // the list may change under the cursor once the fetch is done
+        if let Status::Loading(_) = self.status {
+            return Command::None;
+        }
+        let listing = &mut self.listing;
+        match key {
+            Key::Up | Key::Char('k') => listing.cursor = listing.cursor.saturating_sub(1),
+            Key::Down | Key::Char('j') => {
+                if listing.cursor + 1 < listing.results.len() {
+                    listing.cursor += 1;
+                }
+            }
+            Key::Enter => self.detail = !self.detail && self.current().is_some(),
+            Key::Esc => {
+                self.detail = false;
+                self.status = Status::Idle;
+            }
+            Key::Char(' ') => {
+                if let Some(selected) = listing.selected.get_mut(listing.cursor) {
+                    *selected = !*selected;
+                }
+            }
+            Key::Char('o') => {
+                if let Some(result) = self.current().filter(|result| !result.link.is_empty()) {
+                    return Command::Open(result.link.clone());
+                }
+            }
+            Key::Char('c') => {
+                let result = match self.current() {
+                    Some(result) => result,
+                    None => return Command::None,
+                };
+                if let Some(cite_id) = result.cite_id.clone() {
+                    self.status = Status::Loading(format!("Cited by: {}", result.title));
+                    return Command::CitedBy(cite_id);
+                }
+            }
+            Key::Char('b') => {
+                let selected = listing
+                    .results
+                    .iter()
+                    .zip(&listing.selected)
+                    .filter(|(_, selected)| **selected)
+                    .map(|(result, _)| result.clone())
+                    .collect::<Vec<_>>();
+                let exported = if selected.is_empty() {
+                    self.current().cloned().into_iter().collect()
+                } else {
+                    selected
+                };
+                if !exported.is_empty() {
+                    return Command::Export(export::to_bibtex(&exported));
+                }
+            }
+            Key::Backspace => {
+                if let Some(previous) = self.previous.pop() {
+                    self.listing = previous;
+                    self.detail = false;
+                }
+            }
+            Key::Char(_) => {}
+        }
+        Command::None
+    }
+
+    // the results of a fetch become the current list, the one before kept
+    // for Backspace
+    fn loaded(&mut self, loaded: Result<Vec<ScholarResult>, Error>) {
+        let title = match &self.status {
+            Status::Loading(title) => title.clone(),
+            _ => return,
+        };
+        match loaded {
+            Ok(results) => {
+                let listing = Listing::new(title, results);
+                self.previous
+                    .push(std::mem::replace(&mut self.listing, listing));
+                self.detail = false;
+                self.status = Status::Idle;
+            }
+            Err(e) => self.status = Status::Error(e.user_message().to_string()),
+        }
+    }
+}
+
+impl Listing {
+    fn new(title: String, results: Vec<ScholarResult>) -> Listing {
+        Listing {
+            title,
+            selected: vec![false; results.len()],
+            results,
+            cursor: 0,
+        }
+    }
+}
+
+// Fetches the first page of a Command::CitedBy, for its Event::Loaded
+pub async fn cited_by(client: &Client, cite_id: CiteId) -> Event {
+    Event::Loaded(client.scrape_scholar(&ScholarArgs::cited_by(cite_id)).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scholar::mock::{result_block, results_page_with, MockServer};
+
+    fn result(i: u32) -> ScholarResult {
+        ScholarResult {
+            title: format!("Paper {}", i),
+            authors: vec!["A Author".to_string()],
+            year: Some(2019),
+            link: format!("https://example.org/paper{}", i),
+            cite_id: Some(format!("100{}", i).parse().unwrap()),
+            ..Default::default()
+        }
+    }
+
+    fn browser() -> Browser {
+        Browser::new("graph isomorphism", (0..3).map(result).collect())
+    }
+
+    fn keys(browser: &mut Browser, keys: &[Key]) -> Vec<Command> {
+        keys.iter()
+            .map(|key| browser.update(Event::Key(*key)))
+            .collect()
+    }
+
+    #[test]
+    fn navigation_stays_within_the_list() {
+        let mut browser = browser();
+
+        keys(
+            &mut browser,
+            &[Key::Up, Key::Down, Key::Char('j'), Key::Down],
+        );
+        assert_eq!(browser.cursor(), 2);
+        keys(&mut browser, &[Key::Char('k')]);
+        assert_eq!(browser.current().unwrap().title, "Paper 1");
+
+        assert_eq!(keys(&mut browser, &[Key::Enter]), vec![Command::None]);
+        assert!(browser.detail());
+        keys(&mut browser, &[Key::Esc]);
+        assert!(!browser.detail());
+        assert_eq!(keys(&mut browser, &[Key::Char('q')]), vec![Command::Quit]);
+    }
+
+    #[test]
+    fn opens_the_current_link() {
+        let mut browser = browser();
+
+        let commands = keys(&mut browser, &[Key::Down, Key::Char('o')]);
+
+        assert_eq!(
+            commands[1],
+            Command::Open("https://example.org/paper1".to_string())
+        );
+        let mut empty = Browser::new("nothing", Vec::new());
+        assert_eq!(
+            keys(&mut empty, &[Key::Char('o'), Key::Enter, Key::Char('b')]),
+            vec![Command::None; 3]
+        );
+        assert!(!empty.detail());
+    }
+
+    #[test]
+    fn exports_the_selection() {
+        let mut browser = browser();
+
+        let current = keys(&mut browser, &[Key::Down, Key::Char('b')]);
+        let selection = keys(
+            &mut browser,
+            &[Key::Char(' '), Key::Down, Key::Char(' '), Key::Char('b')],
+        );
+
+        assert_eq!(current[1], Command::Export(export::to_bibtex(&[result(1)])));
+        assert!(browser.is_selected(1) && browser.is_selected(2));
+        assert!(!browser.is_selected(0));
+        assert_eq!(
+            selection[3],
+            Command::Export(export::to_bibtex(&[result(1), result(2)]))
+        );
+    }
+
+    #[test]
+    fn cited_by_loads_in_place() {
+        let mut browser = browser();
+
+        let commands = keys(&mut browser, &[Key::Down, Key::Char('c')]);
+
+        assert_eq!(commands[1], Command::CitedBy("1001".parse().unwrap()));
+        assert_eq!(
+            browser.status(),
+            &Status::Loading("Cited by: Paper 1".to_string())
+        );
+        // keys wait for the fetch, except quitting
+        assert_eq!(keys(&mut browser, &[Key::Down]), vec![Command::None]);
+        assert_eq!(browser.cursor(), 1);
+
+        browser.update(Event::Loaded(Ok(vec![result(7), result(8)])));
+        assert_eq!(browser.status(), &Status::Idle);
+        assert_eq!(browser.title(), "Cited by: Paper 1");
+        assert_eq!(browser.results().len(), 2);
+        assert_eq!(browser.cursor(), 0);
+
+        keys(&mut browser, &[Key::Backspace]);
+        assert_eq!(browser.title(), "graph isomorphism");
+        assert_eq!(browser.cursor(), 1);
+    }
+
+    #[tokio::test]
+    async fn blocked_fetch_shows_a_banner() {
+        let server = MockServer::start(vec![(
+            200,
+            results_page_with(None, vec![result_block(0)]).replace(
+                "<body>",
+                "<body><div id=\"gs_captcha_f\" class=\"gs_captcha\"></div>",
+            ),
+        )])
+        .await;
+        let client = server.client();
+        let mut browser = browser();
+
+        let cite_id = match browser.update(Event::Key(Key::Char('c'))) {
+            Command::CitedBy(cite_id) => cite_id,
+            command => panic!("expected a fetch, got {:?}", command),
+        };
+        browser.update(cited_by(&client, cite_id).await);
+
+        assert_eq!(
+            browser.status(),
+            &Status::Error(Error::Blocked.user_message().to_string())
+        );
+        // the list is kept
+        assert_eq!(browser.title(), "graph isomorphism");
+        keys(&mut browser, &[Key::Esc]);
+        assert_eq!(browser.status(), &Status::Idle);
+    }
+}
@@ -0,0 +1,456 @@
+// Terminal front end of the Browser: keys are read from stdin in raw mode
+// and the whole screen is redrawn with ANSI escapes after every event. The
+// layout is a header with the list's title, the results, the detail pane
+// of the current result when shown, and a status line for the spinner,
+// errors and what the last command did.
+
+use std::fs::OpenOptions;
+use std::io::{self, Read, Write};
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use super::{cited_by, Browser, Command, Event, Key, Status};
+use crate::scholar::{Client, ScholarArgs, ScholarResult};
+
+// file in the working directory `b` appends BibTeX entries to
+pub const EXPORT_FILE: &str = "gscholar.bib";
+
+const SPINNER: &[char] = &['|', '/', '-', '\\'];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+const HELP: &str = "j/k move  Enter details  Space select  o open  c cited by  \
+Backspace back  b BibTeX  q quit";
+
+// Searches `query` with `client` and browses the results until q is
+// pressed. Needs a unix terminal. The thread reading keys stays blocked on
+// stdin after returning, so this is meant to end the program.
+pub async fn run(client: &Client, query: &str) -> io::Result<()> {
+    let _raw = RawMode::enable()?;
+    let mut out = io::stdout();
+    let (key_sender, mut keys) = mpsc::unbounded_channel();
+    std::thread::spawn(move || read_keys(key_sender));
+    let mut ticks = tokio::time::interval(SPINNER_INTERVAL);
+    let mut frame = 0;
+
+    let args = ScholarArgs::new(query);
+    let search = client.scrape_scholar(&args);
+    tokio::pin!(search);
+    let results = loop {
+        tokio::select! {
+            results = &mut search => break results,
+            key = keys.recv() => {
+                if matches!(key, None | Some(Key::Char('q'))) {
+                    return Ok(());
+                }
+            }
+            _ = ticks.tick() => {
+                frame += 1;
+                let line = format!("{} Searching {}", SPINNER[frame % SPINNER.len()], query);
+                draw(&mut out, &[line])?;
+            }
+        }
+    };
+    let mut browser = match results {
+        Ok(results) => Browser::new(query, results),
+        Err(e) => return Err(io::Error::other(e.user_message())),
+    };
+
+    let (loaded_sender, mut loaded) = mpsc::unbounded_channel();
+    let mut notice = None;
+    loop {
+        let (width, height) = terminal_size();
+        draw(
+            &mut out,
+            &render(&browser, frame, notice.as_deref(), width, height),
+        )?;
+        let event = tokio::select! {
+            key = keys.recv() => match key {
+                Some(key) => Event::Key(key),
+                None => return Ok(()),
+            },
+            Some(event) = loaded.recv() => event,
+            _ = ticks.tick() => {
+                frame += 1;
+                continue;
+            }
+        };
+        notice = None;
+        match browser.update(event) {
+            Command::None => {}
+            Command::Open(url) => notice = Some(open_link(&url)),
+            Command::CitedBy(cite_id) => {
+                let client = client.clone();
+                let sender = loaded_sender.clone();
+                tokio::spawn(async move {
+                    let _ = sender.send(cited_by(&client, cite_id).await);
+                });
+            }
+            Command::Export(bibtex) => notice = Some(export_bibtex(&bibtex)),
+            Command::Quit => return Ok(()),
+        }
+    }
+}
+
+// The screen for `browser`, a line per terminal row. `frame` turns the
+// spinner, `notice` is what the last command did, eg: the file exported to.
+pub(crate) fn render(
+    browser: &Browser,
+    frame: usize,
+    notice: Option<&str>,
+    width: usize,
+    height: usize,
+) -> Vec<String> {
+    let results = browser.results();
+    let mut lines = vec![fit(
+        &format!("{} ({} results)", browser.title(), results.len()),
+        width,
+    )];
+
+    let detail = match browser.current() {
+        Some(result) if browser.detail() => detail_lines(result, width, height / 2),
+        _ => Vec::new(),
+    };
+    // header and status line, and the detail pane with its separator
+    let reserved = 2 + if detail.is_empty() {
+        0
+    } else {
+        detail.len() + 1
+    };
+    let rows = height.saturating_sub(reserved).max(1);
+    // scroll so the cursor stays in view
+    let first = (browser.cursor() + 1).saturating_sub(rows);
+    for (i, result) in results.iter().enumerate().skip(first).take(rows) {
+        let cursor = if i == browser.cursor() { '>' } else { ' ' };
+        let selected = if browser.is_selected(i) { '*' } else { ' ' };
+        let year = result.year.map(|y| format!(" ({})", y)).unwrap_or_default();
+        lines.push(fit(
+            &format!("{}{} {}{}", cursor, selected, result.title, year),
+            width,
+        ));
+    }
+    if results.is_empty() {
+        lines.push("  No results".to_string());
+    }
+    if !detail.is_empty() {
+        lines.push("-".repeat(width));
+        lines.extend(detail);
+    }
+    // the status line goes on the last row
+    lines.resize(height.saturating_sub(1).max(lines.len()), String::new());
+    let status = match browser.status() {
+        Status::Loading(message) => {
+            format!("{} {}", SPINNER[frame % SPINNER.len()], message)
+        }
+        Status::Error(message) => format!("Error: {} (Esc to dismiss)", message),
+        Status::Idle => notice.unwrap_or(HELP).to_string(),
+    };
+    lines.push(fit(&status, width));
+    lines
+}
+
+// the detail pane of `result`, at most `max` lines
+fn detail_lines(result: &ScholarResult, width: usize, max: usize) -> Vec<String> {
+    let mut lines = wrap(&result.title, width);
+    if !result.authors.is_empty() {
+        lines.extend(wrap(&result.authors.join(", "), width));
+    }
+    let published = [
+        result.venue.clone(),
+        result.year.map(|year| year.to_string()),
+        result.cited_by.map(|count| format!("cited by {}", count)),
+    ]
+    .iter()
+    .flatten()
+    .cloned()
+    .collect::<Vec<_>>();
+    if !published.is_empty() {
+        lines.push(fit(&published.join(", "), width));
+    }
+    if !result.link.is_empty() {
+        lines.push(fit(&result.link, width));
+    }
+    if !result.abs.is_empty() {
+        lines.push(String::new());
+        lines.extend(wrap(&result.abs, width));
+    }
+    lines.truncate(max.max(1));
+    lines
+}
+
+// `text` cut to `width` characters
+fn fit(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+// `text` broken between words into lines of at most `width` characters
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let len = line.chars().count();
+        if len > 0 && len + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines.into_iter().map(|line| fit(&line, width)).collect()
+}
+
+// The keys in a chunk read from a raw mode terminal, eg: "\x1b[A" is Up.
+// Escape sequences other than the arrows are dropped, Ctrl-C quits like q.
+pub(crate) fn decode_keys(input: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(input);
+    let mut chars = text.chars().peekable();
+    let mut keys = Vec::new();
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' if matches!(chars.peek(), Some('[') | Some('O')) => {
+                chars.next();
+                // parameters, then the final letter or ~
+                let last = chars.find(|c| c.is_ascii_alphabetic() || *c == '~');
+                match last {
+                    Some('A') => Key::Up,
+                    Some('B') => Key::Down,
+                    _ => continue,
+                }
+            }
+            '\x1b' => Key::Esc,
+            '\r' | '\n' => Key::Enter,
+            '\x7f' | '\x08' => Key::Backspace,
+            '\x03' => Key::Char('q'),
+            c if c.is_control() => continue,
+            c => Key::Char(c),
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+fn read_keys(keys: mpsc::UnboundedSender<Key>) {
+    let mut stdin = io::stdin();
+    let mut buf = [0u8; 64];
+    loop {
+        let read = match stdin.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(read) => read,
+        };
+        for key in decode_keys(&buf[..read]) {
+            if keys.send(key).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn draw(out: &mut impl Write, lines: &[String]) -> io::Result<()> {
+    // raw mode doesn't turn \n into \r\n
+    write!(out, "\x1b[H\x1b[2J{}", lines.join("\r\n"))?;
+    out.flush()
+}
+
+// opens `url` with the desktop's default browser
+fn open_link(url: &str) -> String {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    let spawned = std::process::Command::new(opener)
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match spawned {
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+            format!("Opened {}", url)
+        }
+        Err(e) => format!("Could not open {}: {}", url, e),
+    }
+}
+
+fn export_bibtex(bibtex: &str) -> String {
+    let written = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(EXPORT_FILE)
+        .and_then(|mut file| file.write_all(bibtex.as_bytes()));
+    let entries = bibtex.lines().filter(|line| line.starts_with('@')).count();
+    match written {
+        Ok(()) => format!("Exported {} entries to {}", entries, EXPORT_FILE),
+        Err(e) => format!("Could not write {}: {}", EXPORT_FILE, e),
+    }
+}
+
+// (columns, rows) of the terminal, 80x24 when it can't tell
+#[cfg(unix)]
+fn terminal_size() -> (usize, usize) {
+    // SAFETY: TIOCGWINSZ only writes the winsize it is given
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+    if ok && size.ws_col > 0 && size.ws_row > 0 {
+        (usize::from(size.ws_col), usize::from(size.ws_row))
+    } else {
+        (80, 24)
+    }
+}
+
+#[cfg(not(unix))]
+fn terminal_size() -> (usize, usize) {
+    (80, 24)
+}
+
+// The terminal in raw mode on the alternate screen, as it was once dropped
+#[cfg(unix)]
+struct RawMode(libc::termios);
+
+#[cfg(unix)]
+impl RawMode {
+    fn enable() -> io::Result<RawMode> {
+        // SAFETY: tcgetattr fills the termios it is given, tcsetattr reads it
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let saved = termios;
+            libc::cfmakeraw(&mut termios);
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // alternate screen, hidden cursor
+            print!("\x1b[?1049h\x1b[?25l");
+            Ok(RawMode(saved))
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        // SAFETY: restores the termios tcgetattr gave
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct RawMode;
+
+#[cfg(not(unix))]
+impl RawMode {
+    fn enable() -> io::Result<RawMode> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the terminal browser needs a unix terminal",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn browser() -> Browser {
+        let result = |i: u32| ScholarResult {
+            title: format!("Paper {}", i),
+            authors: vec!["A Author".to_string(), "B Author".to_string()],
+            venue: Some("Journal of Examples".to_string()),
+            year: Some(2019),
+            cited_by: Some(i),
+            link: format!("https://example.org/paper{}", i),
+            abs: "An abstract long enough to wrap over two lines".to_string(),
+            cite_id: Some(format!("100{}", i).parse().unwrap()),
+            ..Default::default()
+        };
+        Browser::new("graph isomorphism", (0..6).map(result).collect())
+    }
+
+    #[test]
+    fn decode_terminal_input() {
+        assert_eq!(
+            decode_keys(b"jk\x1b[A\x1bOB\r \x7f\x1b"),
+            vec![
+                Key::Char('j'),
+                Key::Char('k'),
+                Key::Up,
+                Key::Down,
+                Key::Enter,
+                Key::Char(' '),
+                Key::Backspace,
+                Key::Esc,
+            ]
+        );
+        // other sequences, eg: Page Down and Right, are dropped
+        assert_eq!(decode_keys(b"\x1b[6~\x1b[Cq"), vec![Key::Char('q')]);
+        assert_eq!(decode_keys(b"\x03"), vec![Key::Char('q')]);
+        assert_eq!(decode_keys("é".as_bytes()), vec![Key::Char('é')]);
+    }
+
+    #[test]
+    fn list_scrolls_to_the_cursor() {
+        let mut browser = browser();
+        for _ in 0..4 {
+            browser.update(Event::Key(Key::Down));
+        }
+        browser.update(Event::Key(Key::Char(' ')));
+
+        let screen = render(&browser, 0, None, 30, 5);
+
+        assert_eq!(
+            screen,
+            vec![
+                "graph isomorphism (6 results)",
+                "   Paper 2 (2019)",
+                "   Paper 3 (2019)",
+                ">* Paper 4 (2019)",
+                &HELP[..30],
+            ]
+        );
+    }
+
+    #[test]
+    fn detail_pane_and_status() {
+        let mut browser = browser();
+        browser.update(Event::Key(Key::Enter));
+
+        let screen = render(&browser, 0, Some("Opened a link"), 30, 14);
+
+        assert_eq!(
+            screen,
+            vec![
+                "graph isomorphism (6 results)",
+                ">  Paper 0 (2019)",
+                "   Paper 1 (2019)",
+                "   Paper 2 (2019)",
+                "   Paper 3 (2019)",
+                "------------------------------",
+                "Paper 0",
+                "A Author, B Author",
+                "Journal of Examples, 2019, cit",
+                "https://example.org/paper0",
+                "",
+                "An abstract long enough to",
+                "wrap over two lines",
+                "Opened a link",
+            ]
+        );
+
+        browser.update(Event::Key(Key::Char('c')));
+        let screen = render(&browser, 1, None, 30, 14);
+        assert_eq!(screen.last().unwrap(), "/ Cited by: Paper 0");
+    }
+}
@@ -0,0 +1,306 @@
+// Canonical form of result links, so the same paper reached through
+// different tracking urls compares equal.
+
+// query parameters dropped by canonicalize, a trailing * matches a prefix.
+// Compared ignoring case.
+pub const TRACKING_PARAMS: &[&str] = &[
+    "casa_token",
+    "utm_*",
+    "gclid",
+    "dclid",
+    "fbclid",
+    "msclkid",
+    "mc_cid",
+    "mc_eid",
+    "_hsenc",
+    "_hsmi",
+    "jsessionid",
+    "phpsessid",
+    "sessionid",
+];
+
+// canonicalize_with the default TRACKING_PARAMS
+pub fn canonicalize(url: &str) -> String {
+    canonicalize_with(url, TRACKING_PARAMS)
+}
+
+// Lowercases the scheme and host, drops the default port, the fragment and
+// the query parameters matching `tracking`, and sorts the remaining query
+// pairs. The path is left as is. Urls that do not parse are returned
+// unchanged.
+pub fn canonicalize_with(url: &str, tracking: &[&str]) -> String {
+    let mut parsed = match url::Url::parse(url.trim()) {
+        Ok(parsed) => parsed,
+        Err(_) => return url.to_string(),
+    };
+    parsed.set_fragment(None);
+
+    let query = parsed.query().map(|query| {
+        let mut pairs = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .filter(|pair| {
+                let name = pair.split('=').next().unwrap_or_default();
+                !is_tracking(name, tracking)
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs.join("&")
+    });
+    match query {
+        Some(query) if !query.is_empty() => parsed.set_query(Some(&query)),
+        _ => parsed.set_query(None),
+    }
+    parsed.to_string()
+}
+
+fn is_tracking(name: &str, tracking: &[&str]) -> bool {
+    let name = name.to_lowercase();
+    tracking.iter().any(|pattern| {
+        let pattern = pattern.to_lowercase();
+        match pattern.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == pattern,
+        }
+    })
+}
+
+// first "10.<registrant>/<suffix>" in `text`, lowercase, the suffix ending
+// at whitespace, ? or #
+pub(crate) fn find_doi(text: &str) -> Option<String> {
+    let mut from = 0;
+    while let Some(found) = text[from..].find("10.") {
+        let start = from + found;
+        from = start + 3;
+        let boundary = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric());
+        let rest = &text[start + 3..];
+        let registrant = rest.chars().take_while(char::is_ascii_digit).count();
+        if !boundary || registrant < 4 || !rest[registrant..].starts_with('/') {
+            continue;
+        }
+        let suffix = &rest[registrant + 1..];
+        let end = suffix
+            .find(|c: char| c.is_whitespace() || c == '?' || c == '#')
+            .unwrap_or(suffix.len());
+        let suffix = suffix[..end].trim_end_matches(['.', ',', ';', '/']);
+        if !suffix.is_empty() {
+            return Some(format!("10.{}/{}", &rest[..registrant], suffix).to_lowercase());
+        }
+    }
+    None
+}
+
+// PubMed id in a PubMed url, eg: "https://pubmed.ncbi.nlm.nih.gov/12345678/"
+// or the older "https://www.ncbi.nlm.nih.gov/pubmed/12345678" and
+// "https://www.ncbi.nlm.nih.gov/pubmed/?term=12345678"
+pub(crate) fn find_pmid(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url.trim()).ok()?;
+    let (host, segments) = host_and_segments(&parsed)?;
+    let id = match (host.as_str(), segments.as_slice()) {
+        ("pubmed.ncbi.nlm.nih.gov", [id, ..]) => id.to_string(),
+        ("ncbi.nlm.nih.gov", ["pubmed", id, ..]) => id.to_string(),
+        ("ncbi.nlm.nih.gov", ["pubmed"]) => parsed
+            .query_pairs()
+            .find(|(name, _)| name == "term")?
+            .1
+            .trim()
+            .to_string(),
+        ("europepmc.org", ["abstract" | "article", "MED", id, ..]) => id.to_string(),
+        _ => return None,
+    };
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then_some(id)
+}
+
+// PubMed Central id in a PMC url, as "PMC" and digits, eg: from
+// "https://www.ncbi.nlm.nih.gov/pmc/articles/PMC1234567/" or the newer
+// "https://pmc.ncbi.nlm.nih.gov/articles/PMC1234567/"
+pub(crate) fn find_pmcid(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url.trim()).ok()?;
+    let (host, segments) = host_and_segments(&parsed)?;
+    let id = match (host.as_str(), segments.as_slice()) {
+        ("ncbi.nlm.nih.gov", ["pmc", "articles", id, ..]) => *id,
+        ("pmc.ncbi.nlm.nih.gov", ["articles", id, ..]) => *id,
+        ("europepmc.org", ["article", "PMC", id, ..]) => *id,
+        ("europepmc.org", ["articles", id, ..]) => *id,
+        _ => return None,
+    };
+    let digits = id.get(..3)?.eq_ignore_ascii_case("pmc").then(|| &id[3..])?;
+    (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+        .then(|| format!("PMC{}", digits))
+}
+
+// lowercase host without "www.", and the non empty path segments
+fn host_and_segments(url: &url::Url) -> Option<(String, Vec<&str>)> {
+    let host = url.host_str()?;
+    let host = host.strip_prefix("www.").unwrap_or(host).to_string();
+    let segments = url
+        .path_segments()?
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    Some((host, segments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_dois() {
+        assert_eq!(
+            find_doi("https://dl.acm.org/doi/abs/10.1145/3065386?download=true").as_deref(),
+            Some("10.1145/3065386")
+        );
+        assert_eq!(
+            find_doi("https://link.springer.com/article/10.1007/S11192-015-1765-5").as_deref(),
+            Some("10.1007/s11192-015-1765-5")
+        );
+        assert_eq!(find_doi("https://arxiv.org/abs/1706.03762"), None);
+        assert_eq!(find_doi("version 110.1234/5"), None);
+    }
+
+    #[test]
+    fn find_pubmed_ids() {
+        let cases = [
+            (
+                "https://pubmed.ncbi.nlm.nih.gov/31978945/",
+                Some("31978945"),
+                None,
+            ),
+            (
+                "https://pubmed.ncbi.nlm.nih.gov/31978945",
+                Some("31978945"),
+                None,
+            ),
+            (
+                "https://www.ncbi.nlm.nih.gov/pubmed/31978945",
+                Some("31978945"),
+                None,
+            ),
+            (
+                "http://ncbi.nlm.nih.gov/pubmed/31978945/",
+                Some("31978945"),
+                None,
+            ),
+            (
+                "https://www.ncbi.nlm.nih.gov/pubmed/?term=31978945",
+                Some("31978945"),
+                None,
+            ),
+            (
+                "https://europepmc.org/abstract/MED/31978945",
+                Some("31978945"),
+                None,
+            ),
+            (
+                "https://www.ncbi.nlm.nih.gov/pubmed/?term=covid",
+                None,
+                None,
+            ),
+            ("https://pubmed.ncbi.nlm.nih.gov/?term=covid", None, None),
+            (
+                "https://www.ncbi.nlm.nih.gov/pmc/articles/PMC7092803/",
+                None,
+                Some("PMC7092803"),
+            ),
+            (
+                "https://www.ncbi.nlm.nih.gov/pmc/articles/pmc7092803/pdf/main.pdf",
+                None,
+                Some("PMC7092803"),
+            ),
+            (
+                "https://pmc.ncbi.nlm.nih.gov/articles/PMC7092803/",
+                None,
+                Some("PMC7092803"),
+            ),
+            (
+                "https://europepmc.org/article/PMC/PMC7092803",
+                None,
+                Some("PMC7092803"),
+            ),
+            ("https://www.ncbi.nlm.nih.gov/pmc/articles/PMC/", None, None),
+            ("https://www.ncbi.nlm.nih.gov/books/NBK1234/", None, None),
+            ("https://example.org/pubmed/31978945", None, None),
+            ("not a url", None, None),
+        ];
+        for (url, pmid, pmcid) in cases {
+            assert_eq!(find_pmid(url).as_deref(), pmid, "{}", url);
+            assert_eq!(find_pmcid(url).as_deref(), pmcid, "{}", url);
+        }
+    }
+
+    #[test]
+    fn canonicalize_publisher_urls() {
+        let cases = [
+            (
+                "https://www.sciencedirect.com/science/article/pii/S0004370299000521?casa_token=AbC123:xyz",
+                "https://www.sciencedirect.com/science/article/pii/S0004370299000521",
+            ),
+            (
+                "HTTPS://IEEEXplore.IEEE.org:443/abstract/document/726791/",
+                "https://ieeexplore.ieee.org/abstract/document/726791/",
+            ),
+            (
+                "http://dl.acm.org:80/doi/abs/10.1145/3065386#sec-abstract",
+                "http://dl.acm.org/doi/abs/10.1145/3065386",
+            ),
+            (
+                "https://www.nature.com/articles/nature14539?utm_source=twitter&utm_medium=social&error=cookies_not_supported",
+                "https://www.nature.com/articles/nature14539?error=cookies_not_supported",
+            ),
+            (
+                "https://link.springer.com/article/10.1007/BF00994018?gclid=EAIaIQ&UTM_Campaign=x",
+                "https://link.springer.com/article/10.1007/BF00994018",
+            ),
+            (
+                "https://arxiv.org/abs/1706.03762",
+                "https://arxiv.org/abs/1706.03762",
+            ),
+            (
+                "https://arxiv.org/pdf/1706.03762.pdf#page=3",
+                "https://arxiv.org/pdf/1706.03762.pdf",
+            ),
+            (
+                "https://onlinelibrary.wiley.com/doi/full/10.1002/asi.21234?b=2&a=1&a=0",
+                "https://onlinelibrary.wiley.com/doi/full/10.1002/asi.21234?a=0&a=1&b=2",
+            ),
+            (
+                "https://www.jstor.org/stable/2346101?seq=1&PHPSESSID=deadbeef&fbclid=IwAR0",
+                "https://www.jstor.org/stable/2346101?seq=1",
+            ),
+            (
+                "https://academic.oup.com/bioinformatics/article/25/14/1754/225615?login=false&&",
+                "https://academic.oup.com/bioinformatics/article/25/14/1754/225615?login=false",
+            ),
+            (
+                "https://Journals.PLOS.org/plosone/article?id=10.1371/journal.pone.0000001",
+                "https://journals.plos.org/plosone/article?id=10.1371/journal.pone.0000001",
+            ),
+            (
+                "https://www.researchgate.net:8443/publication/Paper_Title",
+                "https://www.researchgate.net:8443/publication/Paper_Title",
+            ),
+            ("https://example.org", "https://example.org/"),
+            ("not a url", "not a url"),
+        ];
+        for (url, expected) in cases.iter() {
+            assert_eq!(canonicalize(url), *expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn canonicalize_custom_list() {
+        let url = "https://example.org/paper?ref=feed&id=7&utm_source=x";
+
+        assert_eq!(
+            canonicalize_with(url, &["ref"]),
+            "https://example.org/paper?id=7&utm_source=x"
+        );
+        assert_eq!(
+            canonicalize_with(url, &[]),
+            "https://example.org/paper?id=7&ref=feed&utm_source=x"
+        );
+    }
+}
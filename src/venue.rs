@@ -0,0 +1,180 @@
+// Journal abbreviation expansion for the venue shown in Scholar bylines.
+//
+// The bundled table, venue_abbreviations.tsv, follows ISO 4 / LTWA
+// abbreviations. Lookups are case-insensitive and ignore periods, commas and
+// colons, so "IEEE Trans Pattern Anal Mach Intell" and "ieee trans. pattern
+// anal. mach. intell." are the same key.
+// Entries added with `register` take precedence over the bundled ones.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+// tab separated (ISO 4 abbreviation, full title) pairs, # starts a comment
+const BUNDLED: &str = include_str!("venue_abbreviations.tsv");
+
+fn bundled() -> impl Iterator<Item = (&'static str, &'static str)> {
+    BUNDLED
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('\t'))
+}
+
+struct Table {
+    // normalized abbreviation -> full title
+    expansions: HashMap<String, String>,
+    // normalized full title -> abbreviation
+    abbreviations: HashMap<String, String>,
+}
+
+impl Table {
+    fn insert(&mut self, abbrev: &str, full: &str) {
+        self.expansions.insert(normalize(abbrev), full.to_string());
+        self.abbreviations
+            .insert(normalize(full), abbrev.to_string());
+    }
+}
+
+fn table() -> &'static RwLock<Table> {
+    static TABLE: OnceLock<RwLock<Table>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = Table {
+            expansions: HashMap::new(),
+            abbreviations: HashMap::new(),
+        };
+        for (abbrev, full) in bundled() {
+            table.insert(abbrev, full);
+        }
+        RwLock::new(table)
+    })
+}
+
+fn normalize(venue: &str) -> String {
+    venue
+        .split_whitespace()
+        .map(|word| word.replace(&['.', ',', ':'][..], "").to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Full title for an abbreviated venue, eg: "Commun. ACM" -> "Communications
+// of the ACM"
+pub fn expand(abbrev: &str) -> Option<String> {
+    let table = table().read().unwrap_or_else(|e| e.into_inner());
+    table.expansions.get(&normalize(abbrev)).cloned()
+}
+
+// ISO 4 abbreviation for a full venue title, eg: "Journal of the ACM" ->
+// "J. ACM"
+pub fn abbreviate(full: &str) -> Option<String> {
+    let table = table().read().unwrap_or_else(|e| e.into_inner());
+    table.abbreviations.get(&normalize(full)).cloned()
+}
+
+// Adds or overrides an abbreviation at runtime
+pub fn register(abbrev: &str, full: &str) {
+    let mut table = table().write().unwrap_or_else(|e| e.into_inner());
+    table.insert(abbrev, full);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_known_venues() {
+        let cases = [
+            (
+                "IEEE Trans. Pattern Anal. Mach. Intell.",
+                "IEEE Transactions on Pattern Analysis and Machine Intelligence",
+            ),
+            (
+                "IEEE Trans Pattern Anal Mach Intell",
+                "IEEE Transactions on Pattern Analysis and Machine Intelligence",
+            ),
+            ("commun. acm", "Communications of the ACM"),
+            ("J Mach Learn Res", "Journal of Machine Learning Research"),
+            ("Nat. Biotechnol.", "Nature Biotechnology"),
+            ("nucleic acids res", "Nucleic Acids Research"),
+            (
+                "Proc Natl Acad Sci USA",
+                "Proceedings of the National Academy of Sciences of the United States of America",
+            ),
+            ("N Engl J Med", "New England Journal of Medicine"),
+        ];
+        for (abbrev, full) in cases.iter() {
+            assert_eq!(
+                expand(abbrev).as_deref(),
+                Some(*full),
+                "expanding {}",
+                abbrev
+            );
+        }
+    }
+
+    #[test]
+    fn abbreviate_known_venues() {
+        let cases = [
+            ("Journal of the ACM", "J. ACM"),
+            ("ACM COMPUTING SURVEYS", "ACM Comput. Surv."),
+            (
+                "International Journal of Computer Vision",
+                "Int. J. Comput. Vis.",
+            ),
+            ("Genome Research", "Genome Res."),
+            ("PLoS Computational Biology", "PLoS Comput. Biol."),
+        ];
+        for (full, abbrev) in cases.iter() {
+            assert_eq!(
+                abbreviate(full).as_deref(),
+                Some(*abbrev),
+                "abbreviating {}",
+                full
+            );
+        }
+    }
+
+    #[test]
+    fn bundled_venues_round_trip() {
+        assert!(bundled().count() > 2000);
+        for (abbrev, full) in bundled() {
+            assert_eq!(
+                expand(abbrev).as_deref(),
+                Some(full),
+                "expanding {}",
+                abbrev
+            );
+            assert_eq!(
+                abbreviate(full).as_deref(),
+                Some(abbrev),
+                "abbreviating {}",
+                full
+            );
+        }
+        assert_eq!(
+            abbreviate("Journal of Physics Condensed Matter").as_deref(),
+            Some("J. Phys. Condens. Matter")
+        );
+    }
+
+    #[test]
+    fn unknown_venues() {
+        assert_eq!(expand("Not. A. Real. Venue."), None);
+        assert_eq!(abbreviate("Journal of Imaginary Results"), None);
+        assert_eq!(expand(""), None);
+    }
+
+    #[test]
+    fn register_at_runtime() {
+        assert_eq!(expand("J. Test. Venues"), None);
+        register("J. Test. Venues", "Journal of Test Venues");
+        assert_eq!(
+            expand("j test venues").as_deref(),
+            Some("Journal of Test Venues")
+        );
+        assert_eq!(
+            abbreviate("journal of test venues").as_deref(),
+            Some("J. Test. Venues")
+        );
+    }
+}
@@ -0,0 +1,152 @@
+// The smallest ZIP archive writer export::write_xlsx needs: every file is
+// stored uncompressed, which spreadsheet applications read as any other
+// archive, at the cost of larger files.
+
+// files of the archive in order, by name
+#[derive(Default)]
+pub(crate) struct ZipWriter {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl ZipWriter {
+    pub(crate) fn add(&mut self, name: &str, data: impl Into<Vec<u8>>) {
+        self.files.push((name.to_string(), data.into()));
+    }
+
+    // The archive, with every file dated 1980-01-01, the earliest date the
+    // format has, so the same files always give the same bytes
+    pub(crate) fn finish(self) -> Vec<u8> {
+        let mut archive = Vec::new();
+        let mut directory = Vec::new();
+        for (name, data) in &self.files {
+            let offset = archive.len() as u32;
+            let crc = crc32(data);
+            let size = data.len() as u32;
+
+            archive.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+            file_header(&mut archive, name, crc, size);
+            archive.extend_from_slice(name.as_bytes());
+            archive.extend_from_slice(data);
+
+            directory.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+            // made by: version 2.0
+            directory.extend_from_slice(&20_u16.to_le_bytes());
+            file_header(&mut directory, name, crc, size);
+            // comment length, disk number, internal and external attributes
+            directory.extend_from_slice(&[0; 10]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+
+        let directory_offset = archive.len() as u32;
+        let count = self.files.len() as u16;
+        archive.extend_from_slice(&directory);
+        archive.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+        // this disk and the disk the directory starts on
+        archive.extend_from_slice(&[0; 4]);
+        archive.extend_from_slice(&count.to_le_bytes());
+        archive.extend_from_slice(&count.to_le_bytes());
+        archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&directory_offset.to_le_bytes());
+        // comment length
+        archive.extend_from_slice(&[0; 2]);
+        archive
+    }
+}
+
+// the fields local file headers and central directory entries share, from
+// the version needed to extract to the extra field length
+fn file_header(out: &mut Vec<u8>, name: &str, crc: u32, size: u32) {
+    // version needed: 2.0, flags: utf-8 names, method: stored
+    out.extend_from_slice(&20_u16.to_le_bytes());
+    out.extend_from_slice(&0x0800_u16.to_le_bytes());
+    out.extend_from_slice(&0_u16.to_le_bytes());
+    // modification time 00:00:00 and date 1980-01-01
+    out.extend_from_slice(&0_u16.to_le_bytes());
+    out.extend_from_slice(&0x0021_u16.to_le_bytes());
+    out.extend_from_slice(&crc.to_le_bytes());
+    // compressed and uncompressed size, the same when stored
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    // extra field length
+    out.extend_from_slice(&0_u16.to_le_bytes());
+}
+
+// CRC-32 as ZIP uses it (IEEE 802.3, reflected)
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+// the files of an archive ZipWriter made, checking their CRC
+#[cfg(test)]
+pub(crate) fn read_stored(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let u16_at = |at: usize| u16::from_le_bytes([archive[at], archive[at + 1]]) as usize;
+    let u32_at = |at: usize| {
+        u32::from_le_bytes([
+            archive[at],
+            archive[at + 1],
+            archive[at + 2],
+            archive[at + 3],
+        ])
+    };
+    let mut files = Vec::new();
+    let mut at = 0;
+    while u32_at(at) == 0x0403_4b50 {
+        assert_eq!(u16_at(at + 8), 0, "stored");
+        let crc = u32_at(at + 14);
+        let size = u32_at(at + 18) as usize;
+        let name_len = u16_at(at + 26);
+        let data_at = at + 30 + name_len + u16_at(at + 28);
+        let name = String::from_utf8(archive[at + 30..at + 30 + name_len].to_vec()).unwrap();
+        let data = archive[data_at..data_at + size].to_vec();
+        assert_eq!(crc32(&data), crc, "crc of {}", name);
+        files.push((name, data));
+        at = data_at + size;
+    }
+    // the end of central directory record counts them all
+    let end = archive.len() - 22;
+    assert_eq!(u32_at(end), 0x0605_4b50);
+    assert_eq!(u16_at(end + 10), files.len());
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn stored_archive_round_trip() {
+        let mut zip = ZipWriter::default();
+        zip.add("a.txt", "hello");
+        zip.add("dir/b.xml", "<b/>");
+
+        let archive = zip.finish();
+
+        assert!(archive.starts_with(b"PK\x03\x04"));
+        assert_eq!(
+            read_stored(&archive),
+            vec![
+                ("a.txt".to_string(), b"hello".to_vec()),
+                ("dir/b.xml".to_string(), b"<b/>".to_vec()),
+            ]
+        );
+    }
+}